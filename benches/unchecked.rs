@@ -0,0 +1,41 @@
+//! compares the speed of the checked and unchecked lookup tables when running
+//! the summation functions
+//!
+//! run with `cargo bench --bench unchecked`
+
+use std::time::Instant;
+
+use train_sim::summation::{self, InterpolateLookup, UncheckedLookup};
+use train_sim::time::Timing;
+
+const LENGTH: usize = 100_000;
+const STEP: u32 = 100;
+const ITERATIONS: u32 = 20;
+
+fn main() {
+    let lookup = (0..LENGTH)
+        .map(|x| (x as f64 / 100.0).sin())
+        .collect::<Vec<f64>>();
+    let lookup = InterpolateLookup::from(lookup);
+    let unchecked = UncheckedLookup::new(&lookup).unwrap();
+
+    let mut checked_timing = Timing::default();
+    let mut unchecked_timing = Timing::default();
+
+    for _ in 0..ITERATIONS {
+        let start = Instant::now();
+        let checked_sum =
+            summation::sum_panels(LENGTH, STEP, &lookup, summation::simpsons).unwrap();
+        checked_timing.update(start.elapsed());
+
+        let start = Instant::now();
+        let unchecked_sum =
+            summation::sum_panels(LENGTH, STEP, &unchecked, summation::simpsons).unwrap();
+        unchecked_timing.update(start.elapsed());
+
+        assert_eq!(checked_sum.to_bits(), unchecked_sum.to_bits());
+    }
+
+    println!("checked\n{checked_timing}");
+    println!("unchecked\n{unchecked_timing}");
+}
@@ -1,10 +1,16 @@
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::io::{BufRead, BufReader, Read};
 use std::path::PathBuf;
 use std::str::FromStr;
+use std::time::Duration;
 
 use anyhow::Context;
 use clap::{Args, Parser, Subcommand, ValueEnum};
+use serde::Deserialize;
 
-use crate::summation::{self, InterpolateLookup};
+use crate::filter::SavGol;
+use crate::summation::{self, Callable, FourierSeries, FourierTerm, InterpolateLookup};
+use crate::{batch, integrator, output, time};
 
 /// an application for running "train" simulations of a given acceleration
 /// profile that will calculate the final velocity and position of the train
@@ -37,108 +43,2878 @@ pub struct SimOpts {
     /// calculation
     #[arg(short, long, default_value("100"))]
     pub step: u32,
-}
 
-/// the available summation algorithms that the simulation is capable of
-/// running
-#[derive(Debug, Clone, ValueEnum)]
-pub enum AppAlgo {
-    LeftRiemann,
-    MidRiemann,
-    RightRiemann,
-    Trapezoidal,
-    Simpsons,
-}
+    /// the error tolerance of each unit interval for the adaptive-simpson
+    /// algorithm
+    #[arg(long, default_value("1e-6"))]
+    pub tolerance: f64,
 
-/// the different kins of simulations available for the program to run
-///
-/// currently the only supported kind is loading data from a csv file
-#[derive(Debug, Subcommand)]
-pub enum SimKind {
-    /// runs a simulation from a given acceleration profile
-    Csv(CsvSim),
-}
+    /// the amount of trapezoidal refinements of each unit interval for the
+    /// romberg algorithm
+    #[arg(long, default_value("5"), value_parser = clap::value_parser!(u32).range(0..=30))]
+    pub levels: u32,
 
-/// options for running a simulation from a specified csv file
-#[derive(Debug, Args)]
-pub struct CsvSim {
-    /// loads acceleration data in a specific column from the csv file
+    /// specifies how the velocity and position are calculated. the coupled
+    /// integrators advance both together which is needed when the
+    /// acceleration depends on the velocity, like with drag
+    #[arg(long, default_value("quadrature"))]
+    pub integrator: AppIntegrator,
+
+    /// linear drag coefficient applied to the velocity when using a coupled
+    /// integrator. the resulting acceleration is `a(t) - drag * v`
+    #[arg(long, default_value("0"))]
+    pub drag: f64,
+
+    /// what each value of the acceleration profile is indexed by. distance
+    /// requires a coupled integrator
+    #[arg(long, default_value("time"))]
+    pub accel_index: AppProfileIndex,
+
+    /// a csv file with the grade of the track that adds the gravity component
+    /// of the grade to the acceleration. positive values are uphill. requires
+    /// a coupled integrator
     #[arg(long)]
-    pub column: Option<String>,
+    pub grade_csv: Option<PathBuf>,
 
-    /// the csv file path to load
-    pub path: PathBuf,
-}
+    /// the unit of the values in the grade csv
+    #[arg(long, default_value("percent"))]
+    pub grade_unit: GradeUnit,
 
-impl CsvSim {
-    /// retrieves the path of the specified csv file
-    ///
-    /// if the given path is relative then it will be resolved using the
-    /// current working directory
-    fn get_path(&self) -> anyhow::Result<PathBuf> {
-        if self.path.is_relative() {
-            let cwd =
-                std::env::current_dir().context("failed to retrieve current working directory")?;
+    /// what each row of the grade csv is indexed by
+    #[arg(long, default_value("distance"))]
+    pub grade_index: AppProfileIndex,
 
-            Ok(cwd.join(&self.path))
+    /// the time in seconds of the profile to start the simulation at. the
+    /// simulation starts from rest at this time
+    #[arg(long)]
+    pub t_start: Option<f64>,
+
+    /// the time in seconds of the profile to end the simulation at
+    #[arg(long)]
+    pub t_end: Option<f64>,
+
+    /// the largest amount of seconds allowed between the rows of a profile
+    /// with a time column. interpolating across a larger gap fails instead
+    /// of guessing the missing data
+    #[arg(long)]
+    pub max_gap: Option<f64>,
+
+    /// whether the integration extends through the trailing interval of the
+    /// last sample. inclusive holds the last sample for one more second
+    #[arg(long, default_value("exclusive"))]
+    pub endpoint: Endpoint,
+
+    /// gives each thread the same contiguous range of seconds on every run
+    /// instead of letting the threads steal work from each other
+    #[arg(long)]
+    pub repeatable_threads: bool,
+
+    /// the velocity of the train at the start of the simulation
+    #[arg(long, default_value("0"), allow_negative_numbers(true))]
+    pub initial_velocity: f64,
+
+    /// the position of the train at the start of the simulation
+    #[arg(long, default_value("0"), allow_negative_numbers(true))]
+    pub initial_position: f64,
+
+    /// caps the velocity at the given value during the integration. any
+    /// acceleration that would go over the cap is discarded
+    #[arg(long, allow_negative_numbers(true))]
+    pub max_velocity: Option<f64>,
+
+    /// clamps the velocity at zero during the integration so that a stopped
+    /// train does not reverse
+    #[arg(long)]
+    pub clamp_velocity_nonneg: bool,
+
+    /// stops the simulation with an error if the velocity goes negative
+    #[arg(long, conflicts_with("clamp_velocity_nonneg"))]
+    pub error_on_negative_velocity: bool,
+
+    /// specifies the amount of steps to take when calculating the velocity.
+    /// defaults to the value of step
+    #[arg(long)]
+    pub velocity_step: Option<u32>,
+
+    /// specifies the amount of steps to take when calculating the position.
+    /// defaults to the value of step
+    #[arg(long)]
+    pub position_step: Option<u32>,
+
+    /// subdivides each unit interval of the velocity pass based on the
+    /// curvature of the profile, using between step and the given amount of
+    /// steps per interval
+    #[arg(
+        long,
+        conflicts_with("quadrature_file"),
+        value_parser = clap::value_parser!(u32).range(1..)
+    )]
+    pub adaptive_max_step: Option<u32>,
+
+    /// adds gaussian noise with the given standard deviation to the profile
+    /// on every iteration and prints the distribution of the final results
+    #[arg(long)]
+    pub monte_carlo: Option<f64>,
+
+    /// the seed for the random noise of a monte carlo run
+    #[arg(long, default_value("0"))]
+    pub seed: u64,
+
+    /// runs the simulation with both single and double precision values and
+    /// prints the difference between the final results
+    #[arg(long)]
+    pub compare_precision: bool,
+
+    /// runs the simulation with each available interpolation method and
+    /// prints a table of the final results
+    #[arg(long)]
+    pub compare_interp: bool,
+
+    /// runs the simulation with each available summation algorithm and
+    /// prints a table of the final results and average iteration time
+    #[arg(long)]
+    pub all_algos: bool,
+
+    /// shifts the profile by a small fraction of its mean magnitude and
+    /// prints how much the final results change relative to the shift
+    #[arg(long)]
+    pub stability_check: bool,
+
+    /// splits the profile into the given amount of regions and prints the
+    /// estimated error of the velocity pass in each one. the error is the
+    /// difference from the same region calculated with double the step
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    pub error_regions: Option<u32>,
+
+    /// prints the L2 norm of the acceleration profile using the selected
+    /// summation algorithm
+    #[arg(long)]
+    pub accel_l2: bool,
+
+    /// prints the signed integral of the acceleration profile along with the
+    /// integral of its absolute value
+    #[arg(long)]
+    pub report_abs_integral: bool,
+
+    /// prints the integral of the absolute jerk, the finite difference of the
+    /// acceleration profile, using the selected summation algorithm
+    #[arg(long)]
+    pub integrate_abs_jerk: bool,
+
+    /// prints the time weighted average of the acceleration profile using
+    /// the selected summation algorithm
+    #[arg(long)]
+    pub time_average: bool,
+
+    /// the mass of the train used to calculate forces from the acceleration
+    #[arg(long)]
+    pub mass: Option<f64>,
+
+    /// prints the total mechanical work done by integrating the force times
+    /// the velocity over time. requires mass
+    #[arg(long, requires("mass"))]
+    pub report_work: bool,
+
+    /// prints the times that the velocity crosses zero calculated on the
+    /// final iteration
+    #[arg(long)]
+    pub report_stops: bool,
+
+    /// uses compensated summation when accumulating the values of the
+    /// summation algorithm
+    #[arg(long)]
+    pub compensated: bool,
+
+    /// skips the bounds checks of the lookup tables during the simulation.
+    /// values requested outside of the tables will be clamped instead of
+    /// panicking so only use this with trusted data
+    #[arg(long)]
+    pub unchecked: bool,
+
+    /// writes the cumulative velocity table calculated on the final iteration
+    /// to the given csv file
+    #[arg(long)]
+    pub cumulative_output: Option<PathBuf>,
+
+    /// writes the acceleration, velocity, and position of each second
+    /// calculated on the final iteration to the given csv file
+    #[arg(long)]
+    pub output: Option<PathBuf>,
+
+    /// a csv file of times in seconds to write to the output file instead of
+    /// every second. the values are interpolated from the calculated tables
+    #[arg(long, requires("output"))]
+    pub sample_output_times: Option<PathBuf>,
+
+    /// only writes every given amount of seconds to the output file. the
+    /// last second is always written
+    #[arg(
+        long,
+        requires("output"),
+        conflicts_with("sample_output_times"),
+        value_parser = clap::value_parser!(u32).range(1..)
+    )]
+    pub output_downsample: Option<u32>,
+
+    /// writes the velocity and position of each second calculated on the
+    /// final iteration as a 2D NumPy .npy array to the given file
+    #[arg(long)]
+    pub output_npy: Option<PathBuf>,
+
+    /// the file path to persist the accumulated timing information to while
+    /// the simulation is running
+    #[arg(long, requires("checkpoint_every_iters"))]
+    pub checkpoint: Option<PathBuf>,
+
+    /// writes the accumulated timing information to the checkpoint file every
+    /// given amount of iterations
+    #[arg(long, requires("checkpoint"), value_parser = clap::value_parser!(u32).range(1..))]
+    pub checkpoint_every_iters: Option<u32>,
+
+    /// delays the acceleration profile by the given amount of seconds
+    #[arg(long)]
+    pub delay: Option<f64>,
+
+    /// the acceleration used before the delay has passed
+    #[arg(long, default_value("zero"))]
+    pub delay_fill: DelayFill,
+
+    /// treats the loaded profile as velocity and differentiates it into an
+    /// acceleration profile instead of running a simulation
+    #[arg(long)]
+    pub accel_from_velocity: bool,
+
+    /// writes the acceleration profile calculated from velocity to the given
+    /// csv file
+    #[arg(long, requires("accel_from_velocity"))]
+    pub derivative_output: Option<PathBuf>,
+
+    /// writes statistics of the loaded profile as json to the given file
+    #[arg(long)]
+    pub profile_stats_json: Option<PathBuf>,
+
+    /// writes the final velocity, position, and timing information as
+    /// prometheus text format metrics to the given file
+    #[arg(long)]
+    pub metrics: Option<PathBuf>,
+
+    /// appends a row with the profile hash, options, and final results to the
+    /// results table of the given sqlite database, creating it if needed
+    #[cfg(feature = "rusqlite")]
+    #[arg(long)]
+    pub sqlite: Option<PathBuf>,
+
+    /// prints the x values and weights sampled by the selected algorithm over
+    /// a single unit interval before running
+    #[arg(long)]
+    pub dump_nodes: bool,
+
+    /// runs the selected algorithm on a small cache resident profile and a
+    /// large synthetic profile then reports the time per evaluation of each
+    /// instead of running the simulation
+    #[arg(long)]
+    pub memory_bound_report: bool,
+
+    /// a csv file of "node,weight" rows on [-1, 1] that replaces the selected
+    /// algorithm. the rule is mapped onto each of the steps of a unit interval
+    #[arg(long)]
+    pub quadrature_file: Option<PathBuf>,
+
+    /// prints a short note about the error order and exactness of the
+    /// selected summation algorithm before running
+    #[arg(long)]
+    pub explain: bool,
+
+    /// specifies how the final timing information is displayed
+    #[arg(long, default_value("human"))]
+    pub timing_format: TimingFormat,
+
+    /// writes the final timing information as json with durations in
+    /// nanoseconds to the given file
+    #[arg(long)]
+    pub timing_json: Option<PathBuf>,
+
+    /// the amount of seconds between each progress line. zero prints every
+    /// iteration
+    #[arg(long, default_value("10"), value_parser = parse_seconds)]
+    pub log_interval: Duration,
+
+    /// does not print the summary of the options, the progress or the final
+    /// timing information
+    #[arg(short, long)]
+    pub quiet: bool,
+
+    /// the format to print the final velocity and position in. csv prints a
+    /// single line of "velocity,position"
+    #[arg(long, default_value("human"))]
+    pub format: ResultFormat,
+}
+
+impl SimOpts {
+    /// retrieves the summation function for the specified options that can
+    /// be used with the given callable type
+    pub fn summation<T>(&self) -> fn(f64, f64, u32, &T) -> Result<f64, summation::SummationError>
+    where
+        T: Callable<f64> + ?Sized,
+    {
+        if self.compensated {
+            self.algo.compensated()
         } else {
-            Ok(self.path.clone())
+            self.algo.summation()
         }
     }
 
-    /// builds the [`csv::Reader`] from the provided csv path
-    fn get_csv_reader(&self) -> anyhow::Result<csv::Reader<std::fs::File>> {
-        let path = self.get_path()?;
+    /// applies the selected endpoint to the loaded profile
+    ///
+    /// when inclusive the last value is repeated so that the trailing interval
+    /// of the last sample is integrated. empty profiles are left unchanged
+    pub fn apply_endpoint(&self, lookup: &mut InterpolateLookup) {
+        if let Endpoint::Inclusive = self.endpoint {
+            if let Some(last) = lookup.last() {
+                lookup.push(last);
+            }
+        }
+    }
 
-        let mut builder = csv::ReaderBuilder::new();
+    /// restricts the loaded profile to the requested start and end times
+    ///
+    /// both times are clamped to the profile. the profile is sampled every
+    /// second from the start time so the window is shortened to a whole
+    /// amount of seconds
+    pub fn apply_window(&self, lookup: &mut InterpolateLookup) {
+        if lookup.is_empty() || (self.t_start.is_none() && self.t_end.is_none()) {
+            return;
+        }
 
-        if self.column.is_some() {
-            builder.has_headers(true);
-        } else {
-            builder.has_headers(false);
+        let last = (lookup.len() - 1) as f64;
+        let start = self.t_start.unwrap_or(0.0).clamp(0.0, last);
+        let end = self.t_end.unwrap_or(last).clamp(start, last);
+        let seconds = (end - start).floor() as usize;
+
+        let window = (0..=seconds)
+            .map(|sec| lookup.call(start + sec as f64))
+            .collect::<Vec<f64>>();
+
+        *lookup = InterpolateLookup::from(window);
+    }
+
+    /// loads the times to write to the output file if they were requested
+    ///
+    /// when downsampling the times are taken from the given amount of
+    /// calculated seconds
+    pub fn output_times(&self, length: usize) -> anyhow::Result<Option<Vec<f64>>> {
+        if let Some(factor) = self.output_downsample {
+            return Ok(Some(output::downsampled_times(length, factor as usize)));
         }
 
-        builder.from_path(&path).context("failed to load csv file")
+        let Some(path) = &self.sample_output_times else {
+            return Ok(None);
+        };
+
+        let times = CsvSim::from_path(path.clone())
+            .get_callable()
+            .context("failed to load output times csv")?;
+
+        Ok(Some(times.into_inner()))
     }
 
-    /// parses the given csv file into a lookup table that supports
-    /// interpolation
-    pub fn get_callable(self) -> anyhow::Result<summation::InterpolateLookup> {
+    /// loads the custom quadrature rule if one was requested
+    ///
+    /// fails if a node is outside of [-1, 1]
+    pub fn quadrature_rule(&self) -> anyhow::Result<Option<Vec<(f64, f64)>>> {
+        let Some(path) = &self.quadrature_file else {
+            return Ok(None);
+        };
+
+        let mut reader = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .from_path(path)
+            .context("failed to load quadrature csv")?;
         let mut rtn = Vec::new();
-        let mut reader = self.get_csv_reader()?;
 
-        let data_index = if let Some(column) = self.column {
-            let mut maybe_index: Option<usize> = None;
-            let headers = reader.headers().context("failed to retrieve csv headers")?;
+        for (index, try_record) in reader.records().enumerate() {
+            let record = try_record
+                .with_context(|| format!("failed to retrieve quadrature entry. {}", index + 1))?;
 
-            for (index, header) in headers.iter().enumerate() {
-                if header == column {
-                    maybe_index = Some(index);
+            let value = |column: usize| -> anyhow::Result<f64> {
+                record
+                    .get(column)
+                    .and_then(|value| f64::from_str(value.trim()).ok())
+                    .with_context(|| format!("invalid quadrature entry. {}", index + 1))
+            };
+            let node = value(0)?;
+            let weight = value(1)?;
 
-                    break;
-                }
+            if !(-1.0..=1.0).contains(&node) {
+                anyhow::bail!("quadrature node is outside of [-1, 1]. {}", index + 1);
             }
 
-            maybe_index.context("failed to find the desired csv column")?
+            rtn.push((node, weight));
+        }
+
+        if rtn.is_empty() {
+            anyhow::bail!("quadrature csv has no nodes");
+        }
+
+        Ok(Some(rtn))
+    }
+
+    /// the smallest and largest velocity allowed during the integration
+    ///
+    /// the smallest is zero when clamping to non-negative velocities and the
+    /// largest is the requested cap. otherwise they are infinite. the largest
+    /// is never less than the smallest
+    pub fn velocity_bounds(&self) -> (f64, f64) {
+        let min = if self.clamp_velocity_nonneg {
+            0.0
         } else {
-            0
+            f64::NEG_INFINITY
         };
+        let max = self.max_velocity.unwrap_or(f64::INFINITY).max(min);
 
-        let records = reader.records();
+        (min, max)
+    }
 
-        for (index, try_record) in records.enumerate() {
-            let record = try_record
-                .with_context(|| format!("failed to retrieve csv entry. {}", index + 1))?;
+    /// checks that the rest of the options can subdivide the velocity pass if
+    /// an adaptive max step was requested
+    pub fn check_adaptive_max_step(&self) -> anyhow::Result<()> {
+        if self.adaptive_max_step.is_none() {
+            return Ok(());
+        }
+
+        if self.integrator.method().is_some() {
+            anyhow::bail!("--adaptive-max-step cannot be used with a coupled integrator");
+        }
+
+        if matches!(self.algo, AppAlgo::AdaptiveSimpson | AppAlgo::Romberg) {
+            anyhow::bail!(
+                "--adaptive-max-step cannot be used with an algorithm that ignores the step"
+            );
+        }
+
+        Ok(())
+    }
+
+    /// checks the calculated velocities for negative values if requested
+    pub fn check_velocity(&self, vel_lookup: &InterpolateLookup) -> anyhow::Result<()> {
+        if !self.error_on_negative_velocity {
+            return Ok(());
+        }
+
+        if let Some(sec) = vel_lookup.iter().position(|value| value < 0.0) {
+            anyhow::bail!("velocity went negative at second {sec}");
+        }
+
+        Ok(())
+    }
+
+    /// checks that the consecutive x values of a lookup table of pairs are no
+    /// further apart than the max gap if one was requested
+    ///
+    /// the error has the row of the value after the gap
+    pub fn check_gaps(&self, lookup: &InterpolateLookup) -> anyhow::Result<()> {
+        let Some((max_gap, xs)) = self.max_gap.zip(lookup.xs()) else {
+            return Ok(());
+        };
+
+        for (index, pair) in xs.windows(2).enumerate() {
+            let gap = pair[1] - pair[0];
+
+            if gap > max_gap {
+                anyhow::bail!(
+                    "gap of {gap} seconds is larger than the max gap of {max_gap}. {}",
+                    index + 2
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    /// loads the grade profile if one was requested
+    ///
+    /// the loaded values are converted to angles in radians
+    pub fn grade(&self) -> anyhow::Result<Option<InterpolateLookup>> {
+        let Some(path) = &self.grade_csv else {
+            return Ok(None);
+        };
+
+        let grade = CsvSim::from_path(path.clone())
+            .get_callable()
+            .context("failed to load grade csv")?
+            .into_inner()
+            .into_iter()
+            .map(|value| self.grade_unit.to_radians(value))
+            .collect::<Vec<f64>>();
+
+        if grade.is_empty() {
+            anyhow::bail!("grade csv is empty");
+        }
+
+        Ok(Some(InterpolateLookup::from(grade)))
+    }
+
+    /// the amount of steps to take when calculating the velocity
+    pub fn velocity_step(&self) -> u32 {
+        self.velocity_step.unwrap_or(self.step)
+    }
+
+    /// the amount of steps to take when calculating the position
+    pub fn position_step(&self) -> u32 {
+        self.position_step.unwrap_or(self.step)
+    }
+
+    /// the first requested option that needs a profile sampled every second
+    ///
+    /// these cannot be used with a profile loaded with a time column
+    pub fn uniform_option(&self) -> Option<&'static str> {
+        #[cfg(feature = "rusqlite")]
+        if self.sqlite.is_some() {
+            return Some("--sqlite");
+        }
+
+        [
+            (
+                self.t_start.is_some() || self.t_end.is_some(),
+                "a time window",
+            ),
+            (
+                matches!(self.endpoint, Endpoint::Inclusive),
+                "an inclusive endpoint",
+            ),
+            (self.integrator.method().is_some(), "a coupled integrator"),
+            (self.delay.is_some(), "--delay"),
+            (self.unchecked, "--unchecked"),
+            (self.adaptive_max_step.is_some(), "--adaptive-max-step"),
+            (self.monte_carlo.is_some(), "--monte-carlo"),
+            (self.compare_precision, "--compare-precision"),
+            (self.compare_interp, "--compare-interp"),
+            (self.all_algos, "--all-algos"),
+            (self.stability_check, "--stability-check"),
+            (self.error_regions.is_some(), "--error-regions"),
+            (self.accel_l2, "--accel-l2"),
+            (self.report_abs_integral, "--report-abs-integral"),
+            (self.integrate_abs_jerk, "--integrate-abs-jerk"),
+            (self.report_work, "--report-work"),
+            (self.report_stops, "--report-stops"),
+            (self.cumulative_output.is_some(), "--cumulative-output"),
+            (self.output.is_some(), "--output"),
+            (self.output_npy.is_some(), "--output-npy"),
+            (self.accel_from_velocity, "--accel-from-velocity"),
+            (self.quadrature_file.is_some(), "--quadrature-file"),
+        ]
+        .into_iter()
+        .find_map(|(requested, name)| requested.then_some(name))
+    }
+
+    /// the first requested option that is only applied by the full simulation
+    ///
+    /// these cannot be used when comparing precisions since the single
+    /// precision values do not go through the full simulation
+    pub fn precision_option(&self) -> Option<&'static str> {
+        [
+            (self.integrator.method().is_some(), "a coupled integrator"),
+            (self.delay.is_some(), "--delay"),
+            (self.initial_velocity != 0.0, "--initial-velocity"),
+            (self.initial_position != 0.0, "--initial-position"),
+            (self.max_velocity.is_some(), "--max-velocity"),
+            (self.clamp_velocity_nonneg, "--clamp-velocity-nonneg"),
+            (
+                self.error_on_negative_velocity,
+                "--error-on-negative-velocity",
+            ),
+            (self.compensated, "--compensated"),
+            (self.adaptive_max_step.is_some(), "--adaptive-max-step"),
+        ]
+        .into_iter()
+        .find_map(|(requested, name)| requested.then_some(name))
+    }
+
+    /// the first requested option that needs a profile loaded into a lookup
+    /// table
+    ///
+    /// these cannot be used with a profile that is calculated, like a fourier
+    /// series
+    pub fn lookup_option(&self) -> Option<&'static str> {
+        [
+            (
+                self.t_start.is_some() || self.t_end.is_some(),
+                "a time window",
+            ),
+            (
+                matches!(self.endpoint, Endpoint::Inclusive),
+                "an inclusive endpoint",
+            ),
+            (self.max_gap.is_some(), "--max-gap"),
+            (self.unchecked, "--unchecked"),
+            (self.profile_stats_json.is_some(), "--profile-stats-json"),
+            (self.accel_from_velocity, "--accel-from-velocity"),
+            (self.monte_carlo.is_some(), "--monte-carlo"),
+            (self.compare_precision, "--compare-precision"),
+            (self.compare_interp, "--compare-interp"),
+            (self.all_algos, "--all-algos"),
+            (self.stability_check, "--stability-check"),
+            (self.error_regions.is_some(), "--error-regions"),
+            (self.quadrature_file.is_some(), "--quadrature-file"),
+            (self.accel_l2, "--accel-l2"),
+            (self.report_abs_integral, "--report-abs-integral"),
+            (self.integrate_abs_jerk, "--integrate-abs-jerk"),
+            (self.time_average, "--time-average"),
+            (self.report_work, "--report-work"),
+        ]
+        .into_iter()
+        .find_map(|(requested, name)| requested.then_some(name))
+    }
+
+    /// checks that the step, velocity step, position step and tolerance can
+    /// be used by the summation functions
+    pub fn check_steps(&self) -> Result<(), summation::SummationError> {
+        summation::check_tolerance(self.tolerance)?;
+        summation::check_iterations(self.step)?;
+        summation::check_iterations(self.velocity_step())?;
+        summation::check_iterations(self.position_step())
+    }
+
+    /// a copy of the options that runs the simulation once without writing
+    /// checkpoints
+    ///
+    /// used when the simulation is run many times as part of a larger
+    /// calculation
+    pub fn single_iteration(&self) -> SimOpts {
+        let mut rtn = self.clone();
+        rtn.iterations = 1;
+        rtn.checkpoint = None;
+        rtn
+    }
+
+    /// creates the iteration checkpoint if one was requested
+    pub fn iter_checkpoint(&self) -> Option<time::IterCheckpoint> {
+        self.checkpoint
+            .clone()
+            .zip(self.checkpoint_every_iters)
+            .map(|(path, every)| time::IterCheckpoint::new(path, every))
+    }
 
-            let value = record
-                .get(data_index)
-                .with_context(|| format!("failed to retrieve csv entry column. {}", index + 1))?;
+    /// retrieves the summation config for the specified options
+    pub fn summation_config(&self) -> summation::SummationConfig {
+        summation::SummationConfig {
+            checked: !self.unchecked,
+            tolerance: self.tolerance,
+            levels: self.levels,
+        }
+    }
+}
+
+/// the available summation algorithms that the simulation is capable of
+/// running
+#[derive(Debug, Clone, ValueEnum)]
+pub enum AppAlgo {
+    LeftRiemann,
+    MidRiemann,
+    RightRiemann,
+    Trapezoidal,
+    Simpsons,
+    TanhSinh,
+    GaussLegendre,
+    AdaptiveSimpson,
+    Romberg,
+}
+
+impl AppAlgo {
+    /// retrieves the summation function for the selected algorithm that can
+    /// be used with the given callable type
+    pub fn summation<T>(&self) -> fn(f64, f64, u32, &T) -> Result<f64, summation::SummationError>
+    where
+        T: Callable<f64> + ?Sized,
+    {
+        match self {
+            AppAlgo::LeftRiemann => summation::left_riemann,
+            AppAlgo::MidRiemann => summation::mid_riemann,
+            AppAlgo::RightRiemann => summation::right_riemann,
+            AppAlgo::Trapezoidal => summation::trapezoidal,
+            AppAlgo::Simpsons => summation::simpsons,
+            AppAlgo::TanhSinh => summation::tanh_sinh,
+            AppAlgo::GaussLegendre => summation::gauss_legendre,
+            AppAlgo::AdaptiveSimpson => summation::adaptive_simpson_panel,
+            AppAlgo::Romberg => summation::romberg_panel,
+        }
+    }
+
+    /// creates a short explanation of the error order and exactness of the
+    /// selected algorithm with the given amount of steps per interval and
+    /// romberg refinements
+    pub fn explain(&self, step: u32, levels: u32) -> String {
+        let (name, order, exact) = match self {
+            AppAlgo::LeftRiemann => ("left riemann", "O(h)", "constant"),
+            AppAlgo::MidRiemann => ("midpoint riemann", "O(h²)", "linear"),
+            AppAlgo::RightRiemann => ("right riemann", "O(h)", "constant"),
+            AppAlgo::Trapezoidal => ("trapezoidal", "O(h²)", "linear"),
+            AppAlgo::Simpsons => ("simpsons", "O(h⁴)", "cubic"),
+            AppAlgo::GaussLegendre => ("5 point gauss-legendre", "O(h¹⁰)", "degree 9"),
+            AppAlgo::AdaptiveSimpson => {
+                return String::from(
+                    "adaptive simpsons: splits each interval until the estimated error is within the tolerance and ignores the step",
+                );
+            }
+            AppAlgo::Romberg => {
+                return format!(
+                    "romberg: error is O(h^{}) with h = 1/{} from {levels} trapezoidal refinements and ignores the step",
+                    2 * (levels + 1),
+                    1u64 << levels
+                );
+            }
+            AppAlgo::TanhSinh => {
+                return format!(
+                    "tanh-sinh: error is O(exp(-cN / ln N)) with N = {step} nodes on each side and handles integrable singularities at the interval bounds"
+                );
+            }
+        };
 
-            rtn.push(f64::from_str(value).with_context(|| {
-                format!("failed to convert csv entry into float. {}", index + 1)
-            })?);
+        let mut rtn =
+            format!("{name}: error is {order} with h = 1/{step} and is exact for {exact} profiles");
+
+        if matches!(self, AppAlgo::Simpsons) && step % 2 == 1 {
+            rtn.push_str(". the step is odd so the last 3 steps use simpsons 3/8 rule");
         }
 
-        Ok(InterpolateLookup::from(rtn))
+        rtn
+    }
+
+    /// lists the x values sampled by the selected algorithm between lower and
+    /// upper with the given amount of steps along with the weight that each
+    /// sampled value is multiplied by
+    ///
+    /// returns `None` for adaptive algorithms since the sampled x values
+    /// depend on the callable and for romberg since the weights come from the
+    /// extrapolation. fails if the amount of steps is zero
+    pub fn nodes(
+        &self,
+        lower: f64,
+        upper: f64,
+        iterations: u32,
+    ) -> Result<Option<Vec<(f64, f64)>>, summation::SummationError> {
+        summation::check_iterations(iterations)?;
+
+        let step = (upper - lower) / (iterations as f64);
+        let x = |iter: u32| lower + iter as f64 * step;
+
+        let rtn = match self {
+            AppAlgo::AdaptiveSimpson | AppAlgo::Romberg => return Ok(None),
+            AppAlgo::LeftRiemann => (0..iterations).map(|iter| (x(iter), step)).collect(),
+            AppAlgo::MidRiemann => (0..iterations)
+                .map(|iter| (x(iter) + step / 2.0, step))
+                .collect(),
+            AppAlgo::RightRiemann => (1..=iterations).map(|iter| (x(iter), step)).collect(),
+            AppAlgo::Trapezoidal => (0..=iterations)
+                .map(|iter| {
+                    if iter == 0 || iter == iterations {
+                        (x(iter), step / 2.0)
+                    } else {
+                        (x(iter), step)
+                    }
+                })
+                .collect(),
+            AppAlgo::TanhSinh => summation::tanh_sinh_nodes(lower, upper, iterations)?.collect(),
+            AppAlgo::GaussLegendre => (0..iterations)
+                .flat_map(|iter| {
+                    let center = x(iter) + step / 2.0;
+
+                    summation::GAUSS_LEGENDRE_5
+                        .map(|(node, weight)| (center + node * step / 2.0, weight * step / 2.0))
+                })
+                .collect(),
+            AppAlgo::Simpsons => (0..=iterations)
+                .map(|iter| {
+                    (
+                        x(iter),
+                        step * summation::simpsons_weight(iter, iterations) / 3.0,
+                    )
+                })
+                .collect(),
+        };
+
+        Ok(Some(rtn))
+    }
+
+    /// retrieves the summation function for the selected algorithm that can
+    /// be used with the given callable type and sample value
+    pub fn sample_summation<T, V>(
+        &self,
+    ) -> fn(f64, f64, u32, &T) -> Result<V, summation::SummationError>
+    where
+        T: Callable<f64, V> + ?Sized,
+        V: summation::Sample,
+    {
+        match self {
+            AppAlgo::LeftRiemann => summation::left_riemann,
+            AppAlgo::MidRiemann => summation::mid_riemann,
+            AppAlgo::RightRiemann => summation::right_riemann,
+            AppAlgo::Trapezoidal => summation::trapezoidal,
+            AppAlgo::Simpsons => summation::simpsons,
+            AppAlgo::TanhSinh => summation::tanh_sinh,
+            AppAlgo::GaussLegendre => summation::gauss_legendre,
+            // the error of other samples cannot be measured so the fixed step
+            // version is used instead
+            AppAlgo::AdaptiveSimpson => summation::simpsons,
+            AppAlgo::Romberg => summation::romberg_panel,
+        }
+    }
+
+    /// retrieves the compensated summation function for the selected
+    /// algorithm that can be used with the given callable type
+    pub fn compensated<T>(&self) -> fn(f64, f64, u32, &T) -> Result<f64, summation::SummationError>
+    where
+        T: Callable<f64> + ?Sized,
+    {
+        match self {
+            AppAlgo::LeftRiemann => summation::left_riemann_compensated,
+            AppAlgo::MidRiemann => summation::mid_riemann_compensated,
+            AppAlgo::RightRiemann => summation::right_riemann_compensated,
+            AppAlgo::Trapezoidal => summation::trapezoidal_compensated,
+            AppAlgo::Simpsons => summation::simpsons_compensated,
+            AppAlgo::TanhSinh => summation::tanh_sinh_compensated,
+            AppAlgo::GaussLegendre => summation::gauss_legendre_compensated,
+            // the halves are summed pairwise which already limits the
+            // rounding error
+            AppAlgo::AdaptiveSimpson => summation::adaptive_simpson_panel,
+            AppAlgo::Romberg => summation::romberg_panel_compensated,
+        }
+    }
+}
+
+/// the available methods for calculating velocity and position
+#[derive(Debug, Clone, PartialEq, ValueEnum)]
+pub enum AppIntegrator {
+    /// separate velocity and position passes using the selected summation
+    /// algorithm
+    Quadrature,
+    /// first order coupled euler integration
+    Euler,
+    /// second order coupled heun's method
+    Rk2,
+    /// fourth order coupled runge-kutta
+    Rk4,
+}
+
+impl AppIntegrator {
+    /// retrieves the coupled integration method if one was selected
+    pub fn method(&self) -> Option<integrator::Method> {
+        match self {
+            AppIntegrator::Quadrature => None,
+            AppIntegrator::Euler => Some(integrator::Method::Euler),
+            AppIntegrator::Rk2 => Some(integrator::Method::Rk2),
+            AppIntegrator::Rk4 => Some(integrator::Method::Rk4),
+        }
+    }
+}
+
+/// the available units for the values of a grade profile
+#[derive(Debug, Clone, ValueEnum)]
+pub enum GradeUnit {
+    /// rise over run as a percentage
+    Percent,
+    /// angle of the track in degrees
+    Degrees,
+}
+
+impl GradeUnit {
+    /// converts the given grade value to an angle in radians
+    pub fn to_radians(&self, value: f64) -> f64 {
+        match self {
+            GradeUnit::Percent => (value / 100.0).atan(),
+            GradeUnit::Degrees => value.to_radians(),
+        }
+    }
+}
+
+/// the available values that a profile can be indexed by
+#[derive(Debug, Clone, ValueEnum)]
+pub enum AppProfileIndex {
+    /// each row is one meter of distance travelled
+    Distance,
+    /// each row is one second of time
+    Time,
+}
+
+impl AppProfileIndex {
+    /// retrieves the integrator profile index for the selected value
+    pub fn profile_index(&self) -> integrator::ProfileIndex {
+        match self {
+            AppProfileIndex::Distance => integrator::ProfileIndex::Distance,
+            AppProfileIndex::Time => integrator::ProfileIndex::Time,
+        }
+    }
+}
+
+/// the available ways of handling the last sample of a profile
+#[derive(Debug, Clone, ValueEnum)]
+pub enum Endpoint {
+    /// the last sample is held for one more second
+    Inclusive,
+    /// the last sample is only the end of the final interval
+    Exclusive,
+}
+
+/// the available acceleration values to use before a delay has passed
+#[derive(Debug, Clone, ValueEnum)]
+pub enum DelayFill {
+    /// no acceleration
+    Zero,
+    /// the first value of the acceleration profile
+    Clamp,
+}
+
+impl DelayFill {
+    /// retrieves the shift fill for the selected value
+    pub fn shift_fill(&self) -> summation::ShiftFill {
+        match self {
+            DelayFill::Zero => summation::ShiftFill::Zero,
+            DelayFill::Clamp => summation::ShiftFill::Clamp,
+        }
+    }
+}
+
+/// the available formats for displaying the final timing information
+#[derive(Debug, Clone, ValueEnum)]
+pub enum TimingFormat {
+    Human,
+    Csv,
+}
+
+/// the available formats for printing the final velocity and position
+#[derive(Debug, Clone, ValueEnum)]
+pub enum ResultFormat {
+    Human,
+    Csv,
+    Json,
+}
+
+/// the different kins of simulations available for the program to run
+///
+/// profiles are either loaded from csv files or defined by a fourier series
+#[derive(Debug, Subcommand)]
+pub enum SimKind {
+    /// runs a simulation from a given acceleration profile
+    Csv(CsvSim),
+
+    /// runs a simulation from an acceleration profile stored as json
+    Json(JsonSim),
+
+    /// validates a given acceleration profile without running a simulation
+    Validate(CsvSim),
+
+    /// runs a simulation for every csv file in a given directory
+    Batch(BatchSim),
+
+    /// runs a simulation from a profile defined by fourier coefficients
+    Fourier(FourierSim),
+
+    /// combines the results json files of multiple batches into a single
+    /// summary
+    MergeResults(MergeSim),
+
+    /// compares two trajectory output files and fails if they differ by more
+    /// than the tolerance
+    Verify(VerifySim),
+
+    /// writes a generated acceleration profile to a csv file. the profile can
+    /// be loaded with `csv --column acceleration`
+    Generate(GenerateSim),
+}
+
+/// options for running a simulation from a specified csv file
+#[derive(Debug, Clone, Args)]
+pub struct CsvSim {
+    /// loads acceleration data in a specific column from the csv file
+    #[arg(long)]
+    pub column: Option<String>,
+
+    /// loads acceleration data from the column at the given index of a csv
+    /// file without headers. defaults to the first column
+    #[arg(long, conflicts_with("column"))]
+    pub column_index: Option<usize>,
+
+    /// the single byte that separates the fields of the csv file. \t can be
+    /// used for tabs
+    #[arg(long, default_value(","), value_parser = parse_byte)]
+    pub delimiter: u8,
+
+    /// ignores any line of the csv file that starts with the given byte
+    #[arg(long, value_parser = parse_byte)]
+    pub comment: Option<u8>,
+
+    /// uses the values of the given column as the time of each row instead of
+    /// assuming the rows are one second apart. the times must be strictly
+    /// increasing
+    #[arg(
+        long,
+        requires("column"),
+        conflicts_with("pad_to"),
+        conflicts_with("start_marker"),
+        conflicts_with("end_marker"),
+        conflicts_with("savgol"),
+        conflicts_with("mask_column")
+    )]
+    pub time_column: Option<String>,
+
+    /// loads NaN and infinite values from the csv file instead of failing
+    #[arg(long)]
+    pub allow_nonfinite: bool,
+
+    /// refuses to load the csv file if the estimated memory needed for the
+    /// simulation exceeds the given amount of bytes. accepts K, M, and G
+    /// suffixes
+    #[arg(long, value_parser = parse_bytes)]
+    pub max_memory: Option<u64>,
+
+    /// pads the end of the loaded values up to the given length
+    #[arg(long)]
+    pub pad_to: Option<usize>,
+
+    /// the value used when padding the loaded values. either a number or
+    /// "last" to repeat the last loaded value
+    #[arg(long, default_value("0"))]
+    pub pad_value: PadValue,
+
+    /// only keeps the values after the first row with this value
+    #[arg(long, allow_negative_numbers(true))]
+    pub start_marker: Option<f64>,
+
+    /// only keeps the values before the first row with this value that comes
+    /// after the start marker
+    #[arg(long, allow_negative_numbers(true))]
+    pub end_marker: Option<f64>,
+
+    /// treats the loaded values as forces and divides them by the given mass
+    /// to get the acceleration
+    #[arg(long, allow_negative_numbers(true))]
+    pub force_mass: Option<f64>,
+
+    /// rounds each loaded value to the nearest multiple of the given step
+    #[arg(long)]
+    pub quantize: Option<f64>,
+
+    /// smooths the loaded values with a Savitzky-Golay filter given as
+    /// "window,order". the window must be odd and greater than the order
+    #[arg(long, value_parser = parse_savgol)]
+    pub savgol: Option<SavGol>,
+
+    /// smooths the loaded values with a centered moving average over the
+    /// given amount of samples. a window of 1 leaves the values unchanged
+    #[arg(long, value_parser = clap::value_parser!(u32).range(1..))]
+    pub smooth: Option<u32>,
+
+    /// resamples the loaded values to the given amount of evenly spaced
+    /// samples over the same duration. the simulation then uses the times
+    /// of the new samples the same as a time column
+    #[arg(
+        long,
+        conflicts_with("mask_column"),
+        value_parser = clap::value_parser!(u32).range(1..)
+    )]
+    pub resample: Option<u32>,
+
+    /// excludes samples from the simulation using a column of 0 or 1 values.
+    /// a 0 removes the interval starting at that sample instead of
+    /// interpolating over it
+    #[arg(
+        long,
+        requires("column"),
+        conflicts_with("start_marker"),
+        conflicts_with("end_marker")
+    )]
+    pub mask_column: Option<String>,
+
+    /// the csv file path to load or the directory of csv files when running
+    /// a batch. a path of - reads the csv from stdin
+    pub path: PathBuf,
+}
+
+/// the value to use when padding a loaded profile
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PadValue {
+    /// repeats the last loaded value
+    Last,
+    /// uses the given value
+    Value(f64),
+}
+
+impl FromStr for PadValue {
+    type Err = String;
+
+    fn from_str(given: &str) -> Result<Self, Self::Err> {
+        if given == "last" {
+            Ok(PadValue::Last)
+        } else {
+            f64::from_str(given)
+                .map(PadValue::Value)
+                .map_err(|err| format!("expected \"last\" or a number: {err}"))
+        }
+    }
+}
+
+/// options for running a simulation on every csv file in a directory
+#[derive(Debug, Args)]
+pub struct BatchSim {
+    /// the csv file to write the aggregated results to. if not specified then
+    /// the results will be written to stdout
+    #[arg(long)]
+    pub results: Option<PathBuf>,
+
+    /// the format of the aggregated results
+    #[arg(long, default_value("csv"))]
+    pub results_format: ResultsFormat,
+
+    #[command(flatten)]
+    pub csv: CsvSim,
+}
+
+/// options for combining multiple batch results files
+#[derive(Debug, Args)]
+pub struct MergeSim {
+    /// the file to write the combined results to. if not specified then the
+    /// results will be written to stdout
+    #[arg(long)]
+    pub output: Option<PathBuf>,
+
+    /// the format of the combined results
+    #[arg(long, default_value("csv"))]
+    pub format: ResultsFormat,
+
+    /// the value to sort the combined results by in ascending order
+    #[arg(long, default_value("path"))]
+    pub sort_by: SortMetric,
+
+    /// the results json files to combine
+    #[arg(required = true)]
+    pub files: Vec<PathBuf>,
+}
+
+/// options for comparing two trajectory output files
+#[derive(Debug, Args)]
+pub struct VerifySim {
+    /// the largest absolute difference allowed between the two files
+    #[arg(long, default_value("0"))]
+    pub tolerance: f64,
+
+    /// the first trajectory csv file
+    pub file_a: PathBuf,
+
+    /// the second trajectory csv file
+    pub file_b: PathBuf,
+}
+
+/// the available formats for writing batch results
+#[derive(Debug, Clone, ValueEnum)]
+pub enum ResultsFormat {
+    Csv,
+    Json,
+}
+
+/// the available values to sort batch results by
+#[derive(Debug, Clone, ValueEnum)]
+pub enum SortMetric {
+    Path,
+    Length,
+    FinalVelocity,
+    FinalPosition,
+}
+
+impl SortMetric {
+    /// retrieves the batch metric for the selected value
+    pub fn metric(&self) -> batch::Metric {
+        match self {
+            SortMetric::Path => batch::Metric::Path,
+            SortMetric::Length => batch::Metric::Length,
+            SortMetric::FinalVelocity => batch::Metric::FinalVelocity,
+            SortMetric::FinalPosition => batch::Metric::FinalPosition,
+        }
+    }
+}
+
+/// options for running a simulation from a series of sine terms
+#[derive(Debug, Clone, Args)]
+pub struct FourierSim {
+    /// a term of the series as "amplitude,frequency,phase" with the frequency
+    /// in cycles per second and the phase in radians. can be specified
+    /// multiple times
+    #[arg(
+        long = "term",
+        required = true,
+        allow_hyphen_values = true,
+        value_parser = parse_term
+    )]
+    pub terms: Vec<FourierTerm>,
+
+    /// the amount of seconds to simulate
+    #[arg(long)]
+    pub duration: f64,
+}
+
+impl FourierSim {
+    /// creates the series callable from the given terms
+    pub fn get_callable(&self) -> FourierSeries {
+        FourierSeries::new(self.terms.clone())
+    }
+
+    /// the amount of whole seconds sampled by the simulation, including the
+    /// start
+    ///
+    /// returns `None` when the duration ends partway through a second, see
+    /// [`FourierSim::times`]
+    pub fn length(&self) -> anyhow::Result<Option<usize>> {
+        self.check_duration()?;
+
+        if self.duration.fract() == 0.0 {
+            Ok(Some(self.duration as usize + 1))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// the start of each second of the duration followed by the duration
+    /// itself so the last panel only covers the partial second that remains
+    pub fn times(&self) -> anyhow::Result<Vec<f64>> {
+        self.check_duration()?;
+
+        let mut rtn = (0..=self.duration.floor() as usize)
+            .map(|sec| sec as f64)
+            .collect::<Vec<f64>>();
+
+        if self.duration.fract() != 0.0 {
+            rtn.push(self.duration);
+        }
+
+        Ok(rtn)
+    }
+
+    /// fails if the duration is not a positive number
+    fn check_duration(&self) -> anyhow::Result<()> {
+        if !self.duration.is_finite() || self.duration <= 0.0 {
+            anyhow::bail!("duration must be a positive number");
+        }
+
+        Ok(())
+    }
+}
+
+/// options for running a simulation from a json file
+///
+/// the file is either an array of numbers one second apart or an array of
+/// objects with a `time` and an `accel`
+#[derive(Debug, Clone, Args)]
+pub struct JsonSim {
+    /// the json file path to load. a path of - reads the json from stdin
+    pub path: PathBuf,
+}
+
+/// the accepted layouts of a json acceleration profile
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum JsonProfile {
+    Values(Vec<f64>),
+    Samples(Vec<JsonSample>),
+}
+
+/// a single sample of a json acceleration profile
+#[derive(Debug, Deserialize)]
+struct JsonSample {
+    time: f64,
+    accel: f64,
+}
+
+impl JsonSim {
+    /// loads the json file into a lookup table
+    ///
+    /// an array of objects creates a table of pairs using the times as the
+    /// x values
+    pub fn get_callable(&self) -> anyhow::Result<InterpolateLookup> {
+        let input: Box<dyn Read> = if self.path.as_os_str() == "-" {
+            Box::new(std::io::stdin().lock())
+        } else {
+            Box::new(std::fs::File::open(&self.path).context("failed to load json file")?)
+        };
+
+        let profile: JsonProfile = serde_json::from_reader(BufReader::new(input))
+            .context("failed to parse json profile")?;
+
+        match profile {
+            JsonProfile::Values(values) => {
+                if let Some(index) = values.iter().position(|value| !value.is_finite()) {
+                    anyhow::bail!("json entry is not a finite number. {}", index + 1);
+                }
+
+                Ok(InterpolateLookup::from(values))
+            }
+            JsonProfile::Samples(samples) => {
+                let mut pairs: Vec<(f64, f64)> = Vec::with_capacity(samples.len());
+
+                for (index, sample) in samples.into_iter().enumerate() {
+                    if !sample.time.is_finite() {
+                        anyhow::bail!("json time is not a finite number. {}", index + 1);
+                    }
+
+                    if !sample.accel.is_finite() {
+                        anyhow::bail!("json entry is not a finite number. {}", index + 1);
+                    }
+
+                    if pairs.last().is_some_and(|(last, _)| *last >= sample.time) {
+                        anyhow::bail!("json time is not after the previous time. {}", index + 1);
+                    }
+
+                    pairs.push((sample.time, sample.accel));
+                }
+
+                Ok(InterpolateLookup::from_pairs(pairs))
+            }
+        }
+    }
+}
+
+/// the available kinds of generated profiles
+#[derive(Debug, Clone, ValueEnum)]
+pub enum GenerateKind {
+    /// zero until the step time then the amplitude
+    Step,
+    /// increases by the amplitude every second starting from zero
+    Ramp,
+    /// a sine wave with the given amplitude and period
+    Sine,
+    /// accelerates with the amplitude for the ramp time, coasts, then brakes
+    /// with the amplitude for the ramp time. gives a trapezoidal velocity
+    Trapezoid,
+}
+
+/// options for generating an acceleration profile
+#[derive(Debug, Clone, Args)]
+pub struct GenerateSim {
+    /// the amount of samples to generate
+    #[arg(long)]
+    pub length: usize,
+
+    /// the size of the generated values
+    #[arg(long, default_value("1"), allow_negative_numbers(true))]
+    pub amplitude: f64,
+
+    /// the second that a step profile changes at. defaults to half of the
+    /// length
+    #[arg(long)]
+    pub at: Option<usize>,
+
+    /// the amount of seconds for one cycle of a sine profile. defaults to the
+    /// length
+    #[arg(long)]
+    pub period: Option<f64>,
+
+    /// the amount of seconds spent accelerating and braking for a trapezoid
+    /// profile. defaults to a quarter of the length
+    #[arg(long)]
+    pub ramp: Option<usize>,
+
+    /// the csv file to write the profile to. if not specified then the
+    /// profile will be written to stdout
+    #[arg(long)]
+    pub output: Option<PathBuf>,
+
+    /// the kind of profile to generate
+    pub kind: GenerateKind,
+}
+
+impl GenerateSim {
+    /// creates the requested profile
+    pub fn profile(&self) -> anyhow::Result<InterpolateLookup> {
+        let length = self.length;
+        let amplitude = self.amplitude;
+
+        let values: Vec<f64> = match self.kind {
+            GenerateKind::Step => {
+                let at = self.at.unwrap_or(length / 2);
+
+                (0..length)
+                    .map(|sec| if sec < at { 0.0 } else { amplitude })
+                    .collect()
+            }
+            GenerateKind::Ramp => (0..length).map(|sec| amplitude * sec as f64).collect(),
+            GenerateKind::Sine => {
+                let period = self.period.unwrap_or(length as f64);
+
+                if !period.is_finite() || period <= 0.0 {
+                    anyhow::bail!("period must be a positive number");
+                }
+
+                (0..length)
+                    .map(|sec| amplitude * (std::f64::consts::TAU * sec as f64 / period).sin())
+                    .collect()
+            }
+            GenerateKind::Trapezoid => {
+                let ramp = self.ramp.unwrap_or(length / 4);
+
+                if ramp * 2 > length {
+                    anyhow::bail!("ramp must be at most half of the length");
+                }
+
+                (0..length)
+                    .map(|sec| {
+                        if sec < ramp {
+                            amplitude
+                        } else if sec >= length - ramp {
+                            -amplitude
+                        } else {
+                            0.0
+                        }
+                    })
+                    .collect()
+            }
+        };
+
+        Ok(InterpolateLookup::from(values))
+    }
+}
+
+impl CsvSim {
+    /// creates options for loading the first column of the given csv file
+    /// without any preprocessing
+    pub fn from_path(path: PathBuf) -> Self {
+        Self {
+            column: None,
+            column_index: None,
+            delimiter: b',',
+            comment: None,
+            time_column: None,
+            allow_nonfinite: false,
+            max_memory: None,
+            pad_to: None,
+            pad_value: PadValue::Value(0.0),
+            start_marker: None,
+            end_marker: None,
+            force_mass: None,
+            quantize: None,
+            savgol: None,
+            smooth: None,
+            resample: None,
+            mask_column: None,
+            path,
+        }
+    }
+
+    /// retrieves the path of the specified csv file
+    ///
+    /// if the given path is relative then it will be resolved using the
+    /// current working directory
+    fn get_path(&self) -> anyhow::Result<PathBuf> {
+        if self.path.is_relative() {
+            let cwd =
+                std::env::current_dir().context("failed to retrieve current working directory")?;
+
+            Ok(cwd.join(&self.path))
+        } else {
+            Ok(self.path.clone())
+        }
+    }
+
+    /// names the option that causes the loaded lookup table to have explicit
+    /// x values instead of one value every second
+    pub fn pairs_source(&self) -> &'static str {
+        if self.time_column.is_some() || self.resample.is_none() {
+            "a time column"
+        } else {
+            "--resample"
+        }
+    }
+
+    /// checks if the csv should be read from stdin instead of a file
+    fn is_stdin(&self) -> bool {
+        self.path.as_os_str() == "-"
+    }
+
+    /// fails if the csv is read from stdin since the requested operation
+    /// needs to read the csv more than once
+    fn check_not_stdin(&self, operation: &str) -> anyhow::Result<()> {
+        if self.is_stdin() {
+            anyhow::bail!("{operation} cannot be used when reading the csv from stdin");
+        }
+
+        Ok(())
+    }
+
+    /// builds the [`csv::Reader`] from the provided csv path or stdin
+    fn get_csv_reader(&self) -> anyhow::Result<csv::Reader<Box<dyn Read>>> {
+        let mut builder = csv::ReaderBuilder::new();
+        builder.delimiter(self.delimiter).comment(self.comment);
+
+        if self.column.is_some() {
+            builder.has_headers(true);
+        } else {
+            builder.has_headers(false);
+        }
+
+        let input: Box<dyn Read> = if self.is_stdin() {
+            Box::new(std::io::stdin().lock())
+        } else {
+            let path = self.get_path()?;
+
+            Box::new(std::fs::File::open(&path).context("failed to load csv file")?)
+        };
+
+        Ok(builder.from_reader(input))
+    }
+
+    /// retrieves the csv files from the path when it is a directory
+    ///
+    /// each file will use the same options as the original and the files will
+    /// be sorted by their path
+    pub fn batch_files(&self) -> anyhow::Result<Vec<CsvSim>> {
+        let path = self.get_path()?;
+        let mut rtn = Vec::new();
+
+        for try_entry in std::fs::read_dir(&path).context("failed to read batch directory")? {
+            let entry = try_entry.context("failed to retrieve batch directory entry")?;
+            let entry_path = entry.path();
+
+            if entry_path.is_file() && entry_path.extension().is_some_and(|ext| ext == "csv") {
+                let mut sim = self.clone();
+                sim.path = entry_path;
+
+                rtn.push(sim);
+            }
+        }
+
+        rtn.sort_by(|a, b| a.path.cmp(&b.path));
+
+        Ok(rtn)
+    }
+
+    /// attempts to detect the delimiter used by the csv file
+    ///
+    /// checks the first line of the file that is not a comment for the most
+    /// common of the known delimiters and defaults to a comma if none are
+    /// found
+    fn detect_delimiter(&self) -> anyhow::Result<u8> {
+        self.check_not_stdin("validation")?;
+
+        let path = self.get_path()?;
+        let file = std::fs::File::open(&path).context("failed to open csv file")?;
+        let mut reader = BufReader::new(file);
+        let mut line = String::new();
+
+        // the first line that is not a comment is used
+        loop {
+            line.clear();
+
+            let read = reader
+                .read_line(&mut line)
+                .context("failed to read csv file")?;
+
+            if read == 0
+                || self
+                    .comment
+                    .is_none_or(|comment| line.as_bytes().first() != Some(&comment))
+            {
+                break;
+            }
+        }
+
+        let mut rtn = b',';
+        let mut found = 0;
+
+        for delimiter in [b',', b'\t', b';', b'|'] {
+            let count = line.bytes().filter(|b| *b == delimiter).count();
+
+            if count > found {
+                rtn = delimiter;
+                found = count;
+            }
+        }
+
+        Ok(rtn)
+    }
+
+    /// estimates the amount of memory in bytes needed to run a simulation
+    /// with the csv file
+    ///
+    /// samples the first few lines of the file to get an average line length
+    /// and uses the size of the file to estimate the total number of rows.
+    /// the acceleration and velocity lookup tables are both included in the
+    /// estimate
+    fn estimate_memory(&self) -> anyhow::Result<u64> {
+        self.check_not_stdin("a max memory")?;
+
+        let path = self.get_path()?;
+        let file = std::fs::File::open(&path).context("failed to open csv file")?;
+        let size = file
+            .metadata()
+            .context("failed to retrieve csv file metadata")?
+            .len();
+
+        let mut reader = BufReader::new(file);
+        let mut line = String::new();
+        let mut sampled_bytes = 0u64;
+        let mut sampled_lines = 0u64;
+
+        while sampled_lines < 100 {
+            line.clear();
+
+            let read = reader
+                .read_line(&mut line)
+                .context("failed to read csv file")?;
+
+            if read == 0 {
+                break;
+            }
+
+            sampled_bytes += read as u64;
+            sampled_lines += 1;
+        }
+
+        if sampled_bytes == 0 {
+            return Ok(0);
+        }
+
+        let rows = size * sampled_lines / sampled_bytes;
+
+        Ok(rows * std::mem::size_of::<f64>() as u64 * 2)
+    }
+
+    /// retrieves the index of the column that contains the acceleration data
+    fn get_data_index<R: Read>(&self, reader: &mut csv::Reader<R>) -> anyhow::Result<usize> {
+        if let Some(column) = &self.column {
+            let mut maybe_index: Option<usize> = None;
+            let headers = reader.headers().context("failed to retrieve csv headers")?;
+
+            for (index, header) in headers.iter().enumerate() {
+                if header == column {
+                    maybe_index = Some(index);
+
+                    break;
+                }
+            }
+
+            maybe_index.context("failed to find the desired csv column")
+        } else {
+            Ok(self.column_index.unwrap_or(0))
+        }
+    }
+
+    /// parses the data column of a csv record, rejecting NaN and infinite
+    /// values unless they are allowed
+    fn parse_value(
+        &self,
+        try_record: Result<csv::StringRecord, csv::Error>,
+        data_index: usize,
+        index: usize,
+    ) -> anyhow::Result<f64> {
+        self.check_value(parse_record(try_record, data_index, index)?, index)
+    }
+
+    /// rejects NaN and infinite values unless they are allowed
+    fn check_value(&self, value: f64, index: usize) -> anyhow::Result<f64> {
+        if !value.is_finite() && !self.allow_nonfinite {
+            anyhow::bail!("csv entry is not a finite number. {}", index + 1);
+        }
+
+        Ok(value)
+    }
+
+    /// retrieves the index of the column that contains the time of each row
+    /// if one was specified
+    fn get_time_index<R: Read>(
+        &self,
+        reader: &mut csv::Reader<R>,
+    ) -> anyhow::Result<Option<usize>> {
+        let Some(column) = &self.time_column else {
+            return Ok(None);
+        };
+
+        reader
+            .headers()
+            .context("failed to retrieve csv headers")?
+            .iter()
+            .position(|header| header == column)
+            .context("failed to find the time csv column")
+            .map(Some)
+    }
+
+    /// parses the given csv file into a lookup table that supports
+    /// interpolation
+    pub fn get_callable(self) -> anyhow::Result<summation::InterpolateLookup> {
+        if let Some(max) = self.max_memory {
+            let estimate = self.estimate_memory()?;
+
+            if estimate > max {
+                anyhow::bail!(
+                    "estimated memory usage of {estimate} bytes exceeds the max memory of {max} bytes"
+                );
+            }
+        }
+
+        let mut rtn = Vec::new();
+        let mut reader = self.get_csv_reader()?;
+        let data_index = self.get_data_index(&mut reader)?;
+        let time_index = self.get_time_index(&mut reader)?;
+        let mut times = Vec::new();
+
+        let records = reader.records();
+
+        for (index, try_record) in records.enumerate() {
+            let Some(time_index) = time_index else {
+                rtn.push(self.parse_value(try_record, data_index, index)?);
+
+                continue;
+            };
+
+            let record = get_record(try_record, index)?;
+            let time = parse_field(&record, time_index, index)?;
+
+            if !time.is_finite() {
+                anyhow::bail!("csv time is not a finite number. {}", index + 1);
+            }
+
+            if times.last().is_some_and(|last| *last >= time) {
+                anyhow::bail!("csv time is not after the previous time. {}", index + 1);
+            }
+
+            times.push(time);
+            rtn.push(self.check_value(parse_field(&record, data_index, index)?, index)?);
+        }
+
+        self.preprocess(&mut rtn)?;
+
+        let rtn = if time_index.is_some() {
+            InterpolateLookup::from_pairs(times.into_iter().zip(rtn).collect())
+        } else {
+            InterpolateLookup::from(rtn)
+        };
+
+        let rtn = if let Some(window) = self.smooth {
+            rtn.smoothed(window as usize)
+        } else {
+            rtn
+        };
+
+        if let Some(new_len) = self.resample {
+            Ok(rtn.resample(new_len as usize))
+        } else {
+            Ok(rtn)
+        }
+    }
+
+    /// parses the mask column of the csv file if one was specified
+    ///
+    /// any non zero value keeps the sample. the mask is padded with kept
+    /// samples to match the padding of the loaded values
+    pub fn get_mask(&self) -> anyhow::Result<Option<Vec<bool>>> {
+        let Some(column) = &self.mask_column else {
+            return Ok(None);
+        };
+
+        self.check_not_stdin("a mask column")?;
+
+        let mut reader = self.get_csv_reader()?;
+        let mask_index = reader
+            .headers()
+            .context("failed to retrieve csv headers")?
+            .iter()
+            .position(|header| header == column)
+            .context("failed to find the mask csv column")?;
+
+        let mut rtn = Vec::new();
+
+        for (index, try_record) in reader.records().enumerate() {
+            rtn.push(parse_record(try_record, mask_index, index)? != 0.0);
+        }
+
+        if let Some(pad_to) = self.pad_to {
+            if rtn.len() < pad_to {
+                rtn.resize(pad_to, true);
+            }
+        }
+
+        Ok(Some(rtn))
+    }
+
+    /// applies the requested transforms to the loaded values before they are
+    /// placed into the lookup table
+    fn preprocess(&self, values: &mut Vec<f64>) -> anyhow::Result<()> {
+        if let Some(marker) = self.start_marker {
+            let index = values
+                .iter()
+                .position(|v| *v == marker)
+                .context("failed to find the start marker")?;
+
+            values.drain(..=index);
+        }
+
+        if let Some(marker) = self.end_marker {
+            let index = values
+                .iter()
+                .position(|v| *v == marker)
+                .context("failed to find the end marker")?;
+
+            values.truncate(index);
+        }
+
+        if let Some(mass) = self.force_mass {
+            if !mass.is_finite() || mass <= 0.0 {
+                anyhow::bail!("force mass must be greater than zero");
+            }
+
+            for value in values.iter_mut() {
+                *value /= mass;
+            }
+        }
+
+        if let Some(filter) = self.savgol {
+            *values = filter
+                .apply(values)
+                .context("failed to apply savgol filter")?;
+        }
+
+        if let Some(step) = self.quantize {
+            if !step.is_finite() || step <= 0.0 {
+                anyhow::bail!("quantize step must be greater than zero");
+            }
+
+            for value in values.iter_mut() {
+                *value = (*value / step).round() * step;
+            }
+        }
+
+        if let Some(pad_to) = self.pad_to {
+            let fill = match self.pad_value {
+                PadValue::Last => values.last().copied().unwrap_or(0.0),
+                PadValue::Value(value) => value,
+            };
+
+            if values.len() < pad_to {
+                values.resize(pad_to, fill);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// validates the given csv file and reports any problems found without
+    /// stopping at the first one
+    pub fn validate(&self) -> anyhow::Result<CsvReport> {
+        let delimiter = self.detect_delimiter()?;
+        let mut reader = self.get_csv_reader()?;
+
+        // without headers this is the first row which is not consumed so the
+        // columns are listed by their index instead
+        let headers = reader.headers().context("failed to retrieve csv headers")?;
+        let columns = if self.column.is_some() {
+            headers.iter().map(String::from).collect()
+        } else {
+            (0..headers.len()).map(|index| index.to_string()).collect()
+        };
+
+        let data_index = match self.get_data_index(&mut reader) {
+            Err(err) if delimiter != self.delimiter => {
+                return Err(err.context(format!(
+                    "the detected delimiter {:?} does not match the parsed delimiter {:?}",
+                    delimiter as char, self.delimiter as char
+                )));
+            }
+            result => result?,
+        };
+        let mut rtn = CsvReport {
+            rows: 0,
+            columns,
+            delimiter,
+            parsed_delimiter: self.delimiter,
+            errors: 0,
+            first_error: None,
+        };
+
+        for (index, try_record) in reader.records().enumerate() {
+            rtn.rows += 1;
+
+            if let Err(err) = self.parse_value(try_record, data_index, index) {
+                rtn.errors += 1;
+
+                if rtn.first_error.is_none() {
+                    rtn.first_error = Some((index + 1, format!("{err:#}")));
+                }
+            }
+        }
+
+        Ok(rtn)
+    }
+}
+
+/// parses a given amount of bytes with an optional K, M, or G suffix
+fn parse_bytes(given: &str) -> Result<u64, String> {
+    let trimmed = given.trim();
+    let (number, multiplier) = match trimmed.char_indices().last() {
+        Some((index, 'K' | 'k')) => (&trimmed[..index], 1024),
+        Some((index, 'M' | 'm')) => (&trimmed[..index], 1024 * 1024),
+        Some((index, 'G' | 'g')) => (&trimmed[..index], 1024 * 1024 * 1024),
+        _ => (trimmed, 1),
+    };
+
+    let value = u64::from_str(number).map_err(|err| format!("invalid amount of bytes: {err}"))?;
+
+    value
+        .checked_mul(multiplier)
+        .ok_or_else(|| String::from("amount of bytes is too large"))
+}
+
+/// parses a single byte such as a csv delimiter. \t is accepted for tabs
+fn parse_byte(given: &str) -> Result<u8, String> {
+    match given.as_bytes() {
+        [byte] => Ok(*byte),
+        b"\\t" => Ok(b'\t'),
+        _ => Err(format!("expected a single byte but got {given:?}")),
+    }
+}
+
+/// parses a non-negative amount of seconds. values too large for a duration
+/// are treated as the largest duration
+fn parse_seconds(given: &str) -> Result<Duration, String> {
+    let seconds = f64::from_str(given).map_err(|err| format!("invalid seconds: {err}"))?;
+
+    if seconds.is_nan() || seconds < 0.0 {
+        return Err(String::from("seconds must not be negative"));
+    }
+
+    Ok(Duration::try_from_secs_f64(seconds).unwrap_or(Duration::MAX))
+}
+
+/// parses the savgol filter options given as "window,order"
+fn parse_savgol(given: &str) -> Result<SavGol, String> {
+    let (window, order) = given
+        .split_once(',')
+        .ok_or_else(|| String::from("expected savgol as \"window,order\""))?;
+
+    let window =
+        usize::from_str(window.trim()).map_err(|err| format!("invalid savgol window: {err}"))?;
+    let order =
+        usize::from_str(order.trim()).map_err(|err| format!("invalid savgol order: {err}"))?;
+
+    Ok(SavGol { window, order })
+}
+
+/// parses a fourier term given as "amplitude,frequency,phase"
+fn parse_term(given: &str) -> Result<FourierTerm, String> {
+    let values = given
+        .split(',')
+        .map(|value| f64::from_str(value.trim()))
+        .collect::<Result<Vec<f64>, _>>()
+        .map_err(|err| format!("invalid fourier term: {err}"))?;
+
+    match values.as_slice() {
+        [amplitude, frequency, phase] => Ok(FourierTerm {
+            amplitude: *amplitude,
+            frequency: *frequency,
+            phase: *phase,
+        }),
+        _ => Err(String::from(
+            "expected fourier term as \"amplitude,frequency,phase\"",
+        )),
+    }
+}
+
+/// retrieves the csv record at the given index
+fn get_record(
+    try_record: Result<csv::StringRecord, csv::Error>,
+    index: usize,
+) -> anyhow::Result<csv::StringRecord> {
+    try_record.with_context(|| format!("failed to retrieve csv entry. {}", index + 1))
+}
+
+/// parses the value of the given column from the csv record
+fn parse_field(record: &csv::StringRecord, data_index: usize, index: usize) -> anyhow::Result<f64> {
+    let value = record
+        .get(data_index)
+        .with_context(|| format!("failed to retrieve csv entry column. {}", index + 1))?;
+
+    f64::from_str(value)
+        .with_context(|| format!("failed to convert csv entry into float. {}", index + 1))
+}
+
+/// parses the acceleration value from the given csv record
+fn parse_record(
+    try_record: Result<csv::StringRecord, csv::Error>,
+    data_index: usize,
+    index: usize,
+) -> anyhow::Result<f64> {
+    parse_field(&get_record(try_record, index)?, data_index, index)
+}
+
+/// the results of validating a csv file
+#[derive(Debug)]
+pub struct CsvReport {
+    /// the total number of data rows found
+    pub rows: usize,
+    /// the column names of the csv file if headers are expected otherwise the
+    /// index of each column
+    pub columns: Vec<String>,
+    /// the delimiter detected from the first line of the file
+    pub delimiter: u8,
+    /// the delimiter that the rows were parsed with
+    pub parsed_delimiter: u8,
+    /// the total number of rows that failed to parse
+    pub errors: usize,
+    /// the row number and message of the first row that failed to parse
+    pub first_error: Option<(usize, String)>,
+}
+
+impl Display for CsvReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        writeln!(f, "rows: {}", self.rows)?;
+
+        writeln!(f, "columns: {}", self.columns.join(", "))?;
+        write!(f, "delimiter: {:?}", self.delimiter as char)?;
+
+        if self.delimiter != self.parsed_delimiter {
+            write!(
+                f,
+                " does not match the parsed delimiter {:?}",
+                self.parsed_delimiter as char
+            )?;
+        }
+
+        writeln!(f)?;
+        write!(f, "errors: {}", self.errors)?;
+
+        if let Some((row, msg)) = &self.first_error {
+            write!(f, "\nfirst error: row {row}: {msg}")?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::io::Write;
+
+    use approx::assert_relative_eq;
+
+    use super::*;
+
+    fn csv_file(contents: &str) -> tempfile::NamedTempFile {
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(contents.as_bytes()).unwrap();
+        file
+    }
+
+    fn csv_sim(file: &tempfile::NamedTempFile, args: &[&str]) -> CsvSim {
+        let path = file.path().to_str().unwrap();
+        let app = App::parse_from(["train_sim", "csv"].iter().chain(args).chain([&path]));
+
+        match app.sim {
+            SimKind::Csv(csv) => csv,
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn validate_good() {
+        let file = csv_file("time,accel\n0,1.0\n1,2.0\n2,3.0\n");
+        let report = csv_sim(&file, &["--column", "accel"]).validate().unwrap();
+
+        assert_eq!(report.columns, vec!["time", "accel"]);
+        assert_eq!(report.rows, 3);
+        assert_eq!(report.delimiter, b',');
+        assert_eq!(report.errors, 0);
+        assert!(report.first_error.is_none());
+    }
+
+    #[test]
+    fn validate_bad() {
+        let file = csv_file("1.0\n2.0\nbad\n4.0\nworse\n");
+        let report = csv_sim(&file, &[]).validate().unwrap();
+
+        assert_eq!(report.rows, 5);
+        assert_eq!(report.columns, vec!["0"]);
+        assert_eq!(report.errors, 2);
+        assert_eq!(report.first_error.map(|(row, _)| row), Some(3));
+    }
+
+    #[test]
+    fn validate_delimiter() {
+        let file = csv_file("time;accel\n0;1.0\n1;2.0\n");
+        let err = csv_sim(&file, &["--column", "accel"])
+            .validate()
+            .unwrap_err();
+
+        // the whole line is a single column when parsed with the wrong
+        // delimiter so the requested column cannot be found
+        assert_eq!(
+            err.to_string(),
+            "the detected delimiter ';' does not match the parsed delimiter ','"
+        );
+
+        let file = csv_file("0;1.0\n1;2.0\n");
+        let report = csv_sim(&file, &[]).validate().unwrap();
+
+        assert_eq!(report.columns, vec!["0"]);
+        assert_eq!(report.delimiter, b';');
+        assert_eq!(report.parsed_delimiter, b',');
+        assert_eq!(report.errors, 2);
+        assert!(report
+            .to_string()
+            .contains("delimiter: ';' does not match the parsed delimiter ','"));
+
+        let report = csv_sim(&file, &["--delimiter", ";"]).validate().unwrap();
+
+        assert_eq!(report.columns, vec!["0", "1"]);
+        assert_eq!(report.errors, 0);
+        assert!(!report.to_string().contains("does not match"));
+    }
+
+    #[test]
+    fn seconds() {
+        assert_eq!(parse_seconds("0"), Ok(Duration::ZERO));
+        assert_eq!(parse_seconds("2.5"), Ok(Duration::from_millis(2500)));
+        assert_eq!(parse_seconds("1e300"), Ok(Duration::MAX));
+        assert!(parse_seconds("-1").is_err());
+        assert!(parse_seconds("NaN").is_err());
+    }
+
+    #[test]
+    fn bytes() {
+        assert_eq!(parse_bytes("512"), Ok(512));
+        assert_eq!(parse_bytes("2K"), Ok(2048));
+        assert_eq!(parse_bytes("1M"), Ok(1024 * 1024));
+        assert_eq!(parse_bytes("1G"), Ok(1024 * 1024 * 1024));
+        assert!(parse_bytes("lots").is_err());
+    }
+
+    #[test]
+    fn max_memory() {
+        let file = csv_file(&"1.0\n".repeat(100));
+
+        let result = csv_sim(&file, &["--max-memory", "64"]).get_callable();
+        assert!(result.is_err());
+
+        let lookup = csv_sim(&file, &["--max-memory", "1M"])
+            .get_callable()
+            .unwrap();
+        assert_eq!(lookup.len(), 100);
+    }
+
+    #[test]
+    fn pad_zeros() {
+        let file = csv_file("1.0\n2.0\n3.0\n");
+        let original = csv_sim(&file, &[]).get_callable().unwrap();
+        let padded = csv_sim(&file, &["--pad-to", "6"]).get_callable().unwrap();
+
+        assert_eq!(padded.to_vec(), vec![1.0, 2.0, 3.0, 0.0, 0.0, 0.0]);
+
+        let expected =
+            summation::sum_panels(original.len(), 10, &original, summation::trapezoidal).unwrap();
+        let calc =
+            summation::sum_panels(original.len(), 10, &padded, summation::trapezoidal).unwrap();
+
+        assert_eq!(calc, expected);
+    }
+
+    #[test]
+    fn pad_last() {
+        let file = csv_file("1.0\n2.0\n");
+        let padded = csv_sim(&file, &["--pad-to", "4", "--pad-value", "last"])
+            .get_callable()
+            .unwrap();
+
+        assert_eq!(padded.to_vec(), vec![1.0, 2.0, 2.0, 2.0]);
+    }
+
+    #[test]
+    fn explain() {
+        let app = App::parse_from([
+            "train_sim",
+            "--explain",
+            "--algo",
+            "simpsons",
+            "csv",
+            "a.csv",
+        ]);
+
+        assert!(app.opts.explain);
+        assert!(app
+            .opts
+            .algo
+            .explain(app.opts.step, app.opts.levels)
+            .contains("O(h⁴)"));
+        assert!(AppAlgo::LeftRiemann.explain(100, 5).contains("O(h)"));
+        assert!(AppAlgo::TanhSinh.explain(20, 5).contains("singularities"));
+        assert!(AppAlgo::Romberg
+            .explain(100, 3)
+            .contains("O(h^8) with h = 1/8 from 3"));
+    }
+
+    #[test]
+    fn quantize() {
+        let file = csv_file("1.2\n1.3\n-0.2\n");
+        let lookup = csv_sim(&file, &["--quantize", "0.5"])
+            .get_callable()
+            .unwrap();
+
+        assert_eq!(lookup.to_vec(), vec![1.0, 1.5, 0.0]);
+
+        assert!(csv_sim(&file, &["--quantize", "0"]).get_callable().is_err());
+    }
+
+    #[test]
+    fn position_step() {
+        let coarse = App::parse_from(["train_sim", "--step", "2", "csv", "a.csv"]).opts;
+        let fine = App::parse_from([
+            "train_sim",
+            "--step",
+            "2",
+            "--position-step",
+            "100",
+            "csv",
+            "a.csv",
+        ])
+        .opts;
+
+        assert_eq!(fine.velocity_step(), 2);
+        assert_eq!(fine.position_step(), 100);
+
+        // constant acceleration of 1 for 10 seconds will have a final position
+        // of 50
+        let accel = InterpolateLookup::from(vec![1.0; 11]);
+        let position_error = |opts: &SimOpts| {
+            let vel = summation::cumulative_panels(
+                accel.len(),
+                opts.velocity_step(),
+                &accel,
+                opts.summation(),
+            )
+            .unwrap();
+            let pos =
+                summation::sum_panels(accel.len(), opts.position_step(), &vel, opts.summation())
+                    .unwrap();
+
+            (pos - 50.0).abs()
+        };
+
+        assert!(position_error(&fine) < position_error(&coarse));
+    }
+
+    #[test]
+    fn zero_steps() {
+        let opts = App::parse_from(["train_sim", "csv", "a.csv"]).opts;
+
+        assert_eq!(opts.check_steps(), Ok(()));
+
+        let opts = App::parse_from(["train_sim", "--velocity-step", "0", "csv", "a.csv"]).opts;
+
+        assert_eq!(
+            opts.check_steps(),
+            Err(summation::SummationError::ZeroIterations)
+        );
+    }
+
+    #[test]
+    fn adaptive_max_step() {
+        let parse = |args: &[&str]| {
+            App::parse_from(
+                ["train_sim", "--adaptive-max-step", "20"]
+                    .iter()
+                    .chain(args)
+                    .chain(&["csv", "a.csv"]),
+            )
+            .opts
+        };
+
+        assert!(parse(&[]).check_adaptive_max_step().is_ok());
+        assert!(parse(&["--integrator", "rk4"])
+            .check_adaptive_max_step()
+            .is_err());
+        assert!(parse(&["--algo", "romberg"])
+            .check_adaptive_max_step()
+            .is_err());
+        assert!(App::try_parse_from([
+            "train_sim",
+            "--adaptive-max-step",
+            "20",
+            "--quadrature-file",
+            "rule.csv",
+            "csv",
+            "a.csv",
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn lookup_option() {
+        let opts =
+            App::parse_from(["train_sim", "fourier", "--term", "1,1,0", "--duration", "2"]).opts;
+
+        assert_eq!(opts.lookup_option(), None);
+
+        let opts = App::parse_from([
+            "train_sim",
+            "--all-algos",
+            "--t-end",
+            "1",
+            "fourier",
+            "--term",
+            "1,1,0",
+            "--duration",
+            "2",
+        ])
+        .opts;
+
+        assert_eq!(opts.lookup_option(), Some("a time window"));
+    }
+
+    #[test]
+    fn precision_option() {
+        let opts = App::parse_from(["train_sim", "--compare-precision", "csv", "a.csv"]).opts;
+
+        assert_eq!(opts.precision_option(), None);
+
+        let opts = App::parse_from([
+            "train_sim",
+            "--compare-precision",
+            "--max-velocity",
+            "5",
+            "csv",
+            "a.csv",
+        ])
+        .opts;
+
+        assert_eq!(opts.precision_option(), Some("--max-velocity"));
+    }
+
+    #[test]
+    fn invalid_tolerance() {
+        for tolerance in ["0", "inf", "NaN"] {
+            let opts =
+                App::parse_from(["train_sim", "--tolerance", tolerance, "csv", "a.csv"]).opts;
+
+            assert_eq!(
+                opts.check_steps(),
+                Err(summation::SummationError::InvalidTolerance)
+            );
+        }
+    }
+
+    #[test]
+    fn markers() {
+        let mut contents = String::new();
+
+        for row in 0..20 {
+            if row == 5 || row == 15 {
+                contents.push_str("-999\n");
+            } else {
+                contents.push_str(&format!("{row}\n"));
+            }
+        }
+
+        let file = csv_file(&contents);
+        let lookup = csv_sim(&file, &["--start-marker", "-999", "--end-marker", "-999"])
+            .get_callable()
+            .unwrap();
+
+        assert_eq!(
+            lookup.to_vec(),
+            (6..15).map(|v| v as f64).collect::<Vec<f64>>()
+        );
+
+        assert!(csv_sim(&file, &["--start-marker", "-1"])
+            .get_callable()
+            .is_err());
+    }
+
+    #[test]
+    fn json_profile() {
+        let load = |contents: &str| {
+            let file = csv_file(contents);
+            let app = App::parse_from(["train_sim", "json", file.path().to_str().unwrap()]);
+
+            let SimKind::Json(sim) = app.sim else {
+                unreachable!();
+            };
+
+            sim.get_callable()
+        };
+
+        let values = load("[1.0, 2.5, -3]").unwrap();
+
+        assert_eq!(values.xs(), None);
+        assert_eq!(values.to_vec(), vec![1.0, 2.5, -3.0]);
+
+        let samples = load(r#"[{"time": 0, "accel": 1}, {"time": 0.5, "accel": 2}]"#).unwrap();
+
+        assert_eq!(samples.xs(), Some([0.0, 0.5].as_slice()));
+        assert_eq!(samples.to_vec(), vec![1.0, 2.0]);
+
+        let err = load(r#"[{"time": 1, "accel": 1}, {"time": 1, "accel": 2}]"#).unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "json time is not after the previous time. 2"
+        );
+        assert!(load(r#"{"accel": [1, 2]}"#).is_err());
+    }
+
+    #[test]
+    fn fourier_terms() {
+        let app = App::parse_from([
+            "train_sim",
+            "fourier",
+            "--term",
+            "2,0.5,0",
+            "--term",
+            "-1, 1, 1.5",
+            "--duration",
+            "9.5",
+        ]);
+
+        let SimKind::Fourier(sim) = app.sim else {
+            panic!("expected fourier sim");
+        };
+
+        assert_eq!(sim.terms.len(), 2);
+        assert_eq!(sim.terms[1].amplitude, -1.0);
+        assert_eq!(sim.terms[1].phase, 1.5);
+        assert_eq!(sim.length().unwrap(), None);
+
+        let times = sim.times().unwrap();
+
+        assert_eq!(times.len(), 11);
+        assert_eq!(times[9], 9.0);
+        assert_eq!(times[10], 9.5);
+
+        let whole = FourierSim {
+            duration: 10.0,
+            ..sim
+        };
+
+        assert_eq!(whole.length().unwrap(), Some(11));
+        assert_eq!(whole.times().unwrap().last(), Some(&10.0));
+
+        assert!(parse_term("1,2").is_err());
+        assert!(parse_term("1,a,2").is_err());
+    }
+
+    #[test]
+    fn dump_nodes() {
+        let nodes = AppAlgo::Trapezoidal.nodes(0.0, 1.0, 2).unwrap();
+
+        assert_eq!(nodes, Some(vec![(0.0, 0.25), (0.5, 0.5), (1.0, 0.25)]));
+        assert_eq!(AppAlgo::AdaptiveSimpson.nodes(0.0, 1.0, 2).unwrap(), None);
+
+        // the weighted sum of the nodes should match the summation function
+        let curve = |x: f64| 4.0 * x - x * x;
+
+        for algo in AppAlgo::value_variants() {
+            let Some(nodes) = algo.nodes(0.0, 3.0, 6).unwrap() else {
+                continue;
+            };
+            let weighted = nodes
+                .into_iter()
+                .map(|(x, weight)| curve(x) * weight)
+                .sum::<f64>();
+
+            assert_relative_eq!(weighted, algo.summation()(0.0, 3.0, 6, &curve).unwrap());
+        }
+    }
+
+    #[test]
+    fn grade_csv() {
+        let file = csv_file("-2\n-2\n");
+        let path = file.path().to_str().unwrap();
+
+        let opts = App::parse_from(["train_sim", "--grade-csv", path, "csv", "a.csv"]).opts;
+        let grade = opts.grade().unwrap().unwrap();
+
+        assert_eq!(grade.len(), 2);
+        assert_relative_eq!(grade.get_index(0.0), (-0.02f64).atan());
+
+        let opts = App::parse_from([
+            "train_sim",
+            "--grade-csv",
+            path,
+            "--grade-unit",
+            "degrees",
+            "csv",
+            "a.csv",
+        ])
+        .opts;
+
+        assert_relative_eq!(
+            opts.grade().unwrap().unwrap().get_index(1.0),
+            (-2.0f64).to_radians()
+        );
+    }
+
+    #[test]
+    fn endpoint() {
+        let file = csv_file("1\n1\n1\n");
+
+        let position = |mode: &str| {
+            let app = App::parse_from([
+                "train_sim",
+                "--algo",
+                "trapezoidal",
+                "--endpoint",
+                mode,
+                "csv",
+                file.path().to_str().unwrap(),
+            ]);
+            let SimKind::Csv(sim) = app.sim else {
+                panic!("expected csv sim");
+            };
+
+            let mut accel = sim.get_callable().unwrap();
+            app.opts.apply_endpoint(&mut accel);
+
+            let vel = summation::cumulative_panels(
+                accel.len(),
+                app.opts.step,
+                &accel,
+                app.opts.summation(),
+            )
+            .unwrap();
+
+            summation::sum_panels(accel.len(), app.opts.step, &vel, app.opts.summation()).unwrap()
+        };
+
+        // constant acceleration of 1 for 2 seconds vs 3 seconds
+        assert_relative_eq!(position("exclusive"), 2.0);
+        assert_relative_eq!(position("inclusive"), 4.5);
+    }
+
+    #[test]
+    fn savgol() {
+        let file = csv_file("0\n1\n4\n9\n16\n25\n36\n");
+
+        let lookup = csv_sim(&file, &["--savgol", "5,2"]).get_callable().unwrap();
+
+        for (index, value) in lookup.to_vec().into_iter().enumerate() {
+            assert_relative_eq!(value, (index * index) as f64, epsilon = 1e-9);
+        }
+
+        let smoothed = csv_sim(&file, &["--savgol", "5,1"]).get_callable().unwrap();
+
+        assert!((smoothed.get_index(3.0) - 9.0).abs() > 1.0);
+        assert!(csv_sim(&file, &["--savgol", "9,2"]).get_callable().is_err());
+    }
+
+    #[test]
+    fn force_mass() {
+        let file = csv_file("100\n-50\n20\n");
+
+        let light = csv_sim(&file, &["--force-mass", "10"])
+            .get_callable()
+            .unwrap();
+        let heavy = csv_sim(&file, &["--force-mass", "20"])
+            .get_callable()
+            .unwrap();
+
+        assert_eq!(light.to_vec(), vec![10.0, -5.0, 2.0]);
+
+        for (light, heavy) in light.iter().zip(heavy.iter()) {
+            assert_eq!(heavy, light / 2.0);
+        }
+
+        let err = csv_sim(&file, &["--force-mass", "0"])
+            .get_callable()
+            .unwrap_err();
+
+        assert_eq!(err.to_string(), "force mass must be greater than zero");
+        assert!(csv_sim(&file, &["--force-mass", "-5"])
+            .get_callable()
+            .is_err());
+    }
+
+    #[test]
+    fn smooth() {
+        let file = csv_file("0.1\n0.2\n9\n0.3\n0\n0\n");
+
+        let lookup = csv_sim(&file, &["--smooth", "3"]).get_callable().unwrap();
+
+        let expected = [0.15, 9.3 / 3.0, 9.5 / 3.0, 9.3 / 3.0, 0.1, 0.0];
+
+        for (value, expected) in lookup.to_vec().into_iter().zip(expected) {
+            assert_relative_eq!(value, expected, epsilon = 1e-12);
+        }
+
+        let lookup = csv_sim(&file, &["--smooth", "1"]).get_callable().unwrap();
+
+        assert_eq!(lookup.to_vec(), vec![0.1, 0.2, 9.0, 0.3, 0.0, 0.0]);
+        assert!(App::try_parse_from(["train_sim", "--smooth", "0", "csv", "a.csv"]).is_err());
+    }
+
+    #[test]
+    fn resample() {
+        let file = csv_file("0\n1\n2\n3\n4\n5\n6\n7\n8\n");
+
+        let lookup = csv_sim(&file, &["--resample", "5"]).get_callable().unwrap();
+
+        assert_eq!(lookup.xs(), Some([0.0, 2.0, 4.0, 6.0, 8.0].as_slice()));
+        assert_eq!(lookup.to_vec(), vec![0.0, 2.0, 4.0, 6.0, 8.0]);
+
+        assert_eq!(
+            csv_sim(&file, &["--resample", "5"]).pairs_source(),
+            "--resample"
+        );
+        assert_eq!(
+            csv_sim(&file, &["--column", "accel", "--time-column", "time"]).pairs_source(),
+            "a time column"
+        );
+    }
+
+    #[test]
+    fn mask_column() {
+        let file = csv_file("accel,valid\n1.0,1\n2.0,0\n3.0,1\n");
+
+        let sim = csv_sim(&file, &["--column", "accel", "--mask-column", "valid"]);
+
+        assert_eq!(sim.get_mask().unwrap(), Some(vec![true, false, true]));
+        assert_eq!(
+            csv_sim(&file, &["--column", "accel"]).get_mask().unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn delimiter() {
+        let file = csv_file("time;accel\n0;1.5\n1;2.5\n");
+        let lookup = csv_sim(&file, &["--column", "accel", "--delimiter", ";"])
+            .get_callable()
+            .unwrap();
+
+        assert_eq!(lookup.to_vec(), vec![1.5, 2.5]);
+
+        let file = csv_file("0\t1.5\n1\t2.5\n");
+        let lookup = csv_sim(&file, &["--delimiter", "\\t"])
+            .get_callable()
+            .unwrap();
+
+        assert_eq!(lookup.to_vec(), vec![0.0, 1.0]);
+        assert!(App::try_parse_from(["train_sim", "csv", "--delimiter", ";;", "a.csv"]).is_err());
+    }
+
+    #[test]
+    fn comment_lines() {
+        let file = csv_file("# sensor: a\ntime,accel\n0,1.5\n# paused\n1,2.5\n#end\n");
+        let lookup = csv_sim(&file, &["--column", "accel", "--comment", "#"])
+            .get_callable()
+            .unwrap();
+
+        assert_eq!(lookup.to_vec(), vec![1.5, 2.5]);
+        assert!(csv_sim(&file, &["--column", "accel"])
+            .get_callable()
+            .is_err());
+    }
+
+    #[test]
+    fn column_index() {
+        let file = csv_file("0,1.5\n1,2.5\n");
+
+        assert_eq!(
+            csv_sim(&file, &["--column-index", "1"])
+                .get_callable()
+                .unwrap()
+                .to_vec(),
+            vec![1.5, 2.5]
+        );
+        assert_eq!(
+            csv_sim(&file, &[]).get_callable().unwrap().to_vec(),
+            vec![0.0, 1.0]
+        );
+        assert!(App::try_parse_from([
+            "train_sim",
+            "csv",
+            "--column",
+            "accel",
+            "--column-index",
+            "1",
+            "a.csv"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn nonfinite_values() {
+        let file = csv_file("1.0\nNaN\n3.0\n");
+        let err = csv_sim(&file, &[]).get_callable().unwrap_err();
+
+        assert_eq!(err.to_string(), "csv entry is not a finite number. 2");
+
+        let lookup = csv_sim(&file, &["--allow-nonfinite"])
+            .get_callable()
+            .unwrap();
+
+        assert!(lookup.get_index(1.0).is_nan());
+    }
+
+    #[test]
+    fn max_gap() {
+        let file = csv_file("time,accel\n0,1.0\n0.5,2.0\n4,3.0\n4.5,1.0\n");
+        let lookup = csv_sim(&file, &["--column", "accel", "--time-column", "time"])
+            .get_callable()
+            .unwrap();
+
+        let opts = App::parse_from(["train_sim", "--max-gap", "3", "csv", "a.csv"]).opts;
+        let err = opts.check_gaps(&lookup).unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "gap of 3.5 seconds is larger than the max gap of 3. 3"
+        );
+
+        let opts = App::parse_from(["train_sim", "--max-gap", "3.5", "csv", "a.csv"]).opts;
+
+        assert!(opts.check_gaps(&lookup).is_ok());
+
+        // evenly spaced profiles have no gaps to check
+        let opts = App::parse_from(["train_sim", "--max-gap", "0.1", "csv", "a.csv"]).opts;
+
+        assert!(opts
+            .check_gaps(&InterpolateLookup::from(vec![1.0, 2.0]))
+            .is_ok());
+    }
+
+    #[test]
+    fn time_column() {
+        let file = csv_file("time,accel\n0,1.0\n0.5,2.0\n2,3.0\n");
+        let lookup = csv_sim(&file, &["--column", "accel", "--time-column", "time"])
+            .get_callable()
+            .unwrap();
+
+        assert_eq!(lookup.xs(), Some([0.0, 0.5, 2.0].as_slice()));
+        assert_eq!(lookup.call(1.25), 2.5);
+
+        for (contents, message) in [
+            (
+                "time,accel\n0,1.0\n2,2.0\n1,3.0\n",
+                "csv time is not after the previous time. 3",
+            ),
+            (
+                "time,accel\n0,1.0\n0,2.0\n",
+                "csv time is not after the previous time. 2",
+            ),
+            (
+                "time,accel\nNaN,1.0\n",
+                "csv time is not a finite number. 1",
+            ),
+        ] {
+            let file = csv_file(contents);
+            let err = csv_sim(&file, &["--column", "accel", "--time-column", "time"])
+                .get_callable()
+                .unwrap_err();
+
+            assert_eq!(err.to_string(), message);
+        }
+    }
+
+    #[test]
+    fn stdin_path() {
+        let mut sim = CsvSim::from_path(PathBuf::from("-"));
+
+        assert!(sim.is_stdin());
+        assert!(!CsvSim::from_path(PathBuf::from("a.csv")).is_stdin());
+
+        // these read the csv more than once so they are refused before
+        // anything is read from stdin
+        sim.column = Some(String::from("accel"));
+        sim.mask_column = Some(String::from("valid"));
+
+        assert!(sim.get_mask().is_err());
+        assert!(sim.validate().is_err());
+    }
+
+    #[test]
+    fn quadrature_file() {
+        let file = csv_file("-0.5773502691896257,1\n0.5773502691896257,1\n");
+        let path = file.path().to_str().unwrap();
+
+        let opts = App::parse_from(["train_sim", "--quadrature-file", path, "csv", "a.csv"]).opts;
+        let rule = opts.quadrature_rule().unwrap().unwrap();
+
+        assert_eq!(rule.len(), 2);
+        assert_relative_eq!(rule[1].0, 1.0 / 3.0f64.sqrt());
+        assert_eq!(rule[0].1, 1.0);
+
+        let bad = csv_file("1.5,1\n");
+        let path = bad.path().to_str().unwrap();
+        let opts = App::parse_from(["train_sim", "--quadrature-file", path, "csv", "a.csv"]).opts;
+
+        assert!(opts.quadrature_rule().is_err());
+    }
+
+    #[test]
+    fn generate_ramp() {
+        let generate = |args: &[&str]| {
+            let app = App::parse_from(["train_sim", "generate"].iter().chain(args));
+
+            match app.sim {
+                SimKind::Generate(generate) => generate.profile().unwrap(),
+                _ => unreachable!(),
+            }
+        };
+
+        let ramp = generate(&["--length", "5", "ramp"]);
+
+        assert_eq!(ramp.to_vec(), vec![0.0, 1.0, 2.0, 3.0, 4.0]);
+
+        let step = generate(&["--length", "4", "--amplitude", "-2", "step"]);
+
+        assert_eq!(step.to_vec(), vec![0.0, 0.0, -2.0, -2.0]);
+
+        let trapezoid = generate(&["--length", "6", "--ramp", "2", "trapezoid"]);
+
+        assert_eq!(trapezoid.to_vec(), vec![1.0, 1.0, 0.0, 0.0, -1.0, -1.0]);
+    }
+
+    #[test]
+    fn time_window() {
+        let window = |args: &[&str]| {
+            let mut parse = vec!["train_sim"];
+            parse.extend(args);
+            parse.extend(["csv", "a.csv"]);
+
+            let opts = App::parse_from(parse).opts;
+            let mut accel = InterpolateLookup::from(vec![2.0; 31]);
+            opts.apply_window(&mut accel);
+
+            let vel = summation::cumulative_panels(accel.len(), 10, &accel, summation::trapezoidal)
+                .unwrap();
+
+            (accel.len(), vel.last().unwrap())
+        };
+
+        assert_eq!(window(&[]), (31, 60.0));
+        assert_eq!(window(&["--t-start", "10", "--t-end", "20"]), (11, 20.0));
+        assert_eq!(window(&["--t-start", "25", "--t-end", "100"]), (6, 10.0));
+        assert_eq!(window(&["--t-start", "40"]), (1, 0.0));
+    }
+
+    #[test]
+    fn negative_velocity() {
+        let opts = |args: &[&str]| {
+            let mut parse = vec!["train_sim"];
+            parse.extend(args);
+            parse.extend(["csv", "a.csv"]);
+
+            App::parse_from(parse).opts
+        };
+        let braking = InterpolateLookup::from(vec![1.0, 0.0, -2.0]);
+
+        assert_eq!(
+            opts(&["--clamp-velocity-nonneg", "--max-velocity", "-1"]).velocity_bounds(),
+            (0.0, 0.0)
+        );
+        assert!(opts(&[]).check_velocity(&braking).is_ok());
+        assert!(opts(&["--error-on-negative-velocity"])
+            .check_velocity(&braking)
+            .is_err());
+        assert!(App::try_parse_from([
+            "train_sim",
+            "--clamp-velocity-nonneg",
+            "--error-on-negative-velocity",
+            "csv",
+            "a.csv",
+        ])
+        .is_err());
     }
 }
@@ -0,0 +1,353 @@
+//! running simulations over multiple csv files and collecting the results
+
+use std::io::{Read, Write};
+use std::path::PathBuf;
+
+use anyhow::Context;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::args::{CsvSim, SimOpts};
+use crate::sim;
+
+/// the final results of a single file in a batch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchResult {
+    /// the path of the csv file
+    pub path: PathBuf,
+    /// the total number of values loaded from the file
+    pub length: usize,
+    /// the final calculated velocity
+    pub final_velocity: f64,
+    /// the final calculated position
+    pub final_position: f64,
+}
+
+/// runs the simulation once for each of the given csv files
+///
+/// when parallel is true each file will be run on the global rayon thread
+/// pool. the results will be in the same order as the given files. each file
+/// is simulated once the same as the csv subcommand would, see
+/// [`sim::simulate_profile`]
+pub fn run(sims: Vec<CsvSim>, opts: &SimOpts, parallel: bool) -> anyhow::Result<Vec<BatchResult>> {
+    let single = opts.single_iteration();
+
+    let run_file = |sim: CsvSim| -> anyhow::Result<BatchResult> {
+        let path = sim.path.clone();
+        let pairs_source = sim.pairs_source();
+        let mask = sim
+            .get_mask()
+            .with_context(|| format!("failed to load batch file mask. {}", path.display()))?;
+
+        if mask.is_some() && (opts.t_start.is_some() || opts.t_end.is_some()) {
+            anyhow::bail!("a mask cannot be used with a time window");
+        }
+
+        let mut accel_lookup = sim
+            .get_callable()
+            .with_context(|| format!("failed to load batch file. {}", path.display()))?;
+        opts.check_gaps(&accel_lookup)
+            .with_context(|| format!("invalid batch file. {}", path.display()))?;
+        opts.apply_window(&mut accel_lookup);
+        opts.apply_endpoint(&mut accel_lookup);
+
+        let length = accel_lookup.len();
+
+        let result = if let Some(times) = accel_lookup.xs() {
+            if let Some(option) = opts.uniform_option() {
+                anyhow::bail!("{option} cannot be used with {pairs_source}");
+            }
+
+            sim::simulate_times(times, &single, false, &accel_lookup, |_, _| {})
+        } else {
+            sim::simulate_profile(
+                length,
+                &single,
+                false,
+                &accel_lookup,
+                mask.as_deref(),
+                |_, _| {},
+            )
+        }
+        .with_context(|| format!("failed to simulate batch file. {}", path.display()))?;
+
+        Ok(BatchResult {
+            path,
+            length,
+            final_velocity: result.final_velocity,
+            final_position: result.final_position,
+        })
+    };
+
+    if parallel {
+        sims.into_par_iter().map(run_file).collect()
+    } else {
+        sims.into_iter().map(run_file).collect()
+    }
+}
+
+/// writes the given batch results as csv to the provided writer with one row
+/// per file
+pub fn write_results<W>(writer: W, results: &[BatchResult]) -> anyhow::Result<()>
+where
+    W: Write,
+{
+    let mut csv_writer = csv::Writer::from_writer(writer);
+
+    csv_writer
+        .write_record(["path", "length", "final_velocity", "final_position"])
+        .context("failed to write batch results header")?;
+
+    for result in results {
+        csv_writer
+            .write_record([
+                result.path.display().to_string(),
+                result.length.to_string(),
+                result.final_velocity.to_string(),
+                result.final_position.to_string(),
+            ])
+            .context("failed to write batch result")?;
+    }
+
+    csv_writer
+        .flush()
+        .context("failed to flush batch results")?;
+
+    Ok(())
+}
+
+/// the values that a list of batch results can be sorted by
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Metric {
+    /// the path of the csv file
+    Path,
+    /// the total number of values loaded
+    Length,
+    /// the final calculated velocity
+    FinalVelocity,
+    /// the final calculated position
+    FinalPosition,
+}
+
+/// sorts the given batch results in ascending order of the given metric
+pub fn sort_results(results: &mut [BatchResult], metric: Metric) {
+    match metric {
+        Metric::Path => results.sort_by(|a, b| a.path.cmp(&b.path)),
+        Metric::Length => results.sort_by_key(|result| result.length),
+        Metric::FinalVelocity => {
+            results.sort_by(|a, b| a.final_velocity.total_cmp(&b.final_velocity))
+        }
+        Metric::FinalPosition => {
+            results.sort_by(|a, b| a.final_position.total_cmp(&b.final_position))
+        }
+    }
+}
+
+/// the contents of a results json file which is either a single result or a
+/// list of results
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ResultsJson {
+    Single(BatchResult),
+    List(Vec<BatchResult>),
+}
+
+/// reads the batch results from the given json reader
+///
+/// the json can either be a single result or a list of results
+pub fn read_results<R>(reader: R) -> anyhow::Result<Vec<BatchResult>>
+where
+    R: Read,
+{
+    let results = serde_json::from_reader(reader).context("failed to parse results json")?;
+
+    match results {
+        ResultsJson::Single(result) => Ok(vec![result]),
+        ResultsJson::List(list) => Ok(list),
+    }
+}
+
+/// reads and combines the batch results from each of the given json files
+///
+/// the results will be in the same order as the given files
+pub fn merge_results(paths: &[PathBuf]) -> anyhow::Result<Vec<BatchResult>> {
+    let mut rtn = Vec::new();
+
+    for path in paths {
+        let file = std::fs::File::open(path)
+            .with_context(|| format!("failed to open results file. {}", path.display()))?;
+
+        rtn.extend(
+            read_results(std::io::BufReader::new(file))
+                .with_context(|| format!("failed to read results file. {}", path.display()))?,
+        );
+    }
+
+    Ok(rtn)
+}
+
+/// writes the given batch results as a json list to the provided writer
+pub fn write_results_json<W>(writer: W, results: &[BatchResult]) -> anyhow::Result<()>
+where
+    W: Write,
+{
+    crate::output::write_json(writer, results)
+}
+
+#[cfg(test)]
+mod test {
+    use approx::assert_relative_eq;
+    use clap::Parser;
+
+    use super::*;
+    use crate::args::{App, SimKind};
+
+    #[test]
+    fn two_files() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.csv"), "1.0\n1.0\n1.0\n").unwrap();
+        std::fs::write(dir.path().join("b.csv"), "2.0\n2.0\n2.0\n2.0\n").unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "not a profile").unwrap();
+
+        let app = App::parse_from([
+            "train_sim",
+            "--algo",
+            "trapezoidal",
+            "--step",
+            "10",
+            "batch",
+            dir.path().to_str().unwrap(),
+        ]);
+        let SimKind::Batch(batch) = app.sim else {
+            unreachable!();
+        };
+
+        let sims = batch.csv.batch_files().unwrap();
+        let results = run(sims, &app.opts, false).unwrap();
+
+        assert_eq!(results.len(), 2);
+        assert_relative_eq!(results[0].final_velocity, 2.0);
+        assert_relative_eq!(results[0].final_position, 2.0);
+        assert_relative_eq!(results[1].final_velocity, 6.0);
+        assert_relative_eq!(results[1].final_position, 9.0);
+
+        let mut output = Vec::new();
+        write_results(&mut output, &results).unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+
+        assert_eq!(output.lines().count(), 3);
+    }
+
+    #[test]
+    fn initial_state() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a.csv");
+        std::fs::write(&path, "1.0\n1.0\n1.0\n").unwrap();
+
+        let app = App::parse_from([
+            "train_sim",
+            "--algo",
+            "trapezoidal",
+            "--initial-velocity",
+            "5",
+            "--initial-position",
+            "1",
+            "--delay",
+            "1",
+            "batch",
+            dir.path().to_str().unwrap(),
+        ]);
+        let SimKind::Batch(batch) = app.sim else {
+            unreachable!();
+        };
+
+        let results = run(batch.csv.batch_files().unwrap(), &app.opts, false).unwrap();
+        let expected = sim::simulate_profile(
+            3,
+            &app.opts.single_iteration(),
+            false,
+            &crate::summation::InterpolateLookup::from(vec![1.0; 3]),
+            None,
+            |_, _| {},
+        )
+        .unwrap();
+
+        assert_eq!(results[0].final_velocity, expected.final_velocity);
+        assert_eq!(results[0].final_position, expected.final_position);
+        // the delay leaves one second of acceleration after the start
+        assert_relative_eq!(results[0].final_velocity, 6.0, epsilon = 1e-2);
+    }
+
+    #[test]
+    fn mask_column() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.csv"), "a,m\n1.0,1\n1.0,0\n1.0,1\n").unwrap();
+
+        let app = App::parse_from([
+            "train_sim",
+            "--algo",
+            "left-riemann",
+            "batch",
+            "--column",
+            "a",
+            "--mask-column",
+            "m",
+            dir.path().to_str().unwrap(),
+        ]);
+        let SimKind::Batch(batch) = app.sim else {
+            unreachable!();
+        };
+
+        let results = run(batch.csv.batch_files().unwrap(), &app.opts, false).unwrap();
+
+        // the second of the profile that is masked out does not accelerate
+        assert_relative_eq!(results[0].final_velocity, 1.0);
+    }
+
+    #[test]
+    fn merge_two() {
+        let dir = tempfile::tempdir().unwrap();
+        let first = dir.path().join("first.json");
+        let second = dir.path().join("second.json");
+
+        let result = |path: &str, final_velocity: f64| BatchResult {
+            path: PathBuf::from(path),
+            length: 3,
+            final_velocity,
+            final_position: 1.0,
+        };
+
+        write_results_json(
+            std::fs::File::create(&first).unwrap(),
+            &[result("a.csv", 4.0)],
+        )
+        .unwrap();
+        crate::output::write_json(
+            std::fs::File::create(&second).unwrap(),
+            &result("b.csv", -2.0),
+        )
+        .unwrap();
+
+        let mut results = merge_results(&[first, second]).unwrap();
+        sort_results(&mut results, Metric::FinalVelocity);
+
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].path, PathBuf::from("b.csv"));
+        assert_eq!(results[1].path, PathBuf::from("a.csv"));
+
+        let mut output = Vec::new();
+        write_results(&mut output, &results).unwrap();
+
+        let mut reader = csv::Reader::from_reader(output.as_slice());
+        let rows = reader
+            .records()
+            .map(|record| record.unwrap())
+            .collect::<Vec<csv::StringRecord>>();
+
+        assert_eq!(rows.len(), 2);
+        assert_eq!(&rows[0][0], "b.csv");
+        assert_eq!(&rows[0][2], "-2");
+        assert_eq!(&rows[1][0], "a.csv");
+    }
+}
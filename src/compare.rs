@@ -0,0 +1,894 @@
+//! comparing the results of a simulation when changing how it is calculated
+
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+use anyhow::Context;
+use clap::ValueEnum;
+
+use crate::args::{AppAlgo, SimOpts};
+use crate::monte_carlo::Rng;
+use crate::output::Trajectory;
+use crate::sim;
+use crate::summation::{
+    self, Callable, CubicSplineLookup, DiffStats, InterpolateLookup, Interpolation, MaskedCallable,
+    ShiftedCallable, Single, StepLookup, SummationError,
+};
+
+use crate::time::Timing;
+
+/// the final results of a simulation using a single interpolation method
+#[derive(Debug, Clone)]
+pub struct InterpResult {
+    /// the interpolation method used for the acceleration profile
+    pub method: Interpolation,
+    /// the final calculated velocity
+    pub final_velocity: f64,
+    /// the final calculated position
+    pub final_position: f64,
+}
+
+/// runs the simulation once for each of the available interpolation methods
+/// of the given acceleration lookup
+///
+/// the results will be in the same order as [`Interpolation::ALL`]
+pub fn interp(
+    accel: &InterpolateLookup,
+    opts: &SimOpts,
+    mask: Option<&[bool]>,
+) -> anyhow::Result<Vec<InterpResult>> {
+    Interpolation::ALL
+        .into_iter()
+        .map(|method| {
+            let (final_velocity, final_position) = match method {
+                Interpolation::Linear => simulate(accel.len(), opts, accel, mask)?,
+                Interpolation::Step => {
+                    let step = StepLookup::new(accel);
+
+                    simulate(accel.len(), opts, &step, mask)?
+                }
+                Interpolation::Cubic => {
+                    let cubic = CubicSplineLookup::new(accel);
+
+                    simulate(accel.len(), opts, &cubic, mask)?
+                }
+            };
+
+            Ok(InterpResult {
+                method,
+                final_velocity,
+                final_position,
+            })
+        })
+        .collect()
+}
+
+/// calculates the final velocity and position of the given acceleration
+/// callable with a single iteration of [`sim::simulate_profile`]
+fn simulate<T>(
+    length: usize,
+    opts: &SimOpts,
+    accel: &T,
+    mask: Option<&[bool]>,
+) -> anyhow::Result<(f64, f64)>
+where
+    T: Callable<f64> + Sync + ?Sized,
+{
+    let result = sim::simulate_profile(
+        length,
+        &opts.single_iteration(),
+        false,
+        accel,
+        mask,
+        |_, _| {},
+    )?;
+
+    Ok((result.final_velocity, result.final_position))
+}
+
+/// writes the given interpolation results as a table to the provided writer
+/// with one row per method
+pub fn write_interp<W>(mut writer: W, results: &[InterpResult]) -> anyhow::Result<()>
+where
+    W: Write,
+{
+    writeln!(
+        writer,
+        "{:<8} {:>24} {:>24}",
+        "method", "final velocity", "final position"
+    )
+    .context("failed to write comparison header")?;
+
+    for result in results {
+        writeln!(
+            writer,
+            "{:<8} {:>+24} {:>+24}",
+            result.method.name(),
+            result.final_velocity,
+            result.final_position
+        )
+        .context("failed to write comparison row")?;
+    }
+
+    writer
+        .flush()
+        .context("failed to flush comparison output")?;
+
+    Ok(())
+}
+
+/// the final results and timing of a simulation using a single algorithm
+#[derive(Debug, Clone)]
+pub struct AlgoResult {
+    /// the summation algorithm used
+    pub algo: AppAlgo,
+    /// the final calculated velocity
+    pub final_velocity: f64,
+    /// the final calculated position
+    pub final_position: f64,
+    /// the average time of each iteration
+    pub average: Duration,
+}
+
+/// runs the full simulation once for each of the available summation
+/// algorithms with the rest of the given options unchanged
+pub fn all_algos<A>(
+    length: usize,
+    opts: &SimOpts,
+    parallel: bool,
+    accel: &A,
+    mask: Option<&[bool]>,
+) -> anyhow::Result<Vec<AlgoResult>>
+where
+    A: Callable<f64> + Sync + ?Sized,
+{
+    let mut rtn = Vec::new();
+
+    for algo in AppAlgo::value_variants() {
+        let mut algo_opts = opts.clone();
+        algo_opts.algo = algo.clone();
+
+        let result = sim::simulate_profile(length, &algo_opts, parallel, accel, mask, |_, _| {})?;
+
+        rtn.push(AlgoResult {
+            algo: algo.clone(),
+            final_velocity: result.final_velocity,
+            final_position: result.final_position,
+            average: result.timing.average(),
+        });
+    }
+
+    Ok(rtn)
+}
+
+/// writes the results of each algorithm as an aligned table to the given
+/// writer
+pub fn write_algos<W>(mut writer: W, results: &[AlgoResult]) -> anyhow::Result<()>
+where
+    W: Write,
+{
+    writeln!(
+        writer,
+        "{:<16} {:>24} {:>24} {:>14}",
+        "algo", "final velocity", "final position", "avg seconds"
+    )
+    .context("failed to write comparison header")?;
+
+    for result in results {
+        let name = result
+            .algo
+            .to_possible_value()
+            .map(|value| value.get_name().to_owned())
+            .unwrap_or_default();
+
+        writeln!(
+            writer,
+            "{name:<16} {:>+24.15e} {:>+24.15e} {:>14.9}",
+            result.final_velocity,
+            result.final_position,
+            result.average.as_secs_f64()
+        )
+        .context("failed to write comparison row")?;
+    }
+
+    writer
+        .flush()
+        .context("failed to flush comparison output")?;
+
+    Ok(())
+}
+
+/// the final results of a simulation with single and double precision values
+#[derive(Debug, Clone)]
+pub struct PrecisionResult {
+    /// the final velocity calculated with single precision
+    pub single_velocity: f32,
+    /// the final position calculated with single precision
+    pub single_position: f32,
+    /// the final velocity calculated with double precision
+    pub double_velocity: f64,
+    /// the final position calculated with double precision
+    pub double_position: f64,
+}
+
+impl PrecisionResult {
+    /// the absolute difference between the single and double precision
+    /// final velocities
+    pub fn velocity_diff(&self) -> f64 {
+        (self.single_velocity as f64 - self.double_velocity).abs()
+    }
+
+    /// the absolute difference between the single and double precision
+    /// final positions
+    pub fn position_diff(&self) -> f64 {
+        (self.single_position as f64 - self.double_position).abs()
+    }
+}
+
+/// runs the simulation once with single precision values and once with double
+/// precision values using the selected algorithm
+///
+/// compensated summation is not used for either run. fails if a mask or an
+/// option that only the full simulation applies was given, see
+/// [`SimOpts::precision_option`]
+pub fn precision(
+    accel: &InterpolateLookup,
+    opts: &SimOpts,
+    mask: Option<&[bool]>,
+) -> anyhow::Result<PrecisionResult> {
+    if mask.is_some() {
+        anyhow::bail!("a mask cannot be used with --compare-precision");
+    }
+
+    if let Some(option) = opts.precision_option() {
+        anyhow::bail!("{option} cannot be used with --compare-precision");
+    }
+
+    let length = accel.len();
+    let single = accel
+        .iter()
+        .map(|value| Single(value as f32))
+        .collect::<Vec<Single>>();
+    let single = InterpolateLookup::from(single);
+
+    let (single_velocity, single_position) = simulate_sample(length, opts, &single)?;
+    let (double_velocity, double_position) = simulate_sample(length, opts, accel)?;
+
+    Ok(PrecisionResult {
+        single_velocity: single_velocity.0,
+        single_position: single_position.0,
+        double_velocity,
+        double_position,
+    })
+}
+
+/// calculates the final velocity and position of the given acceleration
+/// lookup using the sample type of the lookup
+fn simulate_sample<V>(
+    length: usize,
+    opts: &SimOpts,
+    accel: &InterpolateLookup<V>,
+) -> Result<(V, V), SummationError>
+where
+    V: summation::Sample,
+{
+    let config = opts.summation_config();
+
+    let vel_lookup = summation::cumulative_panels(
+        length,
+        opts.velocity_step(),
+        &config.apply(accel),
+        opts.algo.sample_summation(),
+    )?;
+    let final_velocity = vel_lookup.last().unwrap_or_default();
+    let final_position = summation::sum_panels(
+        length,
+        opts.position_step(),
+        &config.apply(&vel_lookup),
+        opts.algo.sample_summation(),
+    )?;
+
+    Ok((final_velocity, final_position))
+}
+
+/// writes the given precision results as a table to the provided writer
+pub fn write_precision<W>(mut writer: W, result: &PrecisionResult) -> anyhow::Result<()>
+where
+    W: Write,
+{
+    let rows = [
+        (
+            "f32",
+            result.single_velocity as f64,
+            result.single_position as f64,
+        ),
+        ("f64", result.double_velocity, result.double_position),
+        ("diff", result.velocity_diff(), result.position_diff()),
+    ];
+
+    writeln!(
+        writer,
+        "{:<8} {:>24} {:>24}",
+        "type", "final velocity", "final position"
+    )
+    .context("failed to write comparison header")?;
+
+    for (name, velocity, position) in rows {
+        writeln!(writer, "{name:<8} {velocity:>+24} {position:>+24}")
+            .context("failed to write comparison row")?;
+    }
+
+    writer
+        .flush()
+        .context("failed to flush comparison output")?;
+
+    Ok(())
+}
+
+/// the differences between two trajectories
+#[derive(Debug, Clone)]
+pub struct VerifyReport {
+    /// the differences between the velocities
+    pub velocity: DiffStats,
+    /// the differences between the positions
+    pub position: DiffStats,
+    /// the largest allowed absolute difference
+    pub tolerance: f64,
+}
+
+impl VerifyReport {
+    /// true if all of the differences are within the tolerance
+    pub fn passed(&self) -> bool {
+        self.velocity.max <= self.tolerance && self.position.max <= self.tolerance
+    }
+}
+
+impl Display for VerifyReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        for (name, stats) in [("velocity", &self.velocity), ("position", &self.position)] {
+            writeln!(
+                f,
+                "{name} max diff: {} at row {} rms diff: {}",
+                stats.max, stats.max_index, stats.rms
+            )?;
+        }
+
+        write!(
+            f,
+            "tolerance: {} result: {}",
+            self.tolerance,
+            if self.passed() { "pass" } else { "fail" }
+        )
+    }
+}
+
+/// compares two trajectories against the given tolerance
+///
+/// fails if the trajectories do not have the same amount of rows
+pub fn verify(a: &Trajectory, b: &Trajectory, tolerance: f64) -> anyhow::Result<VerifyReport> {
+    if a.velocity.len() != b.velocity.len() {
+        anyhow::bail!(
+            "trajectories have a different amount of rows. {} != {}",
+            a.velocity.len(),
+            b.velocity.len()
+        );
+    }
+
+    Ok(VerifyReport {
+        velocity: a.velocity.diff_stats(&b.velocity),
+        position: a.position.diff_stats(&b.position),
+        tolerance,
+    })
+}
+
+/// the estimated integration error of a region of the profile
+#[derive(Debug, Clone)]
+pub struct RegionError {
+    /// the first second of the region
+    pub start: usize,
+    /// the last second of the region
+    pub end: usize,
+    /// the sum of the absolute differences between integrating each second of
+    /// the region with the velocity step and with double the velocity step
+    pub error: f64,
+}
+
+/// estimates the integration error of the velocity pass in each of the given
+/// amount of regions
+///
+/// the regions are split on whole seconds so there will be fewer regions if
+/// the profile has less seconds than requested. the mask and delay are
+/// applied to the acceleration the same as [`sim::simulate_profile`]
+pub fn region_errors<T>(
+    length: usize,
+    opts: &SimOpts,
+    accel: &T,
+    mask: Option<&[bool]>,
+    regions: u32,
+) -> Result<Vec<RegionError>, SummationError>
+where
+    T: Callable<f64> + ?Sized,
+{
+    if let Some(mask) = mask {
+        delayed_region_errors(length, opts, &MaskedCallable::new(accel, mask), regions)
+    } else {
+        delayed_region_errors(length, opts, accel, regions)
+    }
+}
+
+/// runs [`panel_errors`] with the acceleration delayed by the delay of the
+/// options if one was requested
+fn delayed_region_errors<T>(
+    length: usize,
+    opts: &SimOpts,
+    accel: &T,
+    regions: u32,
+) -> Result<Vec<RegionError>, SummationError>
+where
+    T: Callable<f64> + ?Sized,
+{
+    if let Some(delay) = opts.delay {
+        let shifted = ShiftedCallable::new(accel, delay, opts.delay_fill.shift_fill());
+
+        panel_errors(length, opts, &shifted, regions)
+    } else {
+        panel_errors(length, opts, accel, regions)
+    }
+}
+
+/// estimates the integration error of each region, see [`region_errors`]
+fn panel_errors<T>(
+    length: usize,
+    opts: &SimOpts,
+    accel: &T,
+    regions: u32,
+) -> Result<Vec<RegionError>, SummationError>
+where
+    T: Callable<f64> + ?Sized,
+{
+    let seconds = length.saturating_sub(1);
+    let regions = (regions as usize).min(seconds);
+    let accel = &opts.summation_config().apply(accel);
+    let sum_cb = opts.summation();
+    let step = opts.velocity_step();
+    let mut rtn = Vec::with_capacity(regions);
+
+    for region in 0..regions {
+        let start = seconds * region / regions;
+        let end = seconds * (region + 1) / regions;
+
+        // the differences of each unit interval are summed separately so the
+        // errors of opposite sides of a feature do not cancel out
+        let error = (start + 1..=end)
+            .map(|sec| {
+                let lower = (sec - 1) as f64;
+                let upper = sec as f64;
+                let coarse = sum_cb(lower, upper, step * 2, accel)?;
+                let fine = sum_cb(lower, upper, step, accel)?;
+
+                Ok((coarse - fine).abs())
+            })
+            .sum::<Result<f64, SummationError>>()?;
+
+        rtn.push(RegionError { start, end, error });
+    }
+
+    Ok(rtn)
+}
+
+/// writes the given region errors as a table to the provided writer with one
+/// row per region
+pub fn write_region_errors<W>(mut writer: W, regions: &[RegionError]) -> anyhow::Result<()>
+where
+    W: Write,
+{
+    writeln!(
+        writer,
+        "{:>12} {:>12} {:>24}",
+        "start", "end", "estimated error"
+    )
+    .context("failed to write region error header")?;
+
+    for region in regions {
+        writeln!(
+            writer,
+            "{:>12} {:>12} {:>24}",
+            region.start, region.end, region.error
+        )
+        .context("failed to write region error row")?;
+    }
+
+    writer
+        .flush()
+        .context("failed to flush region error output")?;
+
+    Ok(())
+}
+
+/// the relative amount that the profile is shifted by for a stability check
+pub const STABILITY_EPSILON: f64 = 1e-6;
+
+/// the sensitivity of the final results to a small change in the profile
+#[derive(Debug, Clone)]
+pub struct StabilityResult {
+    /// the relative change in the final velocity divided by the relative
+    /// change in the profile
+    pub velocity: f64,
+    /// the relative change in the final position divided by the relative
+    /// change in the profile
+    pub position: f64,
+}
+
+/// estimates how sensitive the final results are to changes in the profile
+///
+/// every sample is shifted by `epsilon` times the mean magnitude of the
+/// profile and the simulation is run again. a value near 1 means the results
+/// change about as much as the input, larger values mean that small errors in
+/// the profile are amplified
+pub fn stability(
+    accel: &InterpolateLookup,
+    opts: &SimOpts,
+    mask: Option<&[bool]>,
+    epsilon: f64,
+) -> anyhow::Result<StabilityResult> {
+    let length = accel.len();
+    let values = accel.to_vec();
+    let magnitude = values.iter().map(|value| value.abs()).sum::<f64>() / length.max(1) as f64;
+    let shift = epsilon * magnitude;
+
+    let perturbed = InterpolateLookup::from(
+        values
+            .into_iter()
+            .map(|value| value + shift)
+            .collect::<Vec<f64>>(),
+    );
+
+    let (velocity, position) = simulate(length, opts, accel, mask)?;
+    let (perturbed_velocity, perturbed_position) = simulate(length, opts, &perturbed, mask)?;
+
+    let sensitivity =
+        |original: f64, changed: f64| ((changed - original) / original).abs() / epsilon;
+
+    Ok(StabilityResult {
+        velocity: sensitivity(velocity, perturbed_velocity),
+        position: sensitivity(position, perturbed_position),
+    })
+}
+
+/// the amount of samples in the small profile of a memory bound report. 8 KiB
+/// of samples that should stay in the cache
+pub const MEMORY_SMALL_LENGTH: usize = 1 << 10;
+
+/// the amount of samples in the large profile of a memory bound report. 32 MiB
+/// of samples that should not fit in the cache
+pub const MEMORY_LARGE_LENGTH: usize = 1 << 22;
+
+/// the time per evaluation of the same algorithm on a small and a large
+/// profile
+#[derive(Debug, Clone)]
+pub struct MemoryBoundResult {
+    /// the amount of samples in the small profile
+    pub small_length: usize,
+    /// the nanoseconds per evaluation of the small profile
+    pub small_nanos: f64,
+    /// the amount of samples in the large profile
+    pub large_length: usize,
+    /// the nanoseconds per evaluation of the large profile
+    pub large_nanos: f64,
+}
+
+impl MemoryBoundResult {
+    /// how many times slower each evaluation of the large profile is
+    pub fn slowdown(&self) -> f64 {
+        self.large_nanos / self.small_nanos
+    }
+}
+
+impl Display for MemoryBoundResult {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        writeln!(
+            f,
+            "small profile: {} samples {:.3} ns/eval",
+            self.small_length, self.small_nanos
+        )?;
+        writeln!(
+            f,
+            "large profile: {} samples {:.3} ns/eval",
+            self.large_length, self.large_nanos
+        )?;
+        write!(f, "memory slowdown: {:.2}x", self.slowdown())
+    }
+}
+
+/// times the simulation of a small and a large synthetic profile
+///
+/// the small profile is run repeatedly so both do about the same amount of
+/// evaluations. any difference in the time per evaluation is then attributed
+/// to the large profile not fitting in the cache
+pub fn memory_bound(
+    opts: &SimOpts,
+    small_length: usize,
+    large_length: usize,
+) -> anyhow::Result<MemoryBoundResult> {
+    assert!(small_length >= 2 && large_length >= small_length);
+
+    let repeats = (large_length - 1).div_ceil(small_length - 1);
+
+    Ok(MemoryBoundResult {
+        small_length,
+        small_nanos: nanos_per_eval(opts, small_length, repeats)?,
+        large_length,
+        large_nanos: nanos_per_eval(opts, large_length, 1)?,
+    })
+}
+
+/// runs the simulation of a random profile with the given length the given
+/// amount of times and returns the average nanoseconds per evaluation
+fn nanos_per_eval(opts: &SimOpts, length: usize, repeats: usize) -> anyhow::Result<f64> {
+    let mut rng = Rng::new(opts.seed);
+    let accel =
+        InterpolateLookup::from((0..length).map(|_| rng.gaussian(1.0)).collect::<Vec<f64>>());
+    let mut timing = Timing::default();
+
+    for _ in 0..repeats {
+        let start = Instant::now();
+
+        std::hint::black_box(simulate(length, opts, &accel, None)?);
+
+        timing.update(start.elapsed());
+    }
+
+    let evals = (length - 1) * (opts.velocity_step() + opts.position_step()) as usize * repeats;
+
+    Ok(timing.total().as_secs_f64() * 1e9 / evals as f64)
+}
+
+#[cfg(test)]
+mod test {
+    use approx::assert_relative_eq;
+    use clap::Parser;
+
+    use super::*;
+    use crate::args::App;
+
+    #[test]
+    fn algo_rows() {
+        let opts = App::parse_from(["train_sim", "--iterations", "2", "csv", "a.csv"]).opts;
+        let accel = InterpolateLookup::from(vec![1.0; 5]);
+
+        let results = all_algos(accel.len(), &opts, false, &accel, None).unwrap();
+
+        assert_eq!(results.len(), AppAlgo::value_variants().len());
+
+        // a constant acceleration is integrated exactly by every algorithm
+        for result in &results {
+            assert_relative_eq!(result.final_velocity, 4.0, epsilon = 1e-9);
+        }
+
+        let mut output = Vec::new();
+        write_algos(&mut output, &results).unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        let lines = output.lines().collect::<Vec<&str>>();
+
+        assert_eq!(lines.len(), results.len() + 1);
+        assert!(lines[1].starts_with("left-riemann "));
+        assert!(lines.iter().all(|line| line.len() == lines[0].len()));
+    }
+
+    #[test]
+    fn interp_rows() {
+        let opts = App::parse_from(["train_sim", "--step", "10", "csv", "a.csv"]).opts;
+        let accel = InterpolateLookup::from(vec![0.0, 2.0, 2.0]);
+
+        let results = interp(&accel, &opts, None).unwrap();
+
+        assert_eq!(results.len(), Interpolation::ALL.len());
+        // the default left riemann undershoots the linear ramp of the first
+        // second while the step method only sees the value at its start
+        assert_relative_eq!(results[0].final_velocity, 2.9, epsilon = 1e-9);
+        assert_relative_eq!(results[1].final_velocity, 2.0, epsilon = 1e-9);
+        // the natural spline overshoots past 2 between the two equal values
+        assert!(results[2].final_velocity > results[0].final_velocity);
+
+        let mut output = Vec::new();
+        write_interp(&mut output, &results).unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        let mut lines = output.lines().skip(1);
+
+        for method in Interpolation::ALL {
+            let row = lines.next().unwrap();
+
+            assert!(row.starts_with(method.name()));
+        }
+
+        assert!(lines.next().is_none());
+    }
+
+    #[test]
+    fn interp_matches_sim() {
+        let opts = App::parse_from([
+            "train_sim",
+            "--delay",
+            "0.5",
+            "--max-velocity",
+            "2",
+            "csv",
+            "a.csv",
+        ])
+        .opts;
+        let accel = InterpolateLookup::from(vec![0.0, 2.0, 2.0, 1.0]);
+        let mask = [true, false, true, true];
+
+        let results = interp(&accel, &opts, Some(&mask)).unwrap();
+        let expected =
+            sim::simulate_profile(accel.len(), &opts, false, &accel, Some(&mask), |_, _| {})
+                .unwrap();
+
+        assert_eq!(results[0].final_velocity, expected.final_velocity);
+        assert_eq!(results[0].final_position, expected.final_position);
+        assert!(results.iter().all(|result| result.final_velocity <= 2.0));
+    }
+
+    #[test]
+    fn precision_diverges() {
+        let opts = App::parse_from(["train_sim", "--step", "10", "csv", "a.csv"]).opts;
+
+        let short = InterpolateLookup::from(vec![0.1; 10]);
+        let long = InterpolateLookup::from(vec![0.1; 10_000]);
+
+        let short = precision(&short, &opts, None).unwrap();
+        let long = precision(&long, &opts, None).unwrap();
+
+        assert!(long.velocity_diff() > short.velocity_diff());
+        assert!(long.position_diff() > short.position_diff());
+        assert_relative_eq!(long.double_velocity, 999.9, epsilon = 1e-6);
+
+        let mut output = Vec::new();
+        write_precision(&mut output, &long).unwrap();
+
+        assert_eq!(String::from_utf8(output).unwrap().lines().count(), 4);
+
+        let accel = InterpolateLookup::from(vec![0.1; 10]);
+        let delayed = App::parse_from(["train_sim", "--delay", "1", "csv", "a.csv"]).opts;
+        let error = precision(&accel, &delayed, None).unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "--delay cannot be used with --compare-precision"
+        );
+        assert!(precision(&accel, &opts, Some(&[true; 10])).is_err());
+    }
+
+    #[test]
+    fn verify_tolerance() {
+        let accel = InterpolateLookup::from(vec![1.0, 2.0, 0.5, -1.0, 0.0]);
+        let vel =
+            summation::cumulative_panels(accel.len(), 10, &accel, summation::trapezoidal).unwrap();
+        let pos =
+            summation::cumulative_panels(accel.len(), 10, &vel, summation::trapezoidal).unwrap();
+
+        let mut original = Vec::new();
+        crate::output::write_trajectory(&mut original, &accel, &vel, &pos).unwrap();
+
+        let mut perturbed_vel = vel.to_vec();
+        perturbed_vel[2] += 1e-3;
+        let perturbed_vel = InterpolateLookup::from(perturbed_vel);
+
+        let mut perturbed = Vec::new();
+        crate::output::write_trajectory(&mut perturbed, &accel, &perturbed_vel, &pos).unwrap();
+
+        let original = crate::output::read_trajectory(original.as_slice()).unwrap();
+        let perturbed = crate::output::read_trajectory(perturbed.as_slice()).unwrap();
+
+        let same = verify(&original, &original, 0.0).unwrap();
+
+        assert!(same.passed());
+        assert_eq!(same.velocity.max, 0.0);
+
+        let tight = verify(&original, &perturbed, 1e-6).unwrap();
+
+        assert!(!tight.passed());
+        assert_eq!(tight.velocity.max_index, 2);
+        assert_relative_eq!(tight.velocity.max, 1e-3, epsilon = 1e-9);
+        assert!(tight.to_string().ends_with("fail"));
+
+        assert!(verify(&original, &perturbed, 1e-2).unwrap().passed());
+    }
+
+    #[test]
+    fn memory_bound_timings() {
+        let opts = App::parse_from(["train_sim", "--step", "2", "csv", "a.csv"]).opts;
+        let result = memory_bound(&opts, 16, 4096).unwrap();
+
+        assert_eq!(result.small_length, 16);
+        assert_eq!(result.large_length, 4096);
+        assert!(result.small_nanos > 0.0);
+        assert!(result.large_nanos > 0.0);
+        assert!(result.slowdown().is_finite());
+        assert_eq!(result.to_string().matches("ns/eval").count(), 2);
+    }
+
+    #[test]
+    fn stability_linear() {
+        let accel = InterpolateLookup::from((0..11).map(|sec| sec as f64).collect::<Vec<f64>>());
+        let opts = App::parse_from(["train_sim", "-a", "trapezoidal", "csv", "a.csv"]).opts;
+
+        let result = stability(&accel, &opts, None, STABILITY_EPSILON).unwrap();
+
+        assert_relative_eq!(result.velocity, 1.0, epsilon = 1e-6);
+        assert!(result.position.is_finite());
+
+        // clamping the velocity hides the change in the profile
+        let capped = App::parse_from([
+            "train_sim",
+            "-a",
+            "trapezoidal",
+            "--max-velocity",
+            "10",
+            "csv",
+            "a.csv",
+        ])
+        .opts;
+        let mask = [true; 11];
+        let result = stability(&accel, &capped, Some(&mask), STABILITY_EPSILON).unwrap();
+
+        assert_eq!(result.velocity, 0.0);
+    }
+
+    #[test]
+    fn region_errors_spike() {
+        let mut values = vec![1.0; 21];
+        values[12] = 50.0;
+
+        let accel = InterpolateLookup::from(values);
+        let opts = App::parse_from(["train_sim", "--step", "4", "csv", "a.csv"]).opts;
+
+        let regions = region_errors(accel.len(), &opts, &accel, None, 4).unwrap();
+
+        assert_eq!(regions.len(), 4);
+        assert_eq!((regions[2].start, regions[2].end), (10, 15));
+
+        for (index, region) in regions.iter().enumerate() {
+            if index == 2 {
+                assert!(region.error > 1.0);
+            } else {
+                assert_relative_eq!(region.error, 0.0);
+            }
+        }
+
+        let mut output = Vec::new();
+        write_region_errors(&mut output, &regions).unwrap();
+
+        assert_eq!(String::from_utf8(output).unwrap().lines().count(), 5);
+
+        // the delay moves the spike into the last region
+        let delayed = App::parse_from([
+            "train_sim",
+            "--step",
+            "4",
+            "--delay",
+            "5",
+            "--delay-fill",
+            "clamp",
+            "csv",
+            "a.csv",
+        ])
+        .opts;
+        let regions = region_errors(accel.len(), &delayed, &accel, None, 4).unwrap();
+
+        assert_relative_eq!(regions[2].error, 0.0);
+        assert!(regions[3].error > 1.0);
+
+        // masking the seconds around the spike removes it
+        let mut mask = [true; 21];
+        mask[11] = false;
+        mask[12] = false;
+
+        let regions = region_errors(accel.len(), &opts, &accel, Some(&mask), 4).unwrap();
+
+        assert!(regions[2].error < 1.0);
+    }
+}
@@ -0,0 +1,207 @@
+//! filters that can be applied to the values of a profile before they are
+//! placed into a lookup table
+
+/// the options of a Savitzky-Golay filter
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SavGol {
+    /// the amount of values that each polynomial is fitted to. must be odd
+    pub window: usize,
+    /// the order of the fitted polynomial. must be less than the window
+    pub order: usize,
+}
+
+impl SavGol {
+    /// checks that the window and order can be used to filter the given
+    /// amount of values
+    pub fn check(&self, len: usize) -> anyhow::Result<()> {
+        if self.window.is_multiple_of(2) {
+            anyhow::bail!("savgol window must be odd");
+        }
+
+        if self.order >= self.window {
+            anyhow::bail!("savgol order must be less than the window");
+        }
+
+        if self.window > len {
+            anyhow::bail!("savgol window is larger than the amount of values");
+        }
+
+        Ok(())
+    }
+
+    /// calculates the convolution coefficients for evaluating the fitted
+    /// polynomial at the given position relative to the center of the window
+    ///
+    /// the coefficients are the row of the least squares solution
+    /// `(AᵀA)⁻¹Aᵀ` for the powers of the given position where each row of A is
+    /// the powers of a position in the window
+    pub fn coefficients(&self, at: f64) -> Vec<f64> {
+        let half = (self.window / 2) as f64;
+        let positions = (0..self.window)
+            .map(|index| index as f64 - half)
+            .collect::<Vec<f64>>();
+        let terms = self.order + 1;
+
+        // the normal matrix AᵀA where each entry is the sum of the positions
+        // raised to the combined power
+        let mut normal = vec![vec![0.0; terms]; terms];
+
+        for (row, normal_row) in normal.iter_mut().enumerate() {
+            for (col, entry) in normal_row.iter_mut().enumerate() {
+                *entry = positions.iter().map(|x| x.powi((row + col) as i32)).sum();
+            }
+        }
+
+        // solving AᵀA z = p for the powers of the position gives the
+        // coefficients as A z since AᵀA is symmetric
+        let powers = (0..terms).map(|power| at.powi(power as i32)).collect();
+        let z = solve(normal, powers);
+
+        positions
+            .iter()
+            .map(|x| {
+                z.iter()
+                    .enumerate()
+                    .map(|(power, value)| value * x.powi(power as i32))
+                    .sum()
+            })
+            .collect()
+    }
+
+    /// applies the filter to the given values
+    ///
+    /// values that are closer to the ends than half of the window use the
+    /// polynomial fitted to the first or last full window
+    pub fn apply(&self, values: &[f64]) -> anyhow::Result<Vec<f64>> {
+        self.check(values.len())?;
+
+        let half = self.window / 2;
+        let last_start = values.len() - self.window;
+        let center = self.coefficients(0.0);
+
+        let rtn = (0..values.len())
+            .map(|index| {
+                let start = index.saturating_sub(half).min(last_start);
+                let window = &values[start..start + self.window];
+
+                let owned;
+                let coefficients = if start + half == index {
+                    &center
+                } else {
+                    owned = self.coefficients(index as f64 - (start + half) as f64);
+                    &owned
+                };
+
+                window
+                    .iter()
+                    .zip(coefficients)
+                    .map(|(value, coefficient)| value * coefficient)
+                    .sum()
+            })
+            .collect();
+
+        Ok(rtn)
+    }
+}
+
+/// solves the given square system of linear equations using gaussian
+/// elimination with partial pivoting
+fn solve(mut matrix: Vec<Vec<f64>>, mut rhs: Vec<f64>) -> Vec<f64> {
+    let size = rhs.len();
+
+    for col in 0..size {
+        let pivot = (col..size)
+            .max_by(|a, b| matrix[*a][col].abs().total_cmp(&matrix[*b][col].abs()))
+            .unwrap_or(col);
+
+        matrix.swap(col, pivot);
+        rhs.swap(col, pivot);
+
+        let (above, below) = matrix.split_at_mut(col + 1);
+        let pivot_row = &above[col];
+
+        for (offset, row) in below.iter_mut().enumerate() {
+            let factor = row[col] / pivot_row[col];
+
+            for (entry, pivot) in row[col..].iter_mut().zip(&pivot_row[col..]) {
+                *entry -= factor * pivot;
+            }
+
+            rhs[col + 1 + offset] -= factor * rhs[col];
+        }
+    }
+
+    let mut rtn = vec![0.0; size];
+
+    for row in (0..size).rev() {
+        let known = ((row + 1)..size)
+            .map(|col| matrix[row][col] * rtn[col])
+            .sum::<f64>();
+
+        rtn[row] = (rhs[row] - known) / matrix[row][row];
+    }
+
+    rtn
+}
+
+#[cfg(test)]
+mod test {
+    use approx::assert_relative_eq;
+
+    use super::*;
+
+    #[test]
+    fn savgol_known_coefficients() {
+        let filter = SavGol {
+            window: 5,
+            order: 2,
+        };
+        let expected = [-3.0, 12.0, 17.0, 12.0, -3.0];
+
+        for (calc, expected) in filter.coefficients(0.0).into_iter().zip(expected) {
+            assert_relative_eq!(calc, expected / 35.0, epsilon = 1e-12);
+        }
+    }
+
+    #[test]
+    fn savgol_polynomial() {
+        let filter = SavGol {
+            window: 7,
+            order: 3,
+        };
+        let values = (0..20)
+            .map(|x| {
+                let x = x as f64;
+
+                0.5 * x * x * x - 2.0 * x * x + x - 4.0
+            })
+            .collect::<Vec<f64>>();
+
+        let filtered = filter.apply(&values).unwrap();
+
+        assert_eq!(filtered.len(), values.len());
+
+        for (calc, expected) in filtered.into_iter().zip(values) {
+            assert_relative_eq!(calc, expected, epsilon = 1e-8, max_relative = 1e-10);
+        }
+
+        assert!(SavGol {
+            window: 4,
+            order: 2
+        }
+        .check(10)
+        .is_err());
+        assert!(SavGol {
+            window: 5,
+            order: 5
+        }
+        .check(10)
+        .is_err());
+        assert!(SavGol {
+            window: 11,
+            order: 2
+        }
+        .check(10)
+        .is_err());
+    }
+}
@@ -0,0 +1,419 @@
+//! coupled integrators that advance velocity and position together
+//!
+//! unlike the summation functions, which calculate the velocity and position
+//! in separate passes, these step through time and allow the acceleration to
+//! depend on the current velocity and position of the train
+
+use crate::summation::{Callable, InterpolateLookup};
+
+/// the current state of the train during a coupled integration
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct State {
+    /// the current time in seconds
+    pub time: f64,
+    /// the current velocity
+    pub velocity: f64,
+    /// the current position
+    pub position: f64,
+}
+
+/// defines the acceleration of the train given its current state
+pub trait Dynamics {
+    /// calculates the acceleration at the given time, velocity, and position
+    fn acceleration(&self, time: f64, velocity: f64, position: f64) -> f64;
+}
+
+/// what a profile is indexed by
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProfileIndex {
+    /// each entry is one meter of distance travelled
+    Distance,
+    /// each entry is one second of time
+    Time,
+}
+
+/// an acceleration profile with an additional linear drag term
+///
+/// the resulting acceleration is `a(t) - drag * v` or `a(x) - drag * v` when
+/// the profile is indexed by distance
+pub struct Drag<'a, T: ?Sized> {
+    accel: &'a T,
+    drag: f64,
+    index: ProfileIndex,
+    last: f64,
+}
+
+impl<'a, T: ?Sized> Drag<'a, T> {
+    /// creates new dynamics from the given acceleration profile and drag
+    /// coefficient
+    pub fn new(accel: &'a T, drag: f64) -> Self {
+        Self {
+            accel,
+            drag,
+            index: ProfileIndex::Time,
+            last: f64::INFINITY,
+        }
+    }
+
+    /// creates new dynamics from the given acceleration profile indexed by
+    /// the current position of the train
+    ///
+    /// the position is clamped between zero and the given last position of
+    /// the profile before looking up the acceleration
+    pub fn by_distance(accel: &'a T, drag: f64, last: f64) -> Self {
+        Self {
+            accel,
+            drag,
+            index: ProfileIndex::Distance,
+            last,
+        }
+    }
+}
+
+impl<T> Dynamics for Drag<'_, T>
+where
+    T: Callable<f64> + ?Sized,
+{
+    fn acceleration(&self, time: f64, velocity: f64, position: f64) -> f64 {
+        let at = match self.index {
+            ProfileIndex::Time => time,
+            ProfileIndex::Distance => position.clamp(0.0, self.last),
+        };
+
+        self.accel.call(at) - self.drag * velocity
+    }
+}
+
+/// standard gravity in meters per second squared
+pub const GRAVITY: f64 = 9.80665;
+
+/// adds the gravity component of a track grade to other dynamics
+///
+/// the grade profile holds the angle of the track in radians with positive
+/// values going uphill. the resulting acceleration is
+/// `a - GRAVITY * sin(theta)`. values outside of the grade profile use the
+/// nearest entry
+pub struct Grade<'a, D: ?Sized> {
+    inner: &'a D,
+    grade: &'a InterpolateLookup,
+    index: ProfileIndex,
+}
+
+impl<'a, D: ?Sized> Grade<'a, D> {
+    /// creates new dynamics from the given dynamics and grade profile
+    pub fn new(inner: &'a D, grade: &'a InterpolateLookup, index: ProfileIndex) -> Self {
+        assert!(!grade.is_empty());
+
+        Self {
+            inner,
+            grade,
+            index,
+        }
+    }
+}
+
+impl<D> Dynamics for Grade<'_, D>
+where
+    D: Dynamics + ?Sized,
+{
+    fn acceleration(&self, time: f64, velocity: f64, position: f64) -> f64 {
+        let at = match self.index {
+            ProfileIndex::Distance => position,
+            ProfileIndex::Time => time,
+        };
+        let theta = self
+            .grade
+            .call(at.clamp(0.0, (self.grade.len() - 1) as f64));
+
+        self.inner.acceleration(time, velocity, position) - GRAVITY * theta.sin()
+    }
+}
+
+/// the available methods for stepping through a coupled integration
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Method {
+    /// first order explicit euler
+    Euler,
+    /// second order heun's method (predictor-corrector)
+    Rk2,
+    /// classical fourth order runge-kutta
+    Rk4,
+}
+
+/// advances the state by a single explicit euler step of size h
+pub fn euler_step<D>(dynamics: &D, state: State, h: f64) -> State
+where
+    D: Dynamics + ?Sized,
+{
+    let accel = dynamics.acceleration(state.time, state.velocity, state.position);
+
+    State {
+        time: state.time + h,
+        velocity: state.velocity + h * accel,
+        position: state.position + h * state.velocity,
+    }
+}
+
+/// advances the state by a single heun's method step of size h
+///
+/// an euler step is used to predict the end of the step and the average of the
+/// slopes at the start and predicted end is used to correct it
+pub fn heun_step<D>(dynamics: &D, state: State, h: f64) -> State
+where
+    D: Dynamics + ?Sized,
+{
+    let a0 = dynamics.acceleration(state.time, state.velocity, state.position);
+
+    let predict_v = state.velocity + h * a0;
+    let predict_x = state.position + h * state.velocity;
+    let a1 = dynamics.acceleration(state.time + h, predict_v, predict_x);
+
+    State {
+        time: state.time + h,
+        velocity: state.velocity + h * (a0 + a1) / 2.0,
+        position: state.position + h * (state.velocity + predict_v) / 2.0,
+    }
+}
+
+/// advances the state by a single classical runge-kutta step of size h
+///
+/// the acceleration is evaluated at the start, twice at the midpoint, and at
+/// the end of the step
+pub fn rk4_step<D>(dynamics: &D, state: State, h: f64) -> State
+where
+    D: Dynamics + ?Sized,
+{
+    let half = h / 2.0;
+    let mid = state.time + half;
+
+    let v1 = state.velocity;
+    let a1 = dynamics.acceleration(state.time, v1, state.position);
+
+    let v2 = state.velocity + half * a1;
+    let a2 = dynamics.acceleration(mid, v2, state.position + half * v1);
+
+    let v3 = state.velocity + half * a2;
+    let a3 = dynamics.acceleration(mid, v3, state.position + half * v2);
+
+    let v4 = state.velocity + h * a3;
+    let a4 = dynamics.acceleration(state.time + h, v4, state.position + h * v3);
+
+    State {
+        time: state.time + h,
+        velocity: state.velocity + h * (a1 + 2.0 * a2 + 2.0 * a3 + a4) / 6.0,
+        position: state.position + h * (v1 + 2.0 * v2 + 2.0 * v3 + v4) / 6.0,
+    }
+}
+
+/// integrates the given dynamics from the initial state for the specified
+/// duration in seconds taking the given amount of steps per second
+pub fn integrate<D>(dynamics: &D, method: Method, initial: State, duration: f64, step: u32) -> State
+where
+    D: Dynamics + ?Sized,
+{
+    assert_ne!(step, 0);
+
+    let total = (duration * step as f64).round() as u64;
+    let h = 1.0 / step as f64;
+    let mut state = initial;
+
+    for iter in 0..total {
+        state = match method {
+            Method::Euler => euler_step(dynamics, state, h),
+            Method::Rk2 => heun_step(dynamics, state, h),
+            Method::Rk4 => rk4_step(dynamics, state, h),
+        };
+
+        // avoid accumulating floating point error in the time
+        state.time = initial.time + (iter + 1) as f64 * h;
+    }
+
+    state
+}
+
+/// integrates the given dynamics from the initial state for the specified
+/// amount of seconds taking the given amount of steps per second and keeps
+/// the state at the start of each second
+///
+/// the velocity is clamped to the given minimum and maximum after every step
+/// so the bounds affect the rest of the integration. a clamped step advances
+/// the position by the average of the velocities at its start and end. the
+/// returned states include the initial state
+pub fn trajectory<D>(
+    dynamics: &D,
+    method: Method,
+    initial: State,
+    seconds: usize,
+    step: u32,
+    (min_velocity, max_velocity): (f64, f64),
+) -> Vec<State>
+where
+    D: Dynamics + ?Sized,
+{
+    assert_ne!(step, 0);
+
+    let h = 1.0 / step as f64;
+    let mut state = initial;
+    let mut rtn = Vec::with_capacity(seconds + 1);
+
+    rtn.push(state);
+
+    for second in 0..seconds {
+        for iter in 0..step {
+            let next = match method {
+                Method::Euler => euler_step(dynamics, state, h),
+                Method::Rk2 => heun_step(dynamics, state, h),
+                Method::Rk4 => rk4_step(dynamics, state, h),
+            };
+            let clamped = next.velocity.clamp(min_velocity, max_velocity);
+
+            state = if clamped != next.velocity {
+                State {
+                    time: next.time,
+                    velocity: clamped,
+                    position: state.position + h * (state.velocity + clamped) / 2.0,
+                }
+            } else {
+                next
+            };
+            state.time = initial.time + second as f64 + (iter + 1) as f64 * h;
+        }
+
+        rtn.push(state);
+    }
+
+    rtn
+}
+
+#[cfg(test)]
+mod test {
+    use approx::assert_relative_eq;
+
+    use super::*;
+
+    fn constant(_x: f64) -> f64 {
+        1.0
+    }
+
+    /// the analytic solution of `dv/dt = 1 - k * v` starting from rest
+    fn drag_solution(k: f64, t: f64) -> State {
+        State {
+            time: t,
+            velocity: (1.0 - (-k * t).exp()) / k,
+            position: t / k - (1.0 - (-k * t).exp()) / (k * k),
+        }
+    }
+
+    #[test]
+    fn rk2_beats_euler() {
+        let k = 0.5;
+        let dynamics = Drag::new(&constant, k);
+        let expected = drag_solution(k, 10.0);
+
+        let euler = integrate(&dynamics, Method::Euler, State::default(), 10.0, 4);
+        let rk2 = integrate(&dynamics, Method::Rk2, State::default(), 10.0, 4);
+
+        assert_eq!(euler.time, 10.0);
+        assert_eq!(rk2.time, 10.0);
+
+        let euler_err = (euler.velocity - expected.velocity).abs();
+        let rk2_err = (rk2.velocity - expected.velocity).abs();
+
+        assert!(rk2_err < euler_err, "rk2: {rk2_err} euler: {euler_err}");
+
+        let euler_err = (euler.position - expected.position).abs();
+        let rk2_err = (rk2.position - expected.position).abs();
+
+        assert!(rk2_err < euler_err, "rk2: {rk2_err} euler: {euler_err}");
+    }
+
+    #[test]
+    fn rk4_analytic() {
+        let k = 0.5;
+        let dynamics = Drag::new(&constant, k);
+        let expected = drag_solution(k, 10.0);
+
+        let rk4 = integrate(&dynamics, Method::Rk4, State::default(), 10.0, 4);
+
+        assert!((rk4.velocity - expected.velocity).abs() < 1e-6);
+        assert!((rk4.position - expected.position).abs() < 1e-6);
+    }
+
+    #[test]
+    fn bounded_trajectory() {
+        let dynamics = Drag::new(&constant, 0.0);
+        let unbounded = (f64::NEG_INFINITY, f64::INFINITY);
+
+        let states = trajectory(&dynamics, Method::Rk4, State::default(), 5, 4, unbounded);
+        let last = integrate(&dynamics, Method::Rk4, State::default(), 5.0, 4);
+
+        assert_eq!(states.len(), 6);
+        assert_eq!(states[0], State::default());
+        assert_eq!(states[2].time, 2.0);
+        assert_relative_eq!(states[5].velocity, last.velocity);
+        assert_relative_eq!(states[5].position, last.position);
+
+        // the train reaches the cap after the first second and then travels
+        // at the capped velocity
+        let states = trajectory(&dynamics, Method::Rk4, State::default(), 5, 4, (0.0, 1.0));
+
+        assert!(states.iter().all(|state| state.velocity <= 1.0));
+        assert_relative_eq!(states[5].velocity, 1.0);
+        assert_relative_eq!(states[5].position, 4.5, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn downhill_grade() {
+        let no_thrust = |_x: f64| 0.0;
+        let dynamics = Drag::new(&no_thrust, 0.0);
+
+        // a constant 2% downhill grade
+        let theta = (-0.02f64).atan();
+        let grade = InterpolateLookup::from(vec![theta; 5]);
+        let dynamics = Grade::new(&dynamics, &grade, ProfileIndex::Distance);
+
+        let expected = GRAVITY * theta.abs().sin();
+
+        assert_relative_eq!(dynamics.acceleration(0.0, 0.0, 0.0), expected);
+
+        // the train travels past the end of the grade profile which should
+        // continue to use the last value
+        let rk4 = integrate(&dynamics, Method::Rk4, State::default(), 10.0, 4);
+
+        assert_relative_eq!(rk4.velocity, expected * 10.0, epsilon = 1e-9);
+        assert_relative_eq!(rk4.position, expected * 50.0, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn distance_marker() {
+        // accelerates until 10 meters and then brakes from 11 meters onwards
+        let mut accel = vec![1.0; 11];
+        accel.extend([-1.0; 10]);
+        let accel = InterpolateLookup::from(accel);
+        let last = (accel.len() - 1) as f64;
+
+        let dynamics = Drag::by_distance(&accel, 0.0, last);
+        let mut state = State::default();
+
+        // step until the train starts to slow down
+        loop {
+            let next = rk4_step(&dynamics, state, 0.001);
+
+            if next.velocity < state.velocity {
+                break;
+            }
+
+            state = next;
+        }
+
+        // the acceleration crosses zero half way between the markers. the
+        // velocity comes from v² = 2 * ∫a dx
+        assert_relative_eq!(state.position, 10.5, epsilon = 1e-2);
+        assert_relative_eq!(state.velocity, 20.5f64.sqrt(), epsilon = 1e-2);
+
+        // the same profile indexed by time would still be accelerating
+        let by_time = Drag::new(&accel, 0.0);
+
+        assert_eq!(by_time.acceleration(state.time, 0.0, 0.0), 1.0);
+    }
+}
@@ -0,0 +1,19 @@
+//! simulates the velocity and position of a "train" from a given acceleration
+//! profile using a variety of summation algorithms
+
+// indicates that there are nested modules that can contain code in a different
+// namespace
+pub mod args;
+pub mod batch;
+pub mod compare;
+pub mod filter;
+pub mod integrator;
+pub mod monte_carlo;
+pub mod output;
+pub mod schedule;
+pub mod sim;
+#[cfg(feature = "rusqlite")]
+pub mod sqlite;
+pub mod stream;
+pub mod summation;
+pub mod time;
@@ -5,163 +5,887 @@
 use anyhow::Context;
 use clap::Parser;
 
-// indicates that there are nested modules that can contain code in a different
-// namespace
-mod args;
-mod summation;
-mod time;
+// the modules are declared in the library crate so that they can also be used
+// outside of this binary. we access them by the name of the package
+use train_sim::{args, batch, compare, integrator, monte_carlo, output, sim, summation, time};
 
-use args::{App, AppAlgo, SimKind, SimOpts};
+use args::{App, ResultFormat, ResultsFormat, SimKind, SimOpts, TimingFormat};
 
 // once the mod is known we can access it similar to imported modules or the
 // std namespace
-use summation::InterpolateLookup;
+use summation::{Callable, InterpolateLookup, ShiftedCallable, UncheckedLookup};
 
 fn main() -> anyhow::Result<()> {
     // pull in the command line arguments provided at runtime and parse into
     // the App struct
     let args = App::parse();
 
+    // the summations assert on zero iterations since they are called in the
+    // hot loop so check the steps once before anything is ran
+    args.opts.check_steps()?;
+    args.opts.check_adaptive_max_step()?;
+
+    if args.opts.explain {
+        println!(
+            "{}",
+            args.opts.algo.explain(args.opts.step, args.opts.levels)
+        );
+    }
+
+    if args.opts.dump_nodes {
+        println!("nodes over [0, 1] with step: {}", args.opts.step);
+
+        if let Some(nodes) = args.opts.algo.nodes(0.0, 1.0, args.opts.step)? {
+            for (x, weight) in nodes {
+                println!("x: {x} weight: {weight}");
+            }
+        } else {
+            println!("nodes depend on the profile for adaptive algorithms");
+        }
+    }
+
+    if args.opts.memory_bound_report {
+        let result = compare::memory_bound(
+            &args.opts,
+            compare::MEMORY_SMALL_LENGTH,
+            compare::MEMORY_LARGE_LENGTH,
+        )?;
+
+        println!("{result}");
+
+        return Ok(());
+    }
+
     match args.sim {
         SimKind::Csv(csv_args) => {
+            let mask = csv_args.get_mask()?;
+
+            if mask.is_some() && (args.opts.t_start.is_some() || args.opts.t_end.is_some()) {
+                anyhow::bail!("a mask cannot be used with a time window");
+            }
+
+            let pairs_source = csv_args.pairs_source();
             let cb = csv_args.get_callable()?;
-            let length = cb.len();
 
-            if args.threads == 1 {
-                run_sim(length, args.opts, cb);
+            run_profile(&args.opts, args.threads, cb, mask.as_deref(), pairs_source)?;
+        }
+        SimKind::Batch(batch_args) => {
+            let sims = batch_args.csv.batch_files()?;
+            let parallel = thread_pool(args.threads)?;
+
+            let results = batch::run(sims, &args.opts, parallel)?;
+
+            write_results(
+                batch_args.results.as_deref(),
+                &batch_args.results_format,
+                &results,
+            )?;
+        }
+        SimKind::MergeResults(merge_args) => {
+            let mut results = batch::merge_results(&merge_args.files)?;
+            batch::sort_results(&mut results, merge_args.sort_by.metric());
+
+            write_results(merge_args.output.as_deref(), &merge_args.format, &results)?;
+        }
+        SimKind::Json(json_args) => {
+            let cb = json_args.get_callable()?;
+
+            run_profile(&args.opts, args.threads, cb, None, "a time column")?;
+        }
+        SimKind::Fourier(fourier_args) => {
+            if let Some(option) = args.opts.lookup_option() {
+                anyhow::bail!("{option} cannot be used with a fourier series");
+            }
+
+            let cb = fourier_args.get_callable();
+            let parallel = thread_pool(args.threads)?;
+
+            if let Some(length) = fourier_args.length()? {
+                run_accel(length, &args.opts, parallel, &cb)?;
+            } else {
+                if let Some(option) = args.opts.uniform_option() {
+                    anyhow::bail!("{option} cannot be used with a partial second duration");
+                }
+
+                run_sim_times(&fourier_args.times()?, &args.opts, parallel, &cb)?;
+            }
+        }
+        SimKind::Verify(verify_args) => {
+            let load = |path: &std::path::Path| {
+                let file = std::fs::File::open(path)
+                    .with_context(|| format!("failed to open trajectory. {}", path.display()))?;
+
+                output::read_trajectory(std::io::BufReader::new(file))
+            };
+
+            let a = load(&verify_args.file_a)?;
+            let b = load(&verify_args.file_b)?;
+            let report = compare::verify(&a, &b, verify_args.tolerance)?;
+
+            println!("{report}");
+
+            if !report.passed() {
+                anyhow::bail!("trajectories differ by more than the tolerance");
+            }
+        }
+        SimKind::Generate(generate_args) => {
+            let profile = generate_args.profile()?;
+
+            if let Some(path) = &generate_args.output {
+                let file =
+                    std::fs::File::create(path).context("failed to create generated profile")?;
+
+                output::write_table(file, "acceleration", &profile)?;
             } else {
-                // construct the rayon thread pool with the specified number of
-                // threads and make it globaly available
-                rayon::ThreadPoolBuilder::new()
-                    .num_threads(args.threads)
-                    .build_global()
-                    .context("failed to create global thread pool")?;
-
-                run_sim_rayon(length, args.opts, cb);
+                output::write_table(std::io::stdout().lock(), "acceleration", &profile)?;
             }
         }
+        SimKind::Validate(csv_args) => {
+            let report = csv_args.validate()?;
+
+            println!("{report}");
+        }
     };
 
     Ok(())
 }
 
-/// runs the non multi-threaded train sim with the provided lookup table
-fn run_sim(length: usize, opts: SimOpts, accel_lookup: InterpolateLookup) {
-    println!(
-        "lenth: {length} step: {} iterations: {}",
-        opts.step, opts.iterations
-    );
+/// runs the requested mode with an acceleration profile loaded into a lookup
+/// table
+///
+/// the mask excludes samples from the simulation. the pairs source names what
+/// gave the lookup table explicit x values for the options that need a value
+/// every second
+fn run_profile(
+    opts: &SimOpts,
+    threads: usize,
+    mut cb: InterpolateLookup,
+    mask: Option<&[bool]>,
+    pairs_source: &str,
+) -> anyhow::Result<()> {
+    let config = opts.summation_config();
+
+    opts.check_gaps(&cb)?;
+
+    if cb.xs().is_some() {
+        if let Some(option) = opts.uniform_option() {
+            anyhow::bail!("{option} cannot be used with {pairs_source}");
+        }
+    }
 
-    // let the type system decide what this is supposed to be as I was having
-    // trouble with getting it to behave
-    let sum_cb = match opts.algo {
-        AppAlgo::LeftRiemann => summation::left_riemann,
-        AppAlgo::MidRiemann => summation::mid_riemann,
-        AppAlgo::RightRiemann => summation::right_riemann,
-        AppAlgo::Trapezoidal => summation::trapezoidal,
-        AppAlgo::Simpsons => summation::simpsons,
-    };
+    opts.apply_window(&mut cb);
+    opts.apply_endpoint(&mut cb);
 
-    let mut log_timer = time::LogTimer::default();
-    let mut timer = time::Timing::default();
+    let length = cb.len();
 
-    for iter in 0..(opts.iterations) {
-        // pre-allocate the lookup table before starting the timer
-        let mut vel_lookup = InterpolateLookup::from(Vec::with_capacity(length));
-        vel_lookup.push(0.0);
+    if let Some(path) = &opts.profile_stats_json {
+        let file =
+            std::fs::File::create(path).context("failed to create profile stats json file")?;
 
-        let start = std::time::Instant::now();
+        output::write_json(file, &output::ProfileStats::new(&cb))?;
+    }
 
-        let mut vel_final = 0.0f64;
+    if let Some(times) = cb.xs() {
+        if opts.time_average {
+            print_time_average(opts, &cb)?;
+        }
 
-        for sec in 1..length {
-            let result = sum_cb((sec - 1) as f64, sec as f64, opts.step, &accel_lookup);
+        let parallel = thread_pool(threads)?;
 
-            vel_final += result;
+        return run_sim_times(times, opts, parallel, &cb);
+    }
 
-            vel_lookup.push(vel_final);
+    if opts.accel_from_velocity {
+        let accel = cb.derivative();
+
+        println!("length: {}", accel.len());
+
+        if let Some((min, max)) = accel.min().zip(accel.max()) {
+            println!("min accel: {min:+}");
+            println!("max accel: {max:+}");
         }
 
-        let pos_final = (1..length)
-            .map(|sec| sum_cb((sec - 1) as f64, sec as f64, opts.step, &vel_lookup))
-            .sum::<f64>();
+        if let Some(mean) = accel.mean() {
+            println!("mean accel: {mean:+}");
+        }
 
-        timer.update(start.elapsed());
+        if let Some(path) = &opts.derivative_output {
+            let file =
+                std::fs::File::create(path).context("failed to create derivative output file")?;
 
-        if log_timer.update() {
-            println!("iteration: {iter} {timer}");
+            output::write_table(file, "acceleration", &accel)?;
         }
 
-        if iter == opts.iterations - 1 {
-            println!("final velocity: {vel_final:+}");
-            println!("final position: {pos_final:+}");
+        return Ok(());
+    }
+
+    if let Some(sigma) = opts.monte_carlo {
+        let result = monte_carlo::run(&cb, opts, mask, sigma, opts.seed)?;
+
+        println!("iterations: {}", result.velocities.len());
+
+        if let Some((mean, std)) = result.velocity_stats() {
+            println!("final velocity mean: {mean:+} std: {std}");
         }
+
+        if let Some((mean, std)) = result.position_stats() {
+            println!("final position mean: {mean:+} std: {std}");
+        }
+
+        return Ok(());
+    }
+
+    if opts.compare_precision {
+        let result = compare::precision(&cb, opts, mask)?;
+
+        compare::write_precision(std::io::stdout().lock(), &result)?;
+
+        return Ok(());
+    }
+
+    if opts.compare_interp {
+        let results = compare::interp(&cb, opts, mask)?;
+
+        compare::write_interp(std::io::stdout().lock(), &results)?;
+
+        return Ok(());
+    }
+
+    if opts.all_algos {
+        let parallel = thread_pool(threads)?;
+
+        let results = compare::all_algos(length, opts, parallel, &cb, mask)?;
+
+        compare::write_algos(std::io::stdout().lock(), &results)?;
+
+        return Ok(());
+    }
+
+    if opts.stability_check {
+        let result = compare::stability(&cb, opts, mask, compare::STABILITY_EPSILON)?;
+
+        println!("velocity sensitivity: {}", result.velocity);
+        println!("position sensitivity: {}", result.position);
+
+        return Ok(());
+    }
+
+    if let Some(regions) = opts.error_regions {
+        let results = compare::region_errors(length, opts, &cb, mask, regions)?;
+
+        compare::write_region_errors(std::io::stdout().lock(), &results)?;
+
+        return Ok(());
+    }
+
+    if opts.accel_l2 {
+        let l2 = summation::l2_norm(length, opts.step, &config.apply(&cb), opts.summation())?;
+
+        println!("accel l2: {l2}");
+    }
+
+    if opts.report_abs_integral {
+        let signed =
+            summation::sum_panels(length, opts.step, &config.apply(&cb), opts.summation())?;
+        let abs = summation::sum_panels(
+            length,
+            opts.step,
+            &config.apply(&summation::AbsCallable::new(&cb)),
+            opts.summation(),
+        )?;
+
+        println!("accel integral: {signed:+}");
+        println!("accel abs integral: {abs}");
+    }
+
+    if opts.integrate_abs_jerk {
+        let jerk = cb.derivative();
+        let abs_jerk = summation::sum_panels(
+            length,
+            opts.step,
+            &config.apply(&summation::AbsCallable::new(&jerk)),
+            opts.summation(),
+        )?;
+
+        println!("abs jerk integral: {abs_jerk}");
+    }
+
+    if opts.time_average {
+        print_time_average(opts, &cb)?;
     }
 
-    println!("{timer}");
+    if let Some(mass) = opts.mass.filter(|_| opts.report_work) {
+        let vel = summation::cumulative_panels(
+            length,
+            opts.velocity_step(),
+            &config.apply(&cb),
+            opts.summation(),
+        )?;
+        let power = summation::ProductCallable::new(&cb, &vel);
+        let work = mass
+            * summation::sum_panels(
+                length,
+                opts.position_step(),
+                &config.apply(&power),
+                opts.summation(),
+            )?;
+
+        println!("work: {work:+}");
+    }
+
+    let parallel = thread_pool(threads)?;
+
+    if let Some(mask) = mask {
+        let masked = summation::MaskedCallable::new(&cb, mask);
+
+        run_accel(length, opts, parallel, &masked)?;
+    } else if config.checked {
+        run_accel(length, opts, parallel, &cb)?;
+    } else {
+        run_accel(length, opts, parallel, &UncheckedLookup::new(&cb)?)?;
+    }
+
+    Ok(())
 }
 
-/// runs the multi-threaded train sim with the provided lookup table
-fn run_sim_rayon(length: usize, opts: SimOpts, accel_lookup: InterpolateLookup) {
-    // since this is the only spot that will use the rayon module we can just
-    // import it here.
-    use rayon::prelude::*;
+/// constructs the global rayon thread pool with the specified number of
+/// threads when more than one was requested
+///
+/// returns true if the simulation should run in parallel
+fn thread_pool(threads: usize) -> anyhow::Result<bool> {
+    let parallel = threads != 1;
+
+    if parallel {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build_global()
+            .context("failed to create global thread pool")?;
+    }
 
-    println!(
-        "lenth: {length} step: {} iterations: {}",
-        opts.step, opts.iterations
-    );
+    Ok(parallel)
+}
 
-    let sum_cb = match opts.algo {
-        AppAlgo::LeftRiemann => summation::left_riemann,
-        AppAlgo::MidRiemann => summation::mid_riemann,
-        AppAlgo::RightRiemann => summation::right_riemann,
-        AppAlgo::Trapezoidal => summation::trapezoidal,
-        AppAlgo::Simpsons => summation::simpsons,
+/// applies the requested wrappers to the acceleration callable before running
+/// the simulation
+fn run_accel<A>(length: usize, opts: &SimOpts, parallel: bool, accel: &A) -> anyhow::Result<()>
+where
+    A: Callable<f64> + Sync + ?Sized,
+{
+    if let Some(delay) = opts.delay {
+        let shifted = ShiftedCallable::new(accel, delay, opts.delay_fill.shift_fill());
+
+        run(length, opts, parallel, &shifted)
+    } else {
+        run(length, opts, parallel, accel)
+    }
+}
+
+/// runs either the non multi-threaded or multi-threaded train sim
+fn run<A>(length: usize, opts: &SimOpts, parallel: bool, accel: &A) -> anyhow::Result<()>
+where
+    A: Callable<f64> + Sync + ?Sized,
+{
+    if let Some(method) = opts.integrator.method() {
+        if opts.quadrature_file.is_some() {
+            anyhow::bail!("--quadrature-file cannot be used with a coupled integrator");
+        }
+
+        run_coupled(length, opts, method, accel)
+    } else if opts.grade_csv.is_some() {
+        anyhow::bail!("a grade csv requires a coupled integrator")
+    } else if opts.accel_index.profile_index() == integrator::ProfileIndex::Distance {
+        anyhow::bail!("a distance indexed profile requires a coupled integrator")
+    } else if let Some(rule) = opts.quadrature_rule()? {
+        run_quadrature(length, opts, &rule, accel)
+    } else {
+        run_sim(length, opts, parallel, accel)
+    }
+}
+
+/// runs the coupled train sim with the provided acceleration callable
+///
+/// the coupled integrators are sequential so this will not use multiple
+/// threads
+fn run_coupled<A>(
+    length: usize,
+    opts: &SimOpts,
+    method: integrator::Method,
+    accel: &A,
+) -> anyhow::Result<()>
+where
+    A: Callable<f64> + ?Sized,
+{
+    if !opts.quiet {
+        println!(
+            "lenth: {length} step: {} iterations: {}",
+            opts.step, opts.iterations
+        );
+    }
+
+    let grade = opts.grade()?;
+    let drag = match opts.accel_index.profile_index() {
+        integrator::ProfileIndex::Time => integrator::Drag::new(accel, opts.drag),
+        integrator::ProfileIndex::Distance => {
+            integrator::Drag::by_distance(accel, opts.drag, length.saturating_sub(1) as f64)
+        }
     };
+    let graded = grade
+        .as_ref()
+        .map(|grade| integrator::Grade::new(&drag, grade, opts.grade_index.profile_index()));
+    let dynamics: &dyn integrator::Dynamics = match &graded {
+        Some(graded) => graded,
+        None => &drag,
+    };
+    let checkpoint = opts.iter_checkpoint();
 
-    let mut log_timer = time::LogTimer::default();
-    let mut timer = time::Timing::default();
+    let mut log_timer = time::LogTimer::with_duration(opts.log_interval);
+    let mut timer = time::Timing::with_samples(opts.iterations as usize);
 
     for iter in 0..(opts.iterations) {
-        let mut vel_lookup = InterpolateLookup::from(Vec::with_capacity(length));
-        vel_lookup.push(0.0);
-
         let start = std::time::Instant::now();
 
-        // we are going to calculate all of the differences between the
-        // acceleration values and then sum them together after they have been
-        // calculated. once everything has been calculated we will collected
-        // them into a vec of f64's and the ordering will be preserved.
-        let vel_diffs = (1..length)
-            .into_par_iter()
-            .map(|sec| sum_cb((sec - 1) as f64, sec as f64, opts.step, &accel_lookup))
-            .collect::<Vec<f64>>();
+        let states = integrator::trajectory(
+            dynamics,
+            method,
+            integrator::State {
+                velocity: opts.initial_velocity,
+                position: opts.initial_position,
+                ..Default::default()
+            },
+            length.saturating_sub(1),
+            opts.step,
+            opts.velocity_bounds(),
+        );
 
-        let mut vel_rolling = 0.0f64;
+        timer.update(start.elapsed());
 
-        for v in vel_diffs {
-            vel_rolling += v;
+        if let Some(checkpoint) = &checkpoint {
+            checkpoint.update(iter, &timer)?;
+        }
 
-            vel_lookup.push(vel_rolling);
+        if !opts.quiet && log_timer.update() {
+            println!("iteration: {iter} {timer}");
         }
 
-        let pos_final = (1..length)
-            .into_par_iter()
-            .map(|sec| sum_cb((sec - 1) as f64, sec as f64, opts.step, &vel_lookup))
-            .sum::<f64>();
+        if iter == opts.iterations - 1 {
+            let vel_lookup = states
+                .iter()
+                .map(|state| state.velocity)
+                .collect::<InterpolateLookup>();
+            let pos_lookup = states
+                .iter()
+                .map(|state| state.position)
+                .collect::<InterpolateLookup>();
+            let last = states.last().copied().unwrap_or_default();
+
+            opts.check_velocity(&vel_lookup)?;
+
+            print_result(opts, last.velocity, last.position)?;
+
+            write_outputs(opts, accel, &vel_lookup, &pos_lookup)?;
+            write_metrics(opts, last.velocity, last.position, &timer)?;
+            write_sqlite(opts, length, accel, last.velocity, last.position)?;
+        }
+    }
 
-        timer.update(start.elapsed());
+    write_timing_json(opts, &timer)?;
+    print_timing(opts, &timer);
 
-        if log_timer.update() {
+    Ok(())
+}
+
+/// runs either the non multi-threaded or multi-threaded train sim with the
+/// provided acceleration callable
+fn run_sim<A>(length: usize, opts: &SimOpts, parallel: bool, accel: &A) -> anyhow::Result<()>
+where
+    A: Callable<f64> + Sync + ?Sized,
+{
+    if !opts.quiet {
+        println!(
+            "lenth: {length} step: {} iterations: {}",
+            opts.step, opts.iterations
+        );
+
+        if let Some(steps) = sim::velocity_steps(length, opts, accel) {
+            println!(
+                "velocity steps: {} uniform: {}",
+                steps.iter().map(|step| *step as u64).sum::<u64>(),
+                steps.len() as u64 * opts.adaptive_max_step.unwrap_or_default() as u64
+            );
+        }
+    }
+
+    let mut log_timer = time::LogTimer::with_duration(opts.log_interval);
+
+    let result = sim::simulate(length, opts, parallel, accel, |iter, timer| {
+        if !opts.quiet && log_timer.update() {
             println!("iteration: {iter} {timer}");
         }
+    })?;
+
+    print_result(opts, result.final_velocity, result.final_position)?;
+
+    write_outputs(
+        opts,
+        accel,
+        &result.velocity,
+        &position_lookup(opts, &result.velocity)?,
+    )?;
+    write_metrics(
+        opts,
+        result.final_velocity,
+        result.final_position,
+        &result.timing,
+    )?;
+    write_sqlite(
+        opts,
+        length,
+        accel,
+        result.final_velocity,
+        result.final_position,
+    )?;
+
+    write_timing_json(opts, &result.timing)?;
+    print_timing(opts, &result.timing);
 
-        if iter == opts.iterations - 1 {
-            println!("final velocity: {vel_rolling:+}");
-            println!("final position: {pos_final:+}");
+    Ok(())
+}
+
+/// runs the train sim with the provided acceleration callable using the
+/// custom quadrature rule for every panel
+fn run_quadrature<A>(
+    length: usize,
+    opts: &SimOpts,
+    rule: &[(f64, f64)],
+    accel: &A,
+) -> anyhow::Result<()>
+where
+    A: Callable<f64> + ?Sized,
+{
+    if !opts.quiet {
+        println!(
+            "lenth: {length} step: {} iterations: {}",
+            opts.step, opts.iterations
+        );
+        println!("quadrature nodes: {}", rule.len());
+    }
+
+    let mut log_timer = time::LogTimer::with_duration(opts.log_interval);
+
+    let result = sim::simulate_quadrature(length, opts, rule, accel, |iter, timer| {
+        if !opts.quiet && log_timer.update() {
+            println!("iteration: {iter} {timer}");
         }
+    })?;
+
+    print_result(opts, result.final_velocity, result.final_position)?;
+
+    let pos_lookup = summation::cumulative_custom_quadrature(
+        result.velocity.len(),
+        opts.position_step(),
+        rule,
+        &result.velocity,
+    )?
+    .iter()
+    .map(|position| position + opts.initial_position)
+    .collect();
+
+    write_outputs(opts, accel, &result.velocity, &pos_lookup)?;
+    write_metrics(
+        opts,
+        result.final_velocity,
+        result.final_position,
+        &result.timing,
+    )?;
+    write_sqlite(
+        opts,
+        length,
+        accel,
+        result.final_velocity,
+        result.final_position,
+    )?;
+
+    write_timing_json(opts, &result.timing)?;
+    print_timing(opts, &result.timing);
+
+    Ok(())
+}
+
+/// runs the train sim with a profile that has a time for each sample
+fn run_sim_times<A>(times: &[f64], opts: &SimOpts, parallel: bool, accel: &A) -> anyhow::Result<()>
+where
+    A: Callable<f64> + Sync + ?Sized,
+{
+    if !opts.quiet {
+        println!(
+            "samples: {} duration: {} step: {} iterations: {}",
+            times.len(),
+            times
+                .last()
+                .zip(times.first())
+                .map_or(0.0, |(last, first)| last - first),
+            opts.step,
+            opts.iterations
+        );
     }
 
-    println!("{timer}");
+    let mut log_timer = time::LogTimer::with_duration(opts.log_interval);
+
+    let result = sim::simulate_times(times, opts, parallel, accel, |iter, timer| {
+        if !opts.quiet && log_timer.update() {
+            println!("iteration: {iter} {timer}");
+        }
+    })?;
+
+    print_result(opts, result.final_velocity, result.final_position)?;
+
+    write_metrics(
+        opts,
+        result.final_velocity,
+        result.final_position,
+        &result.timing,
+    )?;
+
+    write_timing_json(opts, &result.timing)?;
+    print_timing(opts, &result.timing);
+
+    Ok(())
+}
+
+/// reports the stops and writes the velocity and position of each second to
+/// the requested outputs
+fn write_outputs<A>(
+    opts: &SimOpts,
+    accel: &A,
+    vel_lookup: &InterpolateLookup,
+    pos_lookup: &InterpolateLookup,
+) -> anyhow::Result<()>
+where
+    A: Callable<f64> + ?Sized,
+{
+    if opts.report_stops {
+        output::write_stops(std::io::stdout().lock(), vel_lookup)?;
+    }
+
+    write_cumulative(opts, vel_lookup)?;
+    write_npy(opts, vel_lookup, pos_lookup)?;
+    write_trajectory(opts, accel, vel_lookup, pos_lookup)?;
+
+    Ok(())
+}
+
+/// writes the cumulative velocity table to the requested output file if one
+/// was specified
+fn write_cumulative(opts: &SimOpts, vel_lookup: &InterpolateLookup) -> anyhow::Result<()> {
+    if let Some(path) = &opts.cumulative_output {
+        let file =
+            std::fs::File::create(path).context("failed to create cumulative output file")?;
+
+        output::write_cumulative(file, vel_lookup)?;
+    }
+
+    Ok(())
+}
+
+/// prints the time weighted average of the acceleration profile, weighting by
+/// the spacing of the time column if the profile has one
+fn print_time_average(opts: &SimOpts, accel: &InterpolateLookup) -> anyhow::Result<()> {
+    let config = opts.summation_config();
+    let avg = if let Some(times) = accel.xs() {
+        summation::time_average_intervals(times, opts.step, &config.apply(accel), opts.summation())?
+    } else {
+        summation::time_average(
+            accel.len(),
+            opts.step,
+            &config.apply(accel),
+            opts.summation(),
+        )?
+    };
+
+    println!("accel time average: {avg:+}");
+
+    Ok(())
+}
+
+/// calculates the position at each second of the velocity table starting from
+/// the initial position of the options
+fn position_lookup(
+    opts: &SimOpts,
+    vel_lookup: &InterpolateLookup,
+) -> Result<InterpolateLookup, summation::SummationError> {
+    Ok(summation::cumulative_panels(
+        vel_lookup.len(),
+        opts.position_step(),
+        vel_lookup,
+        opts.summation(),
+    )?
+    .iter()
+    .map(|position| position + opts.initial_position)
+    .collect())
+}
+
+/// writes the trajectory of each second to the requested output file if one
+/// was specified
+fn write_trajectory<A>(
+    opts: &SimOpts,
+    accel: &A,
+    vel_lookup: &InterpolateLookup,
+    pos_lookup: &InterpolateLookup,
+) -> anyhow::Result<()>
+where
+    A: Callable<f64> + ?Sized,
+{
+    if let Some(path) = &opts.output {
+        let file = std::fs::File::create(path).context("failed to create output file")?;
+
+        if let Some(times) = opts.output_times(vel_lookup.len())? {
+            output::write_trajectory_at(file, &times, accel, vel_lookup, pos_lookup)?;
+        } else {
+            output::write_trajectory(file, accel, vel_lookup, pos_lookup)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// writes the velocity and position of each second to the requested npy file
+/// if one was specified
+fn write_npy(
+    opts: &SimOpts,
+    vel_lookup: &InterpolateLookup,
+    pos_lookup: &InterpolateLookup,
+) -> anyhow::Result<()> {
+    if let Some(path) = &opts.output_npy {
+        let rows = vel_lookup
+            .iter()
+            .zip(pos_lookup.iter())
+            .map(|(velocity, position)| [velocity, position])
+            .collect::<Vec<[f64; 2]>>();
+
+        let file = std::fs::File::create(path).context("failed to create npy output file")?;
+
+        output::write_npy(std::io::BufWriter::new(file), &rows)?;
+    }
+
+    Ok(())
+}
+
+/// writes the final results as prometheus metrics to the requested file if
+/// one was specified
+fn write_metrics(
+    opts: &SimOpts,
+    final_velocity: f64,
+    final_position: f64,
+    timer: &time::Timing,
+) -> anyhow::Result<()> {
+    if let Some(path) = &opts.metrics {
+        let file = std::fs::File::create(path).context("failed to create metrics file")?;
+
+        output::write_metrics(file, final_velocity, final_position, timer)?;
+    }
+
+    Ok(())
+}
+
+/// appends the final results to the requested sqlite database if one was
+/// specified
+#[cfg(feature = "rusqlite")]
+fn write_sqlite<A>(
+    opts: &SimOpts,
+    length: usize,
+    accel: &A,
+    final_velocity: f64,
+    final_position: f64,
+) -> anyhow::Result<()>
+where
+    A: Callable<f64> + ?Sized,
+{
+    if let Some(path) = &opts.sqlite {
+        let row = train_sim::sqlite::ResultRow {
+            input_hash: output::profile_hash(length, accel),
+            options: format!("{opts:?}"),
+            final_velocity,
+            final_position,
+        };
+
+        train_sim::sqlite::append_result(path, &row)?;
+    }
+
+    Ok(())
+}
+
+/// does nothing when built without sqlite support
+#[cfg(not(feature = "rusqlite"))]
+fn write_sqlite<A>(
+    _opts: &SimOpts,
+    _length: usize,
+    _accel: &A,
+    _final_velocity: f64,
+    _final_position: f64,
+) -> anyhow::Result<()>
+where
+    A: Callable<f64> + ?Sized,
+{
+    Ok(())
+}
+
+/// writes the batch results in the given format to the given file or stdout
+/// if no file was specified
+fn write_results(
+    path: Option<&std::path::Path>,
+    format: &ResultsFormat,
+    results: &[batch::BatchResult],
+) -> anyhow::Result<()> {
+    let writer: Box<dyn std::io::Write> = if let Some(path) = path {
+        let file = std::fs::File::create(path).context("failed to create results file")?;
+
+        Box::new(file)
+    } else {
+        Box::new(std::io::stdout().lock())
+    };
+
+    match format {
+        ResultsFormat::Csv => batch::write_results(writer, results),
+        ResultsFormat::Json => batch::write_results_json(writer, results),
+    }
+}
+
+/// writes the final timing information as json to the requested file if one
+/// was specified
+fn write_timing_json(opts: &SimOpts, timer: &time::Timing) -> anyhow::Result<()> {
+    if let Some(path) = &opts.timing_json {
+        let file = std::fs::File::create(path).context("failed to create timing json file")?;
+
+        output::write_json(file, timer)?;
+    }
+
+    Ok(())
+}
+
+/// prints the final velocity and position in the requested format
+fn print_result(opts: &SimOpts, final_velocity: f64, final_position: f64) -> anyhow::Result<()> {
+    let result = output::FinalResult {
+        final_velocity,
+        final_position,
+    };
+
+    match opts.format {
+        ResultFormat::Human => println!("{result}"),
+        ResultFormat::Csv => print!("{}", result.to_csv()),
+        ResultFormat::Json => println!("{}", result.to_json()?),
+    }
+
+    Ok(())
+}
+
+/// prints the final timing information in the requested format
+fn print_timing(opts: &SimOpts, timer: &time::Timing) {
+    if opts.quiet {
+        return;
+    }
+
+    match opts.timing_format {
+        TimingFormat::Human => println!("{timer}"),
+        TimingFormat::Csv => print!("{}", timer.to_csv()),
+    }
 }
@@ -0,0 +1,176 @@
+//! propagating the uncertainty of an acceleration profile into the final
+//! results by integrating randomly perturbed copies of it
+
+use crate::args::SimOpts;
+use crate::sim;
+use crate::summation::InterpolateLookup;
+
+/// a small seeded pseudo random number generator
+///
+/// uses the splitmix64 algorithm which is fast and good enough for adding
+/// noise but is not suitable for anything security related
+#[derive(Debug, Clone)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    /// creates a new generator from the given seed
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// generates the next random 64 bit value
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    /// generates a random value in the range `(0, 1]`
+    pub fn next_f64(&mut self) -> f64 {
+        ((self.next_u64() >> 11) + 1) as f64 / (1u64 << 53) as f64
+    }
+
+    /// generates a normally distributed value with the given standard
+    /// deviation and a mean of zero using the Box-Muller transform
+    pub fn gaussian(&mut self, sigma: f64) -> f64 {
+        let u1 = self.next_f64();
+        let u2 = self.next_f64();
+
+        sigma * (-2.0 * u1.ln()).sqrt() * (std::f64::consts::TAU * u2).cos()
+    }
+}
+
+/// the distribution of the final results of a monte carlo run
+#[derive(Debug, Clone)]
+pub struct MonteCarloResult {
+    /// the final velocity of each iteration
+    pub velocities: Vec<f64>,
+    /// the final position of each iteration
+    pub positions: Vec<f64>,
+}
+
+impl MonteCarloResult {
+    /// the mean and population standard deviation of the final velocities
+    pub fn velocity_stats(&self) -> Option<(f64, f64)> {
+        stats(&self.velocities)
+    }
+
+    /// the mean and population standard deviation of the final positions
+    pub fn position_stats(&self) -> Option<(f64, f64)> {
+        stats(&self.positions)
+    }
+}
+
+/// calculates the mean and population standard deviation of the given values
+fn stats(values: &[f64]) -> Option<(f64, f64)> {
+    let lookup = InterpolateLookup::from(values.to_vec());
+
+    lookup.mean().zip(lookup.std_dev())
+}
+
+/// simulates the given acceleration profile once per iteration with gaussian
+/// noise of the given standard deviation added to every value
+///
+/// each perturbed profile goes through [`sim::simulate_profile`] with the
+/// given mask so it is simulated the same way as the unperturbed profile. the
+/// noise is generated from the given seed so the same seed will always
+/// produce the same results
+pub fn run(
+    accel: &InterpolateLookup,
+    opts: &SimOpts,
+    mask: Option<&[bool]>,
+    sigma: f64,
+    seed: u64,
+) -> anyhow::Result<MonteCarloResult> {
+    // the iterations are the amount of perturbed profiles here
+    let single = opts.single_iteration();
+
+    let length = accel.len();
+    let values = accel.to_vec();
+
+    let mut rng = Rng::new(seed);
+    let mut rtn = MonteCarloResult {
+        velocities: Vec::with_capacity(opts.iterations as usize),
+        positions: Vec::with_capacity(opts.iterations as usize),
+    };
+
+    for _ in 0..opts.iterations {
+        let perturbed = values
+            .iter()
+            .map(|value| value + rng.gaussian(sigma))
+            .collect::<InterpolateLookup>();
+
+        let result = sim::simulate_profile(length, &single, false, &perturbed, mask, |_, _| {})?;
+
+        rtn.velocities.push(result.final_velocity);
+        rtn.positions.push(result.final_position);
+    }
+
+    Ok(rtn)
+}
+
+#[cfg(test)]
+mod test {
+    use approx::assert_relative_eq;
+    use clap::Parser;
+
+    use super::*;
+    use crate::args::App;
+
+    #[test]
+    fn zero_sigma() {
+        let opts = App::parse_from([
+            "train_sim",
+            "--iterations",
+            "5",
+            "--delay",
+            "1.5",
+            "--max-velocity",
+            "6",
+            "csv",
+            "a.csv",
+        ])
+        .opts;
+        let accel = InterpolateLookup::from(vec![0.0, 1.0, 4.0, 9.0, 4.0, 1.0]);
+        let mask = [true, true, false, true, true, true];
+
+        let expected =
+            sim::simulate_profile(accel.len(), &opts, false, &accel, Some(&mask), |_, _| {})
+                .unwrap();
+
+        let result = run(&accel, &opts, Some(&mask), 0.0, 42).unwrap();
+        let (vel_mean, vel_std) = result.velocity_stats().unwrap();
+        let (pos_mean, pos_std) = result.position_stats().unwrap();
+
+        assert_eq!(result.velocities.len(), 5);
+        assert_eq!(expected.final_velocity, 6.0);
+        assert_relative_eq!(vel_mean, expected.final_velocity);
+        assert_relative_eq!(pos_mean, expected.final_position);
+        assert!(vel_std < 1e-12);
+        assert!(pos_std < 1e-12);
+
+        // noise should spread out the results while the same seed repeats them
+        let noisy = run(&accel, &opts, None, 0.5, 42).unwrap();
+
+        assert!(noisy.position_stats().unwrap().1 > 0.0);
+        assert_eq!(
+            noisy.positions,
+            run(&accel, &opts, None, 0.5, 42).unwrap().positions
+        );
+    }
+
+    #[test]
+    fn gaussian_moments() {
+        let mut rng = Rng::new(7);
+        let values = (0..20_000).map(|_| rng.gaussian(2.0)).collect::<Vec<f64>>();
+        let (mean, std) = stats(&values).unwrap();
+
+        assert!(mean.abs() < 0.05, "mean: {mean}");
+        assert!((std - 2.0).abs() < 0.05, "std: {std}");
+    }
+}
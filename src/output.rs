@@ -0,0 +1,635 @@
+//! utilities for writing the results of a simulation to files
+
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::io::{Read, Write};
+
+use anyhow::Context;
+use serde::Serialize;
+
+use crate::summation::{Callable, InterpolateLookup};
+use crate::time::Timing;
+
+/// statistics of a loaded profile
+///
+/// values that cannot be calculated, like for an empty profile, will be
+/// [`None`]
+#[derive(Debug, Clone, Serialize)]
+pub struct ProfileStats {
+    /// the total number of values
+    pub length: usize,
+    /// the minimum value
+    pub min: Option<f64>,
+    /// the maximum value
+    pub max: Option<f64>,
+    /// the mean of all values
+    pub mean: Option<f64>,
+    /// the population standard deviation of all values
+    pub std: Option<f64>,
+    /// the total number of values that are NaN
+    pub nan_count: usize,
+}
+
+impl ProfileStats {
+    /// calculates the statistics of the given lookup table
+    pub fn new(lookup: &InterpolateLookup) -> Self {
+        Self {
+            length: lookup.len(),
+            min: lookup.min(),
+            max: lookup.max(),
+            mean: lookup.mean(),
+            std: lookup.std_dev(),
+            nan_count: lookup.iter().filter(|v| v.is_nan()).count(),
+        }
+    }
+}
+
+/// the final velocity and position of a simulation
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct FinalResult {
+    /// the velocity at the end of the profile
+    pub final_velocity: f64,
+    /// the position at the end of the profile
+    pub final_position: f64,
+}
+
+impl FinalResult {
+    /// formats the result as a single csv row of the velocity and position
+    pub fn to_csv(&self) -> String {
+        format!("{},{}\n", self.final_velocity, self.final_position)
+    }
+
+    /// formats the result as a single line json object
+    pub fn to_json(&self) -> anyhow::Result<String> {
+        serde_json::to_string(self).context("failed to write json")
+    }
+}
+
+impl Display for FinalResult {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        write!(
+            f,
+            "final velocity: {:+}\nfinal position: {:+}",
+            self.final_velocity, self.final_position
+        )
+    }
+}
+
+/// writes the given value as pretty printed json to the provided writer
+pub fn write_json<W, T>(writer: W, value: &T) -> anyhow::Result<()>
+where
+    W: Write,
+    T: Serialize + ?Sized,
+{
+    serde_json::to_writer_pretty(writer, value).context("failed to write json")
+}
+
+/// writes the given cumulative table as csv to the provided writer
+///
+/// each row contains the second and the velocity stored for that second
+pub fn write_cumulative<W>(writer: W, lookup: &InterpolateLookup) -> anyhow::Result<()>
+where
+    W: Write,
+{
+    write_table(writer, "velocity", lookup)
+}
+
+/// writes the trajectory of a simulation as csv to the provided writer
+///
+/// each row contains the second along with the acceleration, velocity, and
+/// position at that second. the acceleration is sampled from the given
+/// callable so it should be the same callable used for the integration
+pub fn write_trajectory<W, A>(
+    writer: W,
+    accel: &A,
+    vel_lookup: &InterpolateLookup,
+    pos_lookup: &InterpolateLookup,
+) -> anyhow::Result<()>
+where
+    W: Write,
+    A: Callable<f64> + ?Sized,
+{
+    let times = (0..vel_lookup.len())
+        .map(|second| second as f64)
+        .collect::<Vec<f64>>();
+
+    write_trajectory_at(writer, &times, accel, vel_lookup, pos_lookup)
+}
+
+/// the seconds written when only keeping every `factor` second of a
+/// trajectory with the given amount of seconds
+///
+/// the first and last seconds are always included
+pub fn downsampled_times(length: usize, factor: usize) -> Vec<f64> {
+    let mut rtn = (0..length)
+        .step_by(factor.max(1))
+        .map(|second| second as f64)
+        .collect::<Vec<f64>>();
+
+    if let Some(last) = length.checked_sub(1) {
+        if rtn.last() != Some(&(last as f64)) {
+            rtn.push(last as f64);
+        }
+    }
+
+    rtn
+}
+
+/// the velocity and position columns of a trajectory csv
+#[derive(Debug, Clone)]
+pub struct Trajectory {
+    /// the velocity of each row
+    pub velocity: InterpolateLookup,
+    /// the position of each row
+    pub position: InterpolateLookup,
+}
+
+/// reads a trajectory csv written by [`write_trajectory`] from the provided
+/// reader
+///
+/// the velocity and position columns are found by their header names
+pub fn read_trajectory<R>(reader: R) -> anyhow::Result<Trajectory>
+where
+    R: Read,
+{
+    let mut csv_reader = csv::Reader::from_reader(reader);
+    let headers = csv_reader
+        .headers()
+        .context("failed to read trajectory header")?;
+
+    let column = |name: &str| {
+        headers
+            .iter()
+            .position(|header| header == name)
+            .with_context(|| format!("trajectory is missing the {name} column"))
+    };
+    let velocity_index = column("velocity")?;
+    let position_index = column("position")?;
+
+    let mut rtn = Trajectory {
+        velocity: InterpolateLookup::from(Vec::new()),
+        position: InterpolateLookup::from(Vec::new()),
+    };
+
+    for (index, try_record) in csv_reader.records().enumerate() {
+        let record =
+            try_record.with_context(|| format!("failed to read trajectory row. {}", index + 1))?;
+
+        let value = |column: usize| -> anyhow::Result<f64> {
+            record
+                .get(column)
+                .and_then(|value| value.parse().ok())
+                .with_context(|| format!("invalid trajectory value. {}", index + 1))
+        };
+
+        rtn.velocity.push(value(velocity_index)?);
+        rtn.position.push(value(position_index)?);
+    }
+
+    Ok(rtn)
+}
+
+/// writes the trajectory of a simulation at the given times as csv to the
+/// provided writer
+///
+/// the velocity and position are interpolated from the given tables. fails
+/// if a time is outside of the tables
+pub fn write_trajectory_at<W, A>(
+    writer: W,
+    times: &[f64],
+    accel: &A,
+    vel_lookup: &InterpolateLookup,
+    pos_lookup: &InterpolateLookup,
+) -> anyhow::Result<()>
+where
+    W: Write,
+    A: Callable<f64> + ?Sized,
+{
+    let last = vel_lookup.len().min(pos_lookup.len()) as f64 - 1.0;
+    let mut csv_writer = csv::Writer::from_writer(writer);
+
+    csv_writer
+        .write_record(["second", "acceleration", "velocity", "position"])
+        .context("failed to write trajectory header")?;
+
+    for time in times {
+        if !(0.0..=last).contains(time) {
+            anyhow::bail!("output time is outside of the simulation. {time}");
+        }
+
+        csv_writer
+            .write_record([
+                time.to_string(),
+                accel.call(*time).to_string(),
+                vel_lookup.call(*time).to_string(),
+                pos_lookup.call(*time).to_string(),
+            ])
+            .context("failed to write trajectory entry")?;
+    }
+
+    csv_writer
+        .flush()
+        .context("failed to flush trajectory output")?;
+
+    Ok(())
+}
+
+/// writes the given rows as a 2D little endian f64 array in the NumPy .npy
+/// format to the provided writer
+///
+/// the shape of the array will be `(rows.len(), N)`
+pub fn write_npy<W, const N: usize>(mut writer: W, rows: &[[f64; N]]) -> anyhow::Result<()>
+where
+    W: Write,
+{
+    const MAGIC: &[u8] = b"\x93NUMPY\x01\x00";
+
+    let mut header = format!(
+        "{{'descr': '<f8', 'fortran_order': False, 'shape': ({}, {N}), }}",
+        rows.len()
+    );
+
+    // the magic string, header length, and header must be a multiple of 64
+    // bytes with the header ending in a newline
+    let unpadded = MAGIC.len() + 2 + header.len() + 1;
+    header.push_str(&" ".repeat((64 - unpadded % 64) % 64));
+    header.push('\n');
+
+    let header_len = u16::try_from(header.len()).context("npy header is too large")?;
+
+    writer
+        .write_all(MAGIC)
+        .and_then(|_| writer.write_all(&header_len.to_le_bytes()))
+        .and_then(|_| writer.write_all(header.as_bytes()))
+        .context("failed to write npy header")?;
+
+    for row in rows {
+        for value in row {
+            writer
+                .write_all(&value.to_le_bytes())
+                .context("failed to write npy entry")?;
+        }
+    }
+
+    writer.flush().context("failed to flush npy output")?;
+
+    Ok(())
+}
+
+/// writes the given table as csv to the provided writer
+///
+/// each row contains the second and the value stored for that second with the
+/// value column using the given name
+pub fn write_table<W>(writer: W, name: &str, lookup: &InterpolateLookup) -> anyhow::Result<()>
+where
+    W: Write,
+{
+    let mut csv_writer = csv::Writer::from_writer(writer);
+
+    csv_writer
+        .write_record(["second", name])
+        .context("failed to write table header")?;
+
+    for (second, value) in lookup.iter().enumerate() {
+        csv_writer
+            .write_record([second.to_string(), value.to_string()])
+            .context("failed to write table entry")?;
+    }
+
+    csv_writer.flush().context("failed to flush table output")?;
+
+    Ok(())
+}
+
+/// writes the amount of stops and the time of each stop found in the given
+/// velocity table to the provided writer
+///
+/// a stop is where the velocity crosses zero, see
+/// [`InterpolateLookup::zero_crossings`]
+pub fn write_stops<W>(mut writer: W, vel_lookup: &InterpolateLookup) -> anyhow::Result<()>
+where
+    W: Write,
+{
+    let stops = vel_lookup.zero_crossings();
+
+    writeln!(writer, "stops: {}", stops.len()).context("failed to write stops")?;
+
+    for time in stops {
+        writeln!(writer, "stop at: {time}").context("failed to write stop")?;
+    }
+
+    Ok(())
+}
+
+/// hashes the values of each second of the given acceleration callable
+///
+/// uses 64 bit FNV-1a so the hash is the same between runs and builds. the
+/// hash is returned as hex
+pub fn profile_hash<A>(length: usize, accel: &A) -> String
+where
+    A: Callable<f64> + ?Sized,
+{
+    let mut hash: u64 = 0xcbf29ce484222325;
+
+    for second in 0..length {
+        for byte in accel.call(second as f64).to_le_bytes() {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+    }
+
+    format!("{hash:016x}")
+}
+
+/// writes the final results and timing information as prometheus text
+/// format metrics to the provided writer
+///
+/// all durations are in seconds
+pub fn write_metrics<W>(
+    mut writer: W,
+    final_velocity: f64,
+    final_position: f64,
+    timing: &Timing,
+) -> anyhow::Result<()>
+where
+    W: Write,
+{
+    let metrics = [
+        ("final_velocity", "gauge", final_velocity),
+        ("final_position", "gauge", final_position),
+        ("iterations", "gauge", timing.count() as f64),
+        ("timing_min_seconds", "gauge", timing.min().as_secs_f64()),
+        ("timing_max_seconds", "gauge", timing.max().as_secs_f64()),
+        (
+            "timing_avg_seconds",
+            "gauge",
+            timing.average().as_secs_f64(),
+        ),
+        (
+            "timing_total_seconds",
+            "gauge",
+            timing.total().as_secs_f64(),
+        ),
+    ];
+
+    for (name, kind, value) in metrics {
+        writeln!(writer, "# TYPE train_sim_{name} {kind}")
+            .and_then(|_| writeln!(writer, "train_sim_{name} {value}"))
+            .context("failed to write metric")?;
+    }
+
+    writer.flush().context("failed to flush metrics output")?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use approx::assert_relative_eq;
+
+    use super::*;
+    use crate::summation;
+
+    #[test]
+    fn final_result() {
+        let result = FinalResult {
+            final_velocity: 2.5,
+            final_position: -10.0,
+        };
+
+        assert_eq!(
+            result.to_string(),
+            "final velocity: +2.5\nfinal position: -10"
+        );
+        assert_eq!(result.to_csv(), "2.5,-10\n");
+        assert_eq!(
+            result.to_json().unwrap(),
+            r#"{"final_velocity":2.5,"final_position":-10.0}"#
+        );
+    }
+
+    #[test]
+    fn cumulative_last() {
+        let accel = InterpolateLookup::from(vec![0.0, 1.0, 4.0, 9.0, 16.0, 25.0]);
+        let vel =
+            summation::cumulative_panels(accel.len(), 10, &accel, summation::simpsons).unwrap();
+        let final_velocity =
+            summation::sum_panels(accel.len(), 10, &accel, summation::simpsons).unwrap();
+
+        let mut output = Vec::new();
+        write_cumulative(&mut output, &vel).unwrap();
+
+        let mut reader = csv::Reader::from_reader(output.as_slice());
+        let records = reader
+            .records()
+            .map(|record| record.unwrap())
+            .collect::<Vec<csv::StringRecord>>();
+        let last = records.last().unwrap();
+
+        assert_eq!(records.len(), accel.len());
+        assert_eq!(&last[0], "5");
+        assert_eq!(last[1].parse::<f64>().unwrap(), final_velocity);
+    }
+
+    #[test]
+    fn profile_stats_json() {
+        let lookup = InterpolateLookup::from(vec![1.0, 2.0, 3.0, 6.0]);
+
+        let mut output = Vec::new();
+        write_json(&mut output, &ProfileStats::new(&lookup)).unwrap();
+
+        let json: serde_json::Value = serde_json::from_slice(&output).unwrap();
+
+        assert_eq!(json["length"], 4);
+        assert_eq!(json["mean"], 3.0);
+        assert_eq!(json["min"], 1.0);
+        assert_eq!(json["max"], 6.0);
+        assert_eq!(json["nan_count"], 0);
+        assert_relative_eq!(json["std"].as_f64().unwrap(), 3.5f64.sqrt());
+    }
+
+    #[test]
+    fn metrics_lines() {
+        let mut timing = Timing::default();
+        timing.update(std::time::Duration::from_millis(250));
+        timing.update(std::time::Duration::from_millis(750));
+
+        let mut output = Vec::new();
+        write_metrics(&mut output, 12.5, -3.25, &timing).unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        let mut metrics = std::collections::HashMap::new();
+
+        for line in output.lines().filter(|line| !line.starts_with('#')) {
+            let (name, value) = line.split_once(' ').unwrap();
+
+            assert!(name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'));
+
+            metrics.insert(name, value.parse::<f64>().unwrap());
+        }
+
+        assert_eq!(metrics["train_sim_final_velocity"], 12.5);
+        assert_eq!(metrics["train_sim_final_position"], -3.25);
+        assert_eq!(metrics["train_sim_iterations"], 2.0);
+        assert_eq!(metrics["train_sim_timing_min_seconds"], 0.25);
+        assert_eq!(metrics["train_sim_timing_max_seconds"], 0.75);
+        assert_eq!(metrics["train_sim_timing_avg_seconds"], 0.5);
+        assert_eq!(metrics["train_sim_timing_total_seconds"], 1.0);
+    }
+
+    #[test]
+    fn npy_round_trip() {
+        let rows = [[0.0, 0.0], [1.5, 0.75], [3.0, 3.0]];
+
+        let mut output = Vec::new();
+        write_npy(&mut output, &rows).unwrap();
+
+        assert_eq!(&output[..8], b"\x93NUMPY\x01\x00");
+
+        let header_len = u16::from_le_bytes([output[8], output[9]]) as usize;
+        let header = std::str::from_utf8(&output[10..10 + header_len]).unwrap();
+        let data = &output[10 + header_len..];
+
+        assert_eq!((10 + header_len) % 64, 0);
+        assert!(header.ends_with('\n'));
+        assert!(header.contains("'descr': '<f8'"));
+        assert!(header.contains("'shape': (3, 2)"));
+
+        let values = data
+            .chunks_exact(8)
+            .map(|chunk| f64::from_le_bytes(chunk.try_into().unwrap()))
+            .collect::<Vec<f64>>();
+
+        assert_eq!(values, rows.concat());
+    }
+
+    #[test]
+    fn trajectory_accel() {
+        let accel = InterpolateLookup::from(vec![1.0, 2.0, 3.0, 4.0, 5.0]);
+        let shifted = summation::ShiftedCallable::new(&accel, 1.5, summation::ShiftFill::Zero);
+
+        let vel = summation::cumulative_panels(accel.len(), 10, &shifted, summation::trapezoidal)
+            .unwrap();
+        let pos =
+            summation::cumulative_panels(accel.len(), 10, &vel, summation::trapezoidal).unwrap();
+
+        let mut output = Vec::new();
+        write_trajectory(&mut output, &shifted, &vel, &pos).unwrap();
+
+        let mut reader = csv::Reader::from_reader(output.as_slice());
+        let records = reader
+            .records()
+            .map(|record| record.unwrap())
+            .collect::<Vec<csv::StringRecord>>();
+
+        assert_eq!(records.len(), accel.len());
+
+        for (second, record) in records.iter().enumerate() {
+            let column = |index: usize| record[index].parse::<f64>().unwrap();
+
+            assert_eq!(column(0), second as f64);
+            assert_eq!(column(1), shifted.call(second as f64));
+            assert_eq!(column(2), vel.get_index(second as f64));
+            assert_eq!(column(3), pos.get_index(second as f64));
+        }
+
+        assert_eq!(&records[1][1], "0");
+        assert_eq!(&records[2][1], "1.5");
+    }
+
+    #[test]
+    fn trajectory_times() {
+        let accel = InterpolateLookup::from(vec![1.0, 3.0, 2.0, 0.5, 4.0, 1.0, 1.0, 2.0, 0.0, 1.0]);
+
+        let vel =
+            summation::cumulative_panels(accel.len(), 10, &accel, summation::trapezoidal).unwrap();
+        let pos =
+            summation::cumulative_panels(accel.len(), 10, &vel, summation::trapezoidal).unwrap();
+        let times = [0.5, 2.5, 9.0];
+
+        let mut output = Vec::new();
+        write_trajectory_at(&mut output, &times, &accel, &vel, &pos).unwrap();
+
+        let mut reader = csv::Reader::from_reader(output.as_slice());
+        let records = reader
+            .records()
+            .map(|record| record.unwrap())
+            .collect::<Vec<csv::StringRecord>>();
+
+        assert_eq!(records.len(), times.len());
+
+        for (time, record) in times.iter().zip(&records) {
+            let column = |index: usize| record[index].parse::<f64>().unwrap();
+
+            assert_eq!(column(0), *time);
+            assert_eq!(column(1), accel.call(*time));
+            assert_eq!(column(2), vel.call(*time));
+            assert_eq!(column(3), pos.call(*time));
+        }
+
+        // half way between two seconds is the average of the table values
+        let halfway = (vel.get_index(2.0) + vel.get_index(3.0)) / 2.0;
+
+        assert_relative_eq!(records[1][2].parse::<f64>().unwrap(), halfway);
+        assert!(write_trajectory_at(Vec::new(), &[9.5], &accel, &vel, &pos).is_err());
+    }
+
+    #[test]
+    fn trajectory_downsample() {
+        let accel = InterpolateLookup::from(vec![1.0; 101]);
+
+        let vel =
+            summation::cumulative_panels(accel.len(), 10, &accel, summation::trapezoidal).unwrap();
+        let pos =
+            summation::cumulative_panels(accel.len(), 10, &vel, summation::trapezoidal).unwrap();
+        let times = downsampled_times(vel.len(), 10);
+
+        let mut output = Vec::new();
+        write_trajectory_at(&mut output, &times, &accel, &vel, &pos).unwrap();
+
+        let trajectory = read_trajectory(output.as_slice()).unwrap();
+
+        assert_eq!(trajectory.velocity.len(), 11);
+        assert_eq!(trajectory.velocity.get_index(0.0), vel.get_index(0.0));
+        assert_eq!(trajectory.velocity.get_index(10.0), vel.get_index(100.0));
+        assert_eq!(trajectory.position.get_index(10.0), pos.get_index(100.0));
+
+        assert_eq!(downsampled_times(12, 5), vec![0.0, 5.0, 10.0, 11.0]);
+        assert!(downsampled_times(0, 5).is_empty());
+    }
+
+    #[test]
+    fn single_stop() {
+        let accel = InterpolateLookup::from(vec![1.0, 1.0, 1.0, -1.0, -1.0, -1.0, -1.0, -1.0]);
+        let vel =
+            summation::cumulative_panels(accel.len(), 10, &accel, summation::trapezoidal).unwrap();
+
+        let mut output = Vec::new();
+        write_stops(&mut output, &vel).unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        let mut lines = output.lines();
+
+        assert_eq!(lines.next(), Some("stops: 1"));
+
+        let time = lines
+            .next()
+            .and_then(|line| line.strip_prefix("stop at: "))
+            .unwrap()
+            .parse::<f64>()
+            .unwrap();
+
+        assert_relative_eq!(time, 5.0, epsilon = 1e-9);
+        assert_eq!(lines.next(), None);
+    }
+
+    #[test]
+    fn profile_hash_values() {
+        let accel = InterpolateLookup::from(vec![1.0, 2.0, 3.0]);
+        let same = InterpolateLookup::from(vec![1.0, 2.0, 3.0]);
+        let changed = InterpolateLookup::from(vec![1.0, 2.0, 3.5]);
+
+        assert_eq!(profile_hash(0, &accel), "cbf29ce484222325");
+        assert_eq!(profile_hash(3, &accel), profile_hash(3, &same));
+        assert_ne!(profile_hash(3, &accel), profile_hash(3, &changed));
+    }
+}
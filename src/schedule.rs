@@ -0,0 +1,89 @@
+//! static scheduling of work on the global rayon thread pool
+//!
+//! rayon normally uses work stealing so the thread that processes a given
+//! index can change between runs. these helpers instead give every thread the
+//! same contiguous range of indices each time they are run
+
+use std::ops::Range;
+
+/// splits the given length into the given amount of contiguous ranges
+///
+/// the ranges are as close to equal in size as possible with the earlier
+/// ranges being one larger when the length does not divide evenly. some
+/// ranges will be empty if there are more parts than the length
+pub fn partition(length: usize, parts: usize) -> Vec<Range<usize>> {
+    assert_ne!(parts, 0);
+
+    let size = length / parts;
+    let remainder = length % parts;
+    let mut start = 0;
+
+    (0..parts)
+        .map(|part| {
+            let end = start + size + usize::from(part < remainder);
+            let range = start..end;
+
+            start = end;
+
+            range
+        })
+        .collect()
+}
+
+/// maps every index of the given range on the current rayon thread pool with
+/// each thread always processing the same partition of the range
+///
+/// the results will be in the same order as the range
+pub fn static_map<T, F>(range: Range<usize>, op: F) -> Vec<T>
+where
+    T: Send,
+    F: Fn(usize) -> T + Sync,
+{
+    let parts = partition(range.len(), rayon::current_num_threads());
+
+    rayon::broadcast(|ctx| {
+        parts[ctx.index()]
+            .clone()
+            .map(|index| op(range.start + index))
+            .collect::<Vec<T>>()
+    })
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn partition_sizes() {
+        assert_eq!(partition(10, 3), vec![0..4, 4..7, 7..10]);
+        assert_eq!(partition(2, 4), vec![0..1, 1..2, 2..2, 2..2]);
+    }
+
+    #[test]
+    fn stable_assignment() {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(4)
+            .build()
+            .unwrap();
+
+        let run = || {
+            pool.install(|| {
+                static_map(1..101, |index| {
+                    (index, rayon::current_thread_index().unwrap())
+                })
+            })
+        };
+
+        let first = run();
+        let second = run();
+
+        assert_eq!(first, second);
+        assert_eq!(first.len(), 100);
+        assert_eq!(first[0], (1, 0));
+        assert_eq!(first[99], (100, 3));
+        assert!(first.iter().map(|(index, _)| *index).eq(1..101));
+    }
+}
@@ -0,0 +1,807 @@
+//! running the velocity and position passes of the simulation over an
+//! acceleration profile
+
+use crate::args::SimOpts;
+use crate::schedule;
+use crate::summation::{
+    self, Callable, InterpolateLookup, KahanSum, MaskedCallable, ShiftedCallable, SummationError,
+    UncheckedLookup,
+};
+use crate::time::Timing;
+
+/// the results of the final iteration of a simulation
+pub struct SimResult {
+    /// the velocity at the last second of the profile
+    pub final_velocity: f64,
+    /// the position at the last second of the profile
+    pub final_position: f64,
+    /// the velocity at each second of the profile
+    pub velocity: InterpolateLookup,
+    /// the timing information of every iteration
+    pub timing: Timing,
+}
+
+/// runs the simulation with the provided acceleration callable for the amount
+/// of iterations specified in the options
+///
+/// when parallel the passes are calculated on the global rayon thread pool.
+/// progress is called with the iteration and the current timing information
+/// after each iteration
+///
+/// ```
+/// use clap::Parser;
+/// use train_sim::args::App;
+/// use train_sim::sim;
+/// use train_sim::summation::InterpolateLookup;
+///
+/// let opts = App::parse_from([
+///     "train_sim",
+///     "--iterations",
+///     "1",
+///     "--step",
+///     "1",
+///     "--algo",
+///     "trapezoidal",
+///     "csv",
+///     "a.csv",
+/// ])
+/// .opts;
+/// let accel = InterpolateLookup::from(vec![1.0; 11]);
+///
+/// let result = sim::simulate(accel.len(), &opts, false, &accel, |_, _| {}).unwrap();
+///
+/// assert_eq!(result.final_velocity, 10.0);
+/// assert_eq!(result.final_position, 50.0);
+/// ```
+pub fn simulate<A, F>(
+    length: usize,
+    opts: &SimOpts,
+    parallel: bool,
+    accel: &A,
+    progress: F,
+) -> anyhow::Result<SimResult>
+where
+    A: Callable<f64> + Sync + ?Sized,
+    F: FnMut(u32, &Timing),
+{
+    simulate_panels(Panels::Seconds(length), opts, parallel, accel, progress)
+}
+
+/// same as [`simulate`] but first removes the intervals excluded by the mask
+/// and then delays the acceleration by the delay of the options, the same as
+/// the command line does for a profile
+pub fn simulate_profile<A, F>(
+    length: usize,
+    opts: &SimOpts,
+    parallel: bool,
+    accel: &A,
+    mask: Option<&[bool]>,
+    progress: F,
+) -> anyhow::Result<SimResult>
+where
+    A: Callable<f64> + Sync + ?Sized,
+    F: FnMut(u32, &Timing),
+{
+    if let Some(mask) = mask {
+        simulate_delayed(
+            length,
+            opts,
+            parallel,
+            &MaskedCallable::new(accel, mask),
+            progress,
+        )
+    } else {
+        simulate_delayed(length, opts, parallel, accel, progress)
+    }
+}
+
+/// runs [`simulate`] with the acceleration delayed by the delay of the options
+/// if one was requested
+fn simulate_delayed<A, F>(
+    length: usize,
+    opts: &SimOpts,
+    parallel: bool,
+    accel: &A,
+    progress: F,
+) -> anyhow::Result<SimResult>
+where
+    A: Callable<f64> + Sync + ?Sized,
+    F: FnMut(u32, &Timing),
+{
+    if let Some(delay) = opts.delay {
+        let shifted = ShiftedCallable::new(accel, delay, opts.delay_fill.shift_fill());
+
+        simulate(length, opts, parallel, &shifted, progress)
+    } else {
+        simulate(length, opts, parallel, accel, progress)
+    }
+}
+
+/// same as [`simulate`] but each panel is between consecutive values of the
+/// given times instead of one second apart
+///
+/// the velocity table of the result uses the given times as its x values
+pub fn simulate_times<A, F>(
+    times: &[f64],
+    opts: &SimOpts,
+    parallel: bool,
+    accel: &A,
+    progress: F,
+) -> anyhow::Result<SimResult>
+where
+    A: Callable<f64> + Sync + ?Sized,
+    F: FnMut(u32, &Timing),
+{
+    simulate_panels(Panels::Times(times), opts, parallel, accel, progress)
+}
+
+/// same as [`simulate`] but every panel of both passes is calculated with
+/// [`summation::custom_quadrature`] and the given rule instead of the
+/// selected algorithm
+///
+/// the passes are sequential since the rule replaces the summation function
+/// that the parallel passes are dispatched with
+pub fn simulate_quadrature<A, F>(
+    length: usize,
+    opts: &SimOpts,
+    rule: &[(f64, f64)],
+    accel: &A,
+    mut progress: F,
+) -> anyhow::Result<SimResult>
+where
+    A: Callable<f64> + ?Sized,
+    F: FnMut(u32, &Timing),
+{
+    opts.check_steps()?;
+
+    let (min_velocity, max_velocity) = opts.velocity_bounds();
+    let checkpoint = opts.iter_checkpoint();
+
+    let mut timer = Timing::with_samples(opts.iterations as usize);
+    let mut last = None;
+    let mut vel_lookup = InterpolateLookup::from(Vec::with_capacity(length));
+
+    for iter in 0..(opts.iterations) {
+        vel_lookup.clear();
+        vel_lookup.push(opts.initial_velocity);
+
+        let start = std::time::Instant::now();
+
+        let mut vel_final = KahanSum::new(opts.initial_velocity);
+
+        for sec in 1..length {
+            let result = summation::custom_quadrature(
+                rule,
+                (sec - 1) as f64,
+                sec as f64,
+                opts.velocity_step(),
+                accel,
+            )?;
+
+            clamped_add(&mut vel_final, result, min_velocity, max_velocity);
+
+            vel_lookup.push(vel_final.value());
+        }
+
+        opts.check_velocity(&vel_lookup)?;
+
+        let mut pos_final = KahanSum::new(opts.initial_position);
+
+        for sec in 1..length {
+            pos_final.add(summation::custom_quadrature(
+                rule,
+                (sec - 1) as f64,
+                sec as f64,
+                opts.position_step(),
+                &vel_lookup,
+            )?);
+        }
+
+        timer.update(start.elapsed());
+
+        if let Some(checkpoint) = &checkpoint {
+            checkpoint.update(iter, &timer)?;
+        }
+
+        progress(iter, &timer);
+
+        last = Some((vel_final.value(), pos_final.value()));
+    }
+
+    let Some((final_velocity, final_position)) = last else {
+        anyhow::bail!("the simulation requires at least one iteration");
+    };
+
+    Ok(SimResult {
+        final_velocity,
+        final_position,
+        velocity: vel_lookup,
+        timing: timer,
+    })
+}
+
+/// chooses the amount of velocity steps of each second from the curvature of
+/// the acceleration callable when an adaptive max step was requested, see
+/// [`summation::curvature_steps`]
+///
+/// the curvature is estimated from the values at each second so the steps
+/// follow any delay or mask that the callable applies
+pub fn velocity_steps<A>(length: usize, opts: &SimOpts, accel: &A) -> Option<Vec<u32>>
+where
+    A: Callable<f64> + ?Sized,
+{
+    let max_step = opts.adaptive_max_step?;
+    let values = (0..length)
+        .map(|sec| accel.call(sec as f64))
+        .collect::<InterpolateLookup>();
+
+    Some(summation::curvature_steps(
+        &values,
+        opts.velocity_step().min(max_step),
+        max_step,
+    ))
+}
+
+/// the bounds of each panel of a simulation
+#[derive(Debug, Clone, Copy)]
+enum Panels<'a> {
+    /// the given amount of seconds with each panel being one second
+    Seconds(usize),
+    /// each panel is between consecutive times
+    Times(&'a [f64]),
+}
+
+impl Panels<'_> {
+    /// the amount of values that the panels are between
+    fn len(&self) -> usize {
+        match self {
+            Panels::Seconds(length) => *length,
+            Panels::Times(times) => times.len(),
+        }
+    }
+
+    /// the lower and upper bound of the panel that ends at the given index
+    fn bounds(&self, index: usize) -> (f64, f64) {
+        match self {
+            Panels::Seconds(_) => ((index - 1) as f64, index as f64),
+            Panels::Times(times) => (times[index - 1], times[index]),
+        }
+    }
+
+    /// converts a lookup table of the values at the end of each panel, spaced
+    /// one apart, to one that uses the x values of the panels
+    fn lookup(&self, values: InterpolateLookup) -> InterpolateLookup {
+        match self {
+            Panels::Seconds(_) => values,
+            Panels::Times(times) => InterpolateLookup::from_pairs(
+                times.iter().copied().zip(values.into_inner()).collect(),
+            ),
+        }
+    }
+}
+
+/// calls a lookup table of values spaced one apart at the index of the given
+/// time
+///
+/// lets a single velocity table be reused across iterations when the panels
+/// are between times instead of creating a table of pairs each iteration
+struct TimedLookup<'a> {
+    times: &'a [f64],
+    lookup: &'a InterpolateLookup,
+}
+
+impl Callable<f64> for TimedLookup<'_> {
+    fn call(&self, given: f64) -> f64 {
+        let index = self
+            .times
+            .partition_point(|time| *time <= given)
+            .clamp(1, self.times.len() - 1);
+        let lower = self.times[index - 1];
+        let upper = self.times[index];
+
+        self.lookup
+            .call((index - 1) as f64 + (given - lower) / (upper - lower))
+    }
+}
+
+/// runs the simulation over the given panels, see [`simulate`]
+fn simulate_panels<A, F>(
+    panels: Panels<'_>,
+    opts: &SimOpts,
+    parallel: bool,
+    accel: &A,
+    mut progress: F,
+) -> anyhow::Result<SimResult>
+where
+    A: Callable<f64> + Sync + ?Sized,
+    F: FnMut(u32, &Timing),
+{
+    opts.check_steps()?;
+
+    let steps = match panels {
+        Panels::Seconds(length) => velocity_steps(length, opts, accel),
+        Panels::Times(_) if opts.adaptive_max_step.is_some() => {
+            anyhow::bail!("an adaptive max step cannot be used with a time column");
+        }
+        Panels::Times(_) => None,
+    };
+
+    let config = opts.summation_config();
+    let accel = &config.apply(accel);
+    let accel_cb = opts.summation();
+
+    let checkpoint = opts.iter_checkpoint();
+
+    let mut timer = Timing::with_samples(opts.iterations as usize);
+    let mut last = None;
+
+    // the same table is cleared and filled again by every iteration so the
+    // values are only allocated once
+    let mut vel_lookup = InterpolateLookup::from(Vec::with_capacity(panels.len()));
+
+    for iter in 0..(opts.iterations) {
+        vel_lookup.clear();
+        vel_lookup.push(opts.initial_velocity);
+
+        let start = std::time::Instant::now();
+
+        let vel_final = if parallel {
+            velocity_pass_rayon(
+                panels,
+                (opts.velocity_step(), steps.as_deref()),
+                accel,
+                accel_cb,
+                opts.velocity_bounds(),
+                opts.repeatable_threads,
+                &mut vel_lookup,
+            )?
+        } else {
+            velocity_pass(
+                panels,
+                (opts.velocity_step(), steps.as_deref()),
+                accel,
+                accel_cb,
+                opts.velocity_bounds(),
+                &mut vel_lookup,
+            )?
+        };
+
+        opts.check_velocity(&vel_lookup)?;
+
+        let pos_final = if let Panels::Times(times) = panels {
+            position_pass(
+                panels,
+                opts.position_step(),
+                &config.apply(&TimedLookup {
+                    times,
+                    lookup: &vel_lookup,
+                }),
+                opts.summation(),
+                opts.initial_position,
+                parallel,
+                opts.repeatable_threads,
+            )?
+        } else if config.checked {
+            position_pass(
+                panels,
+                opts.position_step(),
+                &config.apply(&vel_lookup),
+                opts.summation(),
+                opts.initial_position,
+                parallel,
+                opts.repeatable_threads,
+            )?
+        } else {
+            position_pass(
+                panels,
+                opts.position_step(),
+                &config.apply(&UncheckedLookup::new(&vel_lookup)?),
+                opts.summation(),
+                opts.initial_position,
+                parallel,
+                opts.repeatable_threads,
+            )?
+        };
+
+        timer.update(start.elapsed());
+
+        if let Some(checkpoint) = &checkpoint {
+            checkpoint.update(iter, &timer)?;
+        }
+
+        progress(iter, &timer);
+
+        last = Some((vel_final, pos_final));
+    }
+
+    let Some((final_velocity, final_position)) = last else {
+        anyhow::bail!("the simulation requires at least one iteration");
+    };
+
+    Ok(SimResult {
+        final_velocity,
+        final_position,
+        velocity: panels.lookup(vel_lookup),
+        timing: timer,
+    })
+}
+
+/// calculates the velocity at the end of each panel of the acceleration
+/// callable and pushes it onto the velocities, returning the final velocity
+///
+/// the velocity continues from the last of the velocities and is clamped to
+/// the given minimum and maximum after each panel. each panel uses the step
+/// unless the amount of steps of every panel is given
+fn velocity_pass<T>(
+    panels: Panels<'_>,
+    (step, steps): (u32, Option<&[u32]>),
+    accel: &T,
+    sum_cb: fn(f64, f64, u32, &T) -> Result<f64, SummationError>,
+    (min_velocity, max_velocity): (f64, f64),
+    velocities: &mut InterpolateLookup,
+) -> Result<f64, SummationError>
+where
+    T: Callable<f64> + ?Sized,
+{
+    let mut vel_final = KahanSum::new(velocities.last().unwrap_or_default());
+
+    for index in 1..panels.len() {
+        let (lower, upper) = panels.bounds(index);
+        let step = steps.map_or(step, |steps| steps[index - 1]);
+        let result = sum_cb(lower, upper, step, accel)?;
+
+        clamped_add(&mut vel_final, result, min_velocity, max_velocity);
+
+        velocities.push(vel_final.value());
+    }
+
+    Ok(vel_final.value())
+}
+
+/// multi-threaded version of [`velocity_pass`]
+fn velocity_pass_rayon<T>(
+    panels: Panels<'_>,
+    (step, steps): (u32, Option<&[u32]>),
+    accel: &T,
+    sum_cb: fn(f64, f64, u32, &T) -> Result<f64, SummationError>,
+    (min_velocity, max_velocity): (f64, f64),
+    repeatable: bool,
+    velocities: &mut InterpolateLookup,
+) -> Result<f64, SummationError>
+where
+    T: Callable<f64> + Sync + ?Sized,
+{
+    use rayon::prelude::*;
+
+    // we are going to calculate all of the differences between the
+    // acceleration values and then sum them together after they have been
+    // calculated. once everything has been calculated we will collected
+    // them into a vec of f64's and the ordering will be preserved.
+    let panel = |index: usize| {
+        let (lower, upper) = panels.bounds(index);
+        let step = steps.map_or(step, |steps| steps[index - 1]);
+
+        sum_cb(lower, upper, step, accel)
+    };
+    let vel_diffs = if repeatable {
+        schedule::static_map(1..panels.len(), panel)
+    } else {
+        (1..panels.len())
+            .into_par_iter()
+            .map(panel)
+            .collect::<Vec<_>>()
+    };
+
+    let mut vel_rolling = KahanSum::new(velocities.last().unwrap_or_default());
+
+    for v in vel_diffs {
+        clamped_add(&mut vel_rolling, v?, min_velocity, max_velocity);
+
+        velocities.push(vel_rolling.value());
+    }
+
+    Ok(vel_rolling.value())
+}
+
+/// calculates the final position from the velocity callable starting from the
+/// given initial position
+fn position_pass<T>(
+    panels: Panels<'_>,
+    step: u32,
+    vel: &T,
+    sum_cb: fn(f64, f64, u32, &T) -> Result<f64, SummationError>,
+    initial: f64,
+    parallel: bool,
+    repeatable: bool,
+) -> Result<f64, SummationError>
+where
+    T: Callable<f64> + Sync + ?Sized,
+{
+    let distance = match (panels, parallel) {
+        (Panels::Seconds(length), true) => {
+            summation::sum_panels_rayon(length, step, vel, sum_cb, repeatable)
+        }
+        (Panels::Seconds(length), false) => {
+            summation::sum_panels_compensated(length, step, vel, sum_cb)
+        }
+        (Panels::Times(times), true) => {
+            summation::sum_intervals_rayon(times, step, vel, sum_cb, repeatable)
+        }
+        (Panels::Times(times), false) => {
+            summation::sum_intervals_compensated(times, step, vel, sum_cb)
+        }
+    }?;
+
+    Ok(initial + distance)
+}
+
+/// adds the given value to the sum and clamps the result to the given minimum
+/// and maximum
+///
+/// the compensation is dropped when the sum is clamped since the lost bits no
+/// longer belong to the clamped value
+fn clamped_add(sum: &mut KahanSum, given: f64, min: f64, max: f64) {
+    sum.add(given);
+
+    let value = sum.value();
+    let clamped = value.clamp(min, max);
+
+    if clamped != value {
+        *sum = KahanSum::new(clamped);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::args::App;
+    use approx::assert_relative_eq;
+    use clap::Parser;
+
+    fn opts(args: &[&str]) -> SimOpts {
+        App::parse_from(
+            ["train_sim", "--iterations", "1"]
+                .iter()
+                .chain(args)
+                .chain(&["csv", "a.csv"]),
+        )
+        .opts
+    }
+
+    #[test]
+    fn constant_accel() {
+        // a(t) = 2 so v(t) = 2t and x(t) = t^2
+        let accel = InterpolateLookup::from(vec![2.0; 11]);
+        let opts = opts(&["--algo", "simpsons", "--step", "10"]);
+
+        for parallel in [false, true] {
+            let result = simulate(accel.len(), &opts, parallel, &accel, |_, _| {}).unwrap();
+
+            assert_eq!(result.velocity.len(), 11);
+            assert_eq!(result.velocity.call(5.0), 10.0);
+            assert_eq!(result.final_velocity, 20.0);
+            assert_eq!(result.final_position, 100.0);
+        }
+    }
+
+    #[test]
+    fn initial_state() {
+        let accel = InterpolateLookup::from(vec![2.0; 11]);
+        let base = opts(&["--algo", "trapezoidal", "--step", "2"]);
+        let seeded = opts(&[
+            "--algo",
+            "trapezoidal",
+            "--step",
+            "2",
+            "--initial-velocity",
+            "3",
+            "--initial-position",
+            "-1",
+        ]);
+
+        for parallel in [false, true] {
+            let base = simulate(accel.len(), &base, parallel, &accel, |_, _| {}).unwrap();
+            let seeded = simulate(accel.len(), &seeded, parallel, &accel, |_, _| {}).unwrap();
+
+            assert_eq!(seeded.velocity.call(0.0), 3.0);
+            assert_eq!(seeded.final_velocity, base.final_velocity + 3.0);
+            assert_eq!(
+                seeded.final_position,
+                base.final_position + 3.0 * 10.0 - 1.0
+            );
+        }
+    }
+
+    #[test]
+    fn zero_step() {
+        let accel = InterpolateLookup::from(vec![2.0; 11]);
+        let times = [0.0, 1.0, 2.0];
+
+        for step in [
+            ["--step", "0"],
+            ["--velocity-step", "0"],
+            ["--position-step", "0"],
+        ] {
+            let opts = opts(&step);
+
+            assert!(simulate(accel.len(), &opts, false, &accel, |_, _| {}).is_err());
+            assert!(simulate_times(&times, &opts, true, &accel, |_, _| {}).is_err());
+        }
+    }
+
+    #[test]
+    fn adaptive_steps() {
+        // flat with a single spike so only the seconds around it are refined
+        let mut profile = vec![1.0; 21];
+        profile[10] = 6.0;
+
+        let accel = InterpolateLookup::from(profile);
+        let uniform = opts(&["--algo", "left-riemann", "--step", "2"]);
+        let adaptive = opts(&[
+            "--algo",
+            "left-riemann",
+            "--step",
+            "2",
+            "--adaptive-max-step",
+            "40",
+            "--initial-velocity",
+            "3",
+        ]);
+
+        let steps = velocity_steps(accel.len(), &adaptive, &accel).unwrap();
+
+        assert_eq!(steps.len(), 20);
+        assert_eq!(steps[0], 2);
+        assert_eq!(steps[9], 40);
+        assert!(velocity_steps(accel.len(), &uniform, &accel).is_none());
+
+        let expected =
+            summation::cumulative_adaptive_panels(&steps, &accel, summation::left_riemann).unwrap();
+
+        for parallel in [false, true] {
+            let result = simulate(accel.len(), &adaptive, parallel, &accel, |_, _| {}).unwrap();
+
+            assert_eq!(result.velocity.call(0.0), 3.0);
+
+            for (value, expected) in result.velocity.iter().zip(expected.iter()) {
+                assert_relative_eq!(value, expected + 3.0, epsilon = 1e-12);
+            }
+        }
+
+        let times = [0.0, 1.0, 2.0];
+
+        assert!(simulate_times(&times, &adaptive, false, &accel, |_, _| {}).is_err());
+    }
+
+    #[test]
+    fn custom_rule() {
+        let accel = InterpolateLookup::from(vec![0.0, 1.0, 4.0, 9.0, 4.0, 1.0]);
+        let opts = opts(&[
+            "--algo",
+            "gauss-legendre",
+            "--step",
+            "2",
+            "--initial-velocity",
+            "5",
+            "--initial-position",
+            "2",
+            "--max-velocity",
+            "12",
+        ]);
+
+        let expected = simulate(accel.len(), &opts, false, &accel, |_, _| {}).unwrap();
+        let result = simulate_quadrature(
+            accel.len(),
+            &opts,
+            &summation::GAUSS_LEGENDRE_5,
+            &accel,
+            |_, _| {},
+        )
+        .unwrap();
+
+        assert_eq!(result.final_velocity, 12.0);
+        assert_eq!(result.velocity.call(0.0), 5.0);
+        assert_relative_eq!(result.final_velocity, expected.final_velocity);
+        assert_relative_eq!(
+            result.final_position,
+            expected.final_position,
+            epsilon = 1e-12
+        );
+
+        let rule = [(0.0, 2.0)];
+
+        assert!(simulate_quadrature(
+            accel.len(),
+            &self::opts(&["--step", "0"]),
+            &rule,
+            &accel,
+            |_, _| {}
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn speed_limit() {
+        // accelerates into the cap and then brakes back below it
+        let mut profile = vec![2.0; 8];
+        profile.extend([-3.0; 3]);
+
+        let accel = InterpolateLookup::from(profile);
+        let opts = opts(&[
+            "--algo",
+            "trapezoidal",
+            "--step",
+            "1",
+            "--max-velocity",
+            "10",
+        ]);
+
+        for parallel in [false, true] {
+            let result = simulate(accel.len(), &opts, parallel, &accel, |_, _| {}).unwrap();
+
+            assert_eq!(result.velocity.call(5.0), 10.0);
+            assert_eq!(result.velocity.call(7.0), 10.0);
+            assert_eq!(result.velocity.call(8.0), 9.5);
+            assert_eq!(result.final_velocity, 3.5);
+        }
+    }
+
+    #[test]
+    fn variable_timestep() {
+        // a(t) = 2 sampled at uneven times so v(t) = 2t and x(t) = t^2
+        let times = [0.0, 0.5, 2.0, 2.25, 4.0];
+        let accel = InterpolateLookup::from_pairs(times.iter().map(|time| (*time, 2.0)).collect());
+        let opts = opts(&["--algo", "trapezoidal", "--step", "1"]);
+
+        for parallel in [false, true] {
+            let result = simulate_times(&times, &opts, parallel, &accel, |_, _| {}).unwrap();
+
+            assert_eq!(result.velocity.xs(), Some(times.as_slice()));
+            assert_eq!(result.velocity.call(2.25), 4.5);
+            assert_eq!(result.final_velocity, 8.0);
+            assert_relative_eq!(result.final_position, 16.0, epsilon = 1e-12);
+        }
+    }
+
+    #[test]
+    fn repeated_iterations() {
+        let times = [0.0, 0.5, 2.0, 2.25, 4.0];
+        let accel = InterpolateLookup::from_pairs(
+            times
+                .iter()
+                .zip([1.0, -2.0, 0.5, 3.0, 1.5])
+                .map(|(time, value)| (*time, value))
+                .collect(),
+        );
+        let once = opts(&["--algo", "simpsons", "--step", "10"]);
+        let repeated = App::parse_from([
+            "train_sim",
+            "--iterations",
+            "3",
+            "--algo",
+            "simpsons",
+            "--step",
+            "10",
+            "csv",
+            "a.csv",
+        ])
+        .opts;
+
+        for parallel in [false, true] {
+            let expected = simulate(accel.len(), &once, parallel, &accel, |_, _| {}).unwrap();
+            let result = simulate(accel.len(), &repeated, parallel, &accel, |_, _| {}).unwrap();
+
+            assert_eq!(result.final_velocity, expected.final_velocity);
+            assert_eq!(result.final_position, expected.final_position);
+            assert_eq!(result.velocity.to_vec(), expected.velocity.to_vec());
+            assert_eq!(result.velocity.capacity(), accel.len());
+
+            let expected = simulate_times(&times, &once, parallel, &accel, |_, _| {}).unwrap();
+            let result = simulate_times(&times, &repeated, parallel, &accel, |_, _| {}).unwrap();
+
+            assert_eq!(result.final_velocity, expected.final_velocity);
+            assert_eq!(result.final_position, expected.final_position);
+            assert_eq!(result.velocity.to_vec(), expected.velocity.to_vec());
+        }
+    }
+}
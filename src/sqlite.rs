@@ -0,0 +1,119 @@
+//! appending the results of a simulation to a sqlite database
+
+use std::path::Path;
+
+use anyhow::Context;
+
+/// a single row of the results table
+#[derive(Debug, Clone, PartialEq)]
+pub struct ResultRow {
+    /// the hash of the acceleration profile, see [`crate::output::profile_hash`]
+    pub input_hash: String,
+    /// the options used for the simulation
+    pub options: String,
+    /// the final calculated velocity
+    pub final_velocity: f64,
+    /// the final calculated position
+    pub final_position: f64,
+}
+
+/// opens the database at the given path, creating it if needed, and makes
+/// sure that the results table exists
+fn open(path: &Path) -> anyhow::Result<rusqlite::Connection> {
+    let conn = rusqlite::Connection::open(path).context("failed to open sqlite database")?;
+
+    conn.execute(
+        "create table if not exists results (
+            id integer primary key,
+            created text not null default current_timestamp,
+            input_hash text not null,
+            options text not null,
+            final_velocity real not null,
+            final_position real not null
+        )",
+        (),
+    )
+    .context("failed to create sqlite results table")?;
+
+    Ok(conn)
+}
+
+/// appends the given row to the results table of the database at the given
+/// path
+pub fn append_result(path: &Path, row: &ResultRow) -> anyhow::Result<()> {
+    let conn = open(path)?;
+
+    conn.execute(
+        "insert into results (input_hash, options, final_velocity, final_position)
+        values (?1, ?2, ?3, ?4)",
+        (
+            &row.input_hash,
+            &row.options,
+            row.final_velocity,
+            row.final_position,
+        ),
+    )
+    .context("failed to insert sqlite result")?;
+
+    Ok(())
+}
+
+/// reads all rows of the results table of the database at the given path in
+/// the order they were inserted
+pub fn read_results(path: &Path) -> anyhow::Result<Vec<ResultRow>> {
+    let conn = open(path)?;
+    let mut stmt = conn
+        .prepare(
+            "select input_hash, options, final_velocity, final_position
+            from results
+            order by id",
+        )
+        .context("failed to prepare sqlite results query")?;
+
+    let rows = stmt
+        .query_map((), |row| {
+            Ok(ResultRow {
+                input_hash: row.get(0)?,
+                options: row.get(1)?,
+                final_velocity: row.get(2)?,
+                final_position: row.get(3)?,
+            })
+        })
+        .context("failed to query sqlite results")?;
+
+    rows.collect::<Result<Vec<ResultRow>, _>>()
+        .context("failed to read sqlite result")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::output;
+    use crate::summation::{self, InterpolateLookup};
+
+    #[test]
+    fn append_twice() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("results.db");
+
+        let accel = InterpolateLookup::from(vec![1.0, 2.0, 0.5, -1.0]);
+        let vel =
+            summation::cumulative_panels(accel.len(), 10, &accel, summation::trapezoidal).unwrap();
+        let pos = summation::sum_panels(vel.len(), 10, &vel, summation::trapezoidal).unwrap();
+
+        let row = ResultRow {
+            input_hash: output::profile_hash(accel.len(), &accel),
+            options: String::from("step: 10"),
+            final_velocity: vel.last().unwrap(),
+            final_position: pos,
+        };
+
+        append_result(&path, &row).unwrap();
+        append_result(&path, &row).unwrap();
+
+        let rows = read_results(&path).unwrap();
+
+        assert_eq!(rows, vec![row.clone(), row]);
+        assert_eq!(rows[0].final_velocity, 2.5);
+    }
+}
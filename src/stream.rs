@@ -0,0 +1,140 @@
+//! incremental integration of an acceleration profile that arrives in blocks
+
+use crate::summation::{InterpolateLookup, SummationError};
+
+/// integrates an acceleration profile incrementally as blocks of values are
+/// pushed to it
+///
+/// the running velocity and position are the same as integrating the whole
+/// profile at once with the same summation function and steps. the last value
+/// of each block is kept so that the interval between blocks is integrated
+/// when the next block arrives
+///
+/// ```
+/// use train_sim::stream::Integrator;
+/// use train_sim::summation;
+///
+/// let mut integrator = Integrator::new(10, 10, summation::trapezoidal);
+///
+/// integrator.push_block(&[1.0, 1.0]).unwrap();
+/// integrator.push_block(&[1.0]).unwrap();
+///
+/// assert_eq!(integrator.velocity(), 2.0);
+/// ```
+pub struct Integrator {
+    velocity_step: u32,
+    position_step: u32,
+    sum_cb: fn(f64, f64, u32, &InterpolateLookup) -> Result<f64, SummationError>,
+    last_accel: Option<f64>,
+    velocity: f64,
+    position: f64,
+    length: usize,
+}
+
+impl Integrator {
+    /// creates a new integrator starting from rest with the given steps and
+    /// summation function
+    pub fn new(
+        velocity_step: u32,
+        position_step: u32,
+        sum_cb: fn(f64, f64, u32, &InterpolateLookup) -> Result<f64, SummationError>,
+    ) -> Self {
+        Self {
+            velocity_step,
+            position_step,
+            sum_cb,
+            last_accel: None,
+            velocity: 0.0,
+            position: 0.0,
+            length: 0,
+        }
+    }
+
+    /// extends the integration with the given block of acceleration values
+    ///
+    /// each value is one second after the previous value, including the last
+    /// value of the previous block. fails if the summation function fails for
+    /// the steps of the integrator, the values before the failing interval
+    /// stay integrated
+    pub fn push_block(&mut self, block: &[f64]) -> Result<(), SummationError> {
+        for accel in block {
+            if let Some(last) = self.last_accel {
+                let accel_panel = InterpolateLookup::from(vec![last, *accel]);
+                let velocity =
+                    self.velocity + (self.sum_cb)(0.0, 1.0, self.velocity_step, &accel_panel)?;
+
+                let vel_panel = InterpolateLookup::from(vec![self.velocity, velocity]);
+                self.position += (self.sum_cb)(0.0, 1.0, self.position_step, &vel_panel)?;
+                self.velocity = velocity;
+            }
+
+            self.last_accel = Some(*accel);
+            self.length += 1;
+        }
+
+        Ok(())
+    }
+
+    /// the velocity at the last value pushed
+    pub fn velocity(&self) -> f64 {
+        self.velocity
+    }
+
+    /// the position at the last value pushed
+    pub fn position(&self) -> f64 {
+        self.position
+    }
+
+    /// the total amount of values pushed
+    pub fn len(&self) -> usize {
+        self.length
+    }
+
+    /// true if no values have been pushed
+    pub fn is_empty(&self) -> bool {
+        self.length == 0
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use approx::assert_relative_eq;
+
+    use super::*;
+    use crate::summation;
+
+    #[test]
+    fn two_blocks() {
+        let profile = (0..50)
+            .map(|sec| (sec as f64 / 7.0).sin() + 0.1 * sec as f64)
+            .collect::<Vec<f64>>();
+        let accel = InterpolateLookup::from(profile.clone());
+
+        let vel =
+            summation::cumulative_panels(accel.len(), 10, &accel, summation::simpsons).unwrap();
+        let position = summation::sum_panels(accel.len(), 20, &vel, summation::simpsons).unwrap();
+
+        let mut integrator = Integrator::new(10, 20, summation::simpsons);
+        let (first, second) = profile.split_at(23);
+
+        integrator.push_block(first).unwrap();
+        integrator.push_block(second).unwrap();
+
+        assert_eq!(integrator.len(), profile.len());
+        assert_relative_eq!(integrator.velocity(), vel.last().unwrap(), epsilon = 1e-9);
+        assert_relative_eq!(integrator.position(), position, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn zero_step() {
+        let mut integrator = Integrator::new(0, 10, summation::trapezoidal);
+
+        assert_eq!(integrator.push_block(&[1.0]), Ok(()));
+        assert_eq!(
+            integrator.push_block(&[1.0]),
+            Err(SummationError::ZeroIterations)
+        );
+        assert_eq!(integrator.velocity(), 0.0);
+        assert_eq!(integrator.len(), 1);
+    }
+}
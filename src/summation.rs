@@ -1,23 +1,336 @@
 //! functions for calculating summations and utility structs for ease of use
 //! with the summations
 
+use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::ops::{Add, AddAssign, Div, Mul, Sub};
+
 /// defines something that can be called with a single argument and then return
 /// a single value
-pub trait Callable<T> {
-    /// calls the struct with the given value and a value of the same type
-    fn call(&self, given: T) -> T;
+///
+/// the returned value defaults to the same type as the given value
+pub trait Callable<T, R = T> {
+    /// calls the struct with the given value and returns the result
+    fn call(&self, given: T) -> R;
+
+    /// the error tolerance of each interval when the callable is integrated
+    /// with [`adaptive_simpson_panel`]
+    ///
+    /// defaults to [`DEFAULT_ADAPTIVE_TOLERANCE`], see [`ConfiguredCallable`].
+    /// callables that wrap another one return the tolerance of the inner one
+    fn adaptive_tolerance(&self) -> f64 {
+        DEFAULT_ADAPTIVE_TOLERANCE
+    }
+
+    /// the amount of trapezoidal refinements of each interval when the
+    /// callable is integrated with [`romberg_panel`]
+    ///
+    /// defaults to [`DEFAULT_ROMBERG_LEVELS`], see [`ConfiguredCallable`].
+    /// callables that wrap another one return the levels of the inner one
+    fn romberg_levels(&self) -> u32 {
+        DEFAULT_ROMBERG_LEVELS
+    }
+
+    /// integrates the callable from lower to upper using the midpoint
+    /// rectangle rule with the given amount of iterations
+    ///
+    /// this is the same as calling [`mid_riemann`] with the callable
+    ///
+    /// ```
+    /// use train_sim::summation::{Callable, InterpolateLookup};
+    ///
+    /// let lt = InterpolateLookup::from(vec![0.0, 1.0, 2.0, 3.0, 4.0]);
+    ///
+    /// assert_eq!(lt.integrate_rect(0.0, 4.0, 100), Ok(8.0));
+    /// ```
+    fn integrate_rect(&self, lower: f64, upper: f64, iterations: u32) -> Result<R, SummationError>
+    where
+        Self: Callable<f64, R>,
+        R: Sample,
+    {
+        mid_riemann(lower, upper, iterations, self)
+    }
+}
+
+/// same as [`Callable`] but returns `None` instead of panicking when the value
+/// cannot be calculated for the given argument
+///
+/// ```
+/// use train_sim::summation::{InterpolateLookup, TryCallable};
+///
+/// let lt = InterpolateLookup::from(vec![0.0, 1.0, 2.0]);
+///
+/// assert_eq!(lt.try_call(1.5), Some(1.5));
+/// assert_eq!(lt.try_call(2.5), None);
+/// ```
+pub trait TryCallable<T, R = T> {
+    /// calls the struct with the given value and returns the result if there
+    /// is one
+    fn try_call(&self, given: T) -> Option<R>;
+}
+
+/// the reasons that a summation could not be calculated
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SummationError {
+    /// the summation was given zero iterations
+    ZeroIterations,
+    /// a value needed by the summation is outside of the callable
+    OutOfRange,
+    /// the error tolerance is not a finite value greater than zero
+    InvalidTolerance,
+}
+
+impl Display for SummationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            SummationError::ZeroIterations => {
+                write!(f, "summation requires at least one iteration")
+            }
+            SummationError::OutOfRange => write!(f, "summation value is outside of the callable"),
+            SummationError::InvalidTolerance => {
+                write!(
+                    f,
+                    "summation tolerance must be a finite value greater than zero"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for SummationError {}
+
+/// the reasons that a lookup table could not be used
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LookupError {
+    /// the lookup table has no values
+    Empty,
+    /// the lookup table was created from pairs instead of evenly spaced values
+    Pairs,
+    /// a lookup table of pairs was combined with one of evenly spaced values
+    Mixed,
+    /// the evenly spaced lookup tables have different spacing
+    Spacing,
+    /// the x values of the lookup table are not strictly increasing
+    NotIncreasing,
+}
+
+impl Display for LookupError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
+        match self {
+            LookupError::Empty => write!(f, "lookup table has no values"),
+            LookupError::Pairs => write!(f, "lookup table values are not evenly spaced"),
+            LookupError::Mixed => write!(
+                f,
+                "cannot combine a lookup table of pairs with one of evenly spaced values"
+            ),
+            LookupError::Spacing => write!(f, "lookup table spacing must be the same"),
+            LookupError::NotIncreasing => {
+                write!(f, "lookup table x values must be strictly increasing")
+            }
+        }
+    }
+}
+
+impl std::error::Error for LookupError {}
+
+/// checks that the given amount of iterations can be used with the summation
+/// functions
+///
+/// ```
+/// use train_sim::summation::{check_iterations, SummationError};
+///
+/// assert_eq!(check_iterations(10), Ok(()));
+/// assert_eq!(check_iterations(0), Err(SummationError::ZeroIterations));
+/// ```
+pub fn check_iterations(iterations: u32) -> Result<(), SummationError> {
+    if iterations == 0 {
+        Err(SummationError::ZeroIterations)
+    } else {
+        Ok(())
+    }
+}
+
+/// checks that the given error tolerance can be used with
+/// [`adaptive_simpson`]
+///
+/// ```
+/// use train_sim::summation::{check_tolerance, SummationError};
+///
+/// assert_eq!(check_tolerance(1e-6), Ok(()));
+/// assert_eq!(check_tolerance(0.0), Err(SummationError::InvalidTolerance));
+/// assert_eq!(check_tolerance(f64::NAN), Err(SummationError::InvalidTolerance));
+/// ```
+pub fn check_tolerance(tol: f64) -> Result<(), SummationError> {
+    if tol > 0.0 && tol.is_finite() {
+        Ok(())
+    } else {
+        Err(SummationError::InvalidTolerance)
+    }
 }
 
-impl<F, T> Callable<T> for F
+impl<F, T, R> Callable<T, R> for F
 where
-    F: Fn(T) -> T + 'static,
+    F: Fn(T) -> R + 'static,
     T: Sized,
 {
-    fn call(&self, given: T) -> T {
+    fn call(&self, given: T) -> R {
         self(given)
     }
 }
 
+/// defines a value that can be accumulated and scaled by the summation
+/// functions
+///
+/// implemented for [`f64`], [`Single`] for single precision and [`Channels`]
+/// which allows for multiple values to be integrated at the same time
+pub trait Sample:
+    Copy
+    + Default
+    + Add<Output = Self>
+    + AddAssign
+    + Sub<Output = Self>
+    + Mul<f64, Output = Self>
+    + Div<f64, Output = Self>
+{
+}
+
+impl<T> Sample for T where
+    T: Copy
+        + Default
+        + Add<Output = T>
+        + AddAssign
+        + Sub<Output = T>
+        + Mul<f64, Output = T>
+        + Div<f64, Output = T>
+{
+}
+
+/// a fixed amount of independent values that are operated on together
+///
+/// each operation is applied to every channel individually
+///
+/// ```
+/// use train_sim::summation::{Callable, Channels, InterpolateLookup};
+///
+/// let lt = InterpolateLookup::from(vec![Channels([0.0, 0.0]), Channels([1.0, 2.0])]);
+///
+/// assert_eq!(lt.call(0.5), Channels([0.5, 1.0]));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Channels<const N: usize>(pub [f64; N]);
+
+impl<const N: usize> Default for Channels<N> {
+    fn default() -> Self {
+        Self([0.0; N])
+    }
+}
+
+impl<const N: usize> Add for Channels<N> {
+    type Output = Self;
+
+    fn add(mut self, rhs: Self) -> Self {
+        self += rhs;
+        self
+    }
+}
+
+impl<const N: usize> AddAssign for Channels<N> {
+    fn add_assign(&mut self, rhs: Self) {
+        for (lhs, rhs) in self.0.iter_mut().zip(rhs.0) {
+            *lhs += rhs;
+        }
+    }
+}
+
+impl<const N: usize> Sub for Channels<N> {
+    type Output = Self;
+
+    fn sub(mut self, rhs: Self) -> Self {
+        for (lhs, rhs) in self.0.iter_mut().zip(rhs.0) {
+            *lhs -= rhs;
+        }
+
+        self
+    }
+}
+
+impl<const N: usize> Mul<f64> for Channels<N> {
+    type Output = Self;
+
+    fn mul(mut self, rhs: f64) -> Self {
+        for lhs in self.0.iter_mut() {
+            *lhs *= rhs;
+        }
+
+        self
+    }
+}
+
+impl<const N: usize> Div<f64> for Channels<N> {
+    type Output = Self;
+
+    fn div(mut self, rhs: f64) -> Self {
+        for lhs in self.0.iter_mut() {
+            *lhs /= rhs;
+        }
+
+        self
+    }
+}
+
+/// a single precision value that is operated on as single precision
+///
+/// the step sizes and weights of the summation functions are still
+/// calculated as f64 but are converted to f32 before being applied so that
+/// all sums are accumulated with single precision
+///
+/// ```
+/// use train_sim::summation::{Callable, InterpolateLookup, Single};
+///
+/// let lt = InterpolateLookup::from(vec![Single(0.0), Single(1.0)]);
+///
+/// assert_eq!(lt.call(0.5), Single(0.5));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd)]
+pub struct Single(pub f32);
+
+impl Add for Single {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self(self.0 + rhs.0)
+    }
+}
+
+impl AddAssign for Single {
+    fn add_assign(&mut self, rhs: Self) {
+        self.0 += rhs.0;
+    }
+}
+
+impl Sub for Single {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        Self(self.0 - rhs.0)
+    }
+}
+
+impl Mul<f64> for Single {
+    type Output = Self;
+
+    fn mul(self, rhs: f64) -> Self {
+        Self(self.0 * rhs as f32)
+    }
+}
+
+impl Div<f64> for Single {
+    type Output = Self;
+
+    fn div(self, rhs: f64) -> Self {
+        Self(self.0 / rhs as f32)
+    }
+}
+
 /// provides interpolated lookups between values stored
 ///
 /// each index of the table is considered the x value and each value stored at
@@ -33,7 +346,7 @@ where
 ///
 /// you can manually fill the lookup table from an empty vector
 /// ```
-/// use Callable;
+/// use train_sim::summation::{Callable, InterpolateLookup};
 ///
 /// let mut lt = InterpolateLookup::from(Vec::new());
 /// // list of y values available
@@ -47,224 +360,3833 @@ where
 ///
 /// or fill it with a vector of filled values
 /// ```
+/// # use train_sim::summation::{Callable, InterpolateLookup};
 /// let mut lt = InterpolateLookup::from(vec![0.0, 1.5, 3.0]);
 ///
 /// lt.call(1.5);
 /// ```
+///
+/// the stored values default to [`f64`] but can be anything that is a
+/// [`Sample`]
+///
+/// the values are spaced 1 apart unless created with
+/// [`InterpolateLookup::with_spacing`] or given explicit x values with
+/// [`InterpolateLookup::from_pairs`]
 #[derive(Debug, Clone)]
-pub struct InterpolateLookup {
-    lookup: Vec<f64>,
+pub struct InterpolateLookup<V = f64> {
+    lookup: Vec<V>,
+    dx: f64,
+    xs: Option<Vec<f64>>,
+    extrapolation: Extrapolation,
 }
 
-impl InterpolateLookup {
+/// how an [`InterpolateLookup`] calculates values for x values outside of the
+/// table
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Extrapolation {
+    /// returns the value of the nearest end of the table
+    Clamp,
+    /// extends the line through the two values at the nearest end of the
+    /// table
+    Linear,
+    /// returns zero
+    Zero,
+    /// panics when called and returns `None` when using
+    /// [`TryCallable::try_call`]
+    #[default]
+    Panic,
+}
+
+impl<V> InterpolateLookup<V> {
+    /// creates a lookup table with the given values spaced dx apart
+    ///
+    /// panics if dx is not a positive number
+    ///
+    /// ```
+    /// use train_sim::summation::{Callable, InterpolateLookup};
+    ///
+    /// let lt = InterpolateLookup::with_spacing(vec![0.0, 1.0, 4.0], 0.5);
+    ///
+    /// assert_eq!(lt.call(1.0), 4.0);
+    /// assert_eq!(lt.call(0.75), 2.5);
+    /// ```
+    pub fn with_spacing(given: Vec<V>, dx: f64) -> Self {
+        assert!(
+            dx.is_finite() && dx > 0.0,
+            "lookup spacing must be a positive number"
+        );
+
+        Self {
+            lookup: given,
+            dx,
+            xs: None,
+            extrapolation: Extrapolation::default(),
+        }
+    }
+
+    /// creates a lookup table from explicit (x, y) pairs for irregularly
+    /// sampled data
+    ///
+    /// the bracketing values of an x are found with a binary search. the
+    /// index based functions like [`InterpolateLookup::get_index`] still use
+    /// the position of the pair. panics if the x values are not strictly
+    /// increasing
+    ///
+    /// ```
+    /// use train_sim::summation::{Callable, InterpolateLookup};
+    ///
+    /// let lt = InterpolateLookup::from_pairs(vec![(0.0, 0.0), (3.0, 9.0), (10.0, 100.0)]);
+    ///
+    /// assert_eq!(lt.call(3.0), 9.0);
+    /// assert_eq!(lt.call(1.0), 3.0);
+    /// ```
+    pub fn from_pairs(given: Vec<(f64, V)>) -> Self {
+        let (xs, lookup): (Vec<f64>, Vec<V>) = given.into_iter().unzip();
+
+        assert!(
+            xs.windows(2).all(|pair| pair[0] < pair[1]),
+            "lookup x values must be strictly increasing"
+        );
+
+        Self {
+            lookup,
+            dx: 1.0,
+            xs: Some(xs),
+            extrapolation: Extrapolation::default(),
+        }
+    }
+
+    /// returns the explicit x values of the table if it was created from
+    /// pairs
+    pub fn xs(&self) -> Option<&[f64]> {
+        self.xs.as_deref()
+    }
+
+    /// sets how values are calculated for x values outside of the table
+    pub fn set_extrapolation(&mut self, given: Extrapolation) {
+        self.extrapolation = given;
+    }
+
+    /// returns how values are calculated for x values outside of the table
+    pub fn extrapolation(&self) -> Extrapolation {
+        self.extrapolation
+    }
+
+    /// returns the x value of the stored value at the given index
+    fn x_at(&self, index: usize) -> f64 {
+        match &self.xs {
+            Some(xs) => xs[index],
+            None => index as f64 * self.dx,
+        }
+    }
+
+    /// returns the distance between each of the stored values
+    pub fn dx(&self) -> f64 {
+        self.dx
+    }
+}
+
+impl<V: Sample> InterpolateLookup<V> {
     /// attempt to retrieve a value from the lookup table with the given index
     ///
     /// the [`f64`] will be cast to a [`usize`] and then attempt to retrieve a
     /// copied value
-    pub fn try_get_index(&self, given: f64) -> Option<f64> {
+    pub fn try_get_index(&self, given: f64) -> Option<V> {
         self.lookup.get(given as usize).copied()
     }
 
     /// retrieve a value from the lookup table with the given index
     ///
     /// panics if the desired index is not found in the lookup table
-    pub fn get_index(&self, given: f64) -> f64 {
+    pub fn get_index(&self, given: f64) -> V {
         self.try_get_index(given)
             .expect("failed to retrieve given index")
     }
 
+    /// returns the last value of the lookup table if there is one
+    pub fn last(&self) -> Option<V> {
+        self.lookup.last().copied()
+    }
+
     /// returns the current length of the lookup table
     pub fn len(&self) -> usize {
         self.lookup.len()
     }
 
+    /// returns true if the lookup table contains no values
+    pub fn is_empty(&self) -> bool {
+        self.lookup.is_empty()
+    }
+
     /// adds a new value to the end of the lookup table
-    pub fn push(&mut self, given: f64) {
+    ///
+    /// panics if the table was created from pairs as there is no x value for
+    /// the new value
+    pub fn push(&mut self, given: V) {
+        assert!(
+            self.xs.is_none(),
+            "cannot push a value without an x onto a table of pairs"
+        );
+
         self.lookup.push(given);
     }
-}
 
-impl From<Vec<f64>> for InterpolateLookup {
-    fn from(given: Vec<f64>) -> Self {
-        Self { lookup: given }
+    /// removes all of the stored values while keeping the allocated capacity
+    ///
+    /// the x values of a table created from pairs are removed as well
+    pub fn clear(&mut self) {
+        self.lookup.clear();
+
+        if let Some(xs) = &mut self.xs {
+            xs.clear();
+        }
     }
-}
 
-impl Callable<f64> for InterpolateLookup {
-    fn call(&self, x: f64) -> f64 {
-        let x0 = x.floor();
+    /// returns the amount of values the lookup table can hold without
+    /// reallocating
+    pub fn capacity(&self) -> usize {
+        self.lookup.capacity()
+    }
 
-        // check to see if the given x is a whole number, if so then dont
-        // interpolate and instead just retrieve the value at that index
-        // if possible
-        if x0 == x {
-            return self.get_index(x);
-        }
+    /// adds the values of the other table to the end of this one
+    ///
+    /// the values of a table spaced dx apart continue one dx after the last
+    /// value of this table. the x values of a table of pairs are offset by
+    /// the last x value of this table so they are treated as being relative
+    /// to the end of it, a first pair that lands on the last x value of this
+    /// table is skipped. fails without changing this table if one table is
+    /// made of pairs and the other is not, if the spacing of the tables
+    /// differ or if the offset x values are not strictly increasing
+    ///
+    /// ```
+    /// use train_sim::summation::{Callable, InterpolateLookup};
+    ///
+    /// let mut accel = InterpolateLookup::from(vec![1.0, 1.0]);
+    /// accel.append(&InterpolateLookup::from(vec![-1.0, -1.0])).unwrap();
+    ///
+    /// assert_eq!(accel.len(), 4);
+    /// assert_eq!(accel.call(1.5), 0.0);
+    /// ```
+    pub fn append(&mut self, other: &Self) -> Result<(), LookupError> {
+        match (&mut self.xs, &other.xs) {
+            (Some(xs), Some(other_xs)) => {
+                let offset = xs.last().copied();
+                let skip = usize::from(offset.is_some() && other_xs.first() == Some(&0.0));
+                let offset = offset.unwrap_or_default();
+                let appended = other_xs[skip..].iter().map(|x| x + offset);
 
-        let x1 = x0 + 1.0;
+                let mut previous = xs.last().copied();
 
-        let y0 = self.get_index(x0);
-        let y1 = self.get_index(x1);
+                for x in appended.clone() {
+                    if previous.is_some_and(|previous| previous >= x) {
+                        return Err(LookupError::NotIncreasing);
+                    }
 
-        // if x1 is always 1 greater than x0 then it can be removed and just be
-        // 1, otherwise this: y0 + (x - x0) * ((y1 - y0) / (x1 - x0))
-        y0 + (x - x0) * (y1 - y0)
-    }
-}
+                    previous = Some(x);
+                }
 
-/// performs a left riemann summation with the given callable
-pub fn left_riemann<T>(lower: f64, upper: f64, iterations: u32, cb: &T) -> f64
-where
-    T: Callable<f64> + ?Sized,
-{
-    assert_ne!(iterations, 0);
+                xs.extend(appended);
+                self.lookup.extend(other.lookup[skip..].iter().copied());
+            }
+            (None, None) => {
+                if self.dx != other.dx {
+                    return Err(LookupError::Spacing);
+                }
 
-    let step = (upper - lower) / (iterations as f64);
-    let mut sum = 0.0;
+                self.lookup.extend(other.lookup.iter().copied());
+            }
+            _ => return Err(LookupError::Mixed),
+        }
 
-    for iter in 0..iterations {
-        let i = iter as f64;
-        let x = lower + i * step;
+        Ok(())
+    }
 
-        sum += cb.call(x);
+    /// consumes the lookup table and returns the stored values
+    pub fn into_inner(self) -> Vec<V> {
+        self.lookup
+    }
+
+    /// returns an iterator over copies of the stored values
+    ///
+    /// ```
+    /// use train_sim::summation::InterpolateLookup;
+    ///
+    /// let lt = InterpolateLookup::from(vec![1.0, 2.0, 3.0]);
+    ///
+    /// assert_eq!(lt.iter().sum::<f64>(), 6.0);
+    /// ```
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = V> + '_ {
+        self.lookup.iter().copied()
     }
 
-    sum * step
+    /// returns a copy of the stored values
+    pub fn to_vec(&self) -> Vec<V> {
+        self.lookup.clone()
+    }
 }
 
-/// performs a midpoint riemann summation with the given callable
-pub fn mid_riemann<T>(lower: f64, upper: f64, iterations: u32, cb: &T) -> f64
-where
-    T: Callable<f64> + ?Sized,
-{
-    assert_ne!(iterations, 0);
+impl InterpolateLookup {
+    /// returns the minimum value stored in the lookup table
+    pub fn min(&self) -> Option<f64> {
+        self.lookup.iter().copied().reduce(f64::min)
+    }
 
-    let step = (upper - lower) / (iterations as f64);
-    let half = step / 2.0;
-    let mut sum = 0.0;
+    /// returns the maximum value stored in the lookup table
+    pub fn max(&self) -> Option<f64> {
+        self.lookup.iter().copied().reduce(f64::max)
+    }
 
-    for iter in 0..iterations {
-        let i = iter as f64;
-        let x = (lower + i * step) + half;
+    /// returns the mean of the values stored in the lookup table
+    pub fn mean(&self) -> Option<f64> {
+        if self.lookup.is_empty() {
+            None
+        } else {
+            Some(self.lookup.iter().sum::<f64>() / self.lookup.len() as f64)
+        }
+    }
 
-        sum += cb.call(x);
+    /// returns the population standard deviation of the values stored in the
+    /// lookup table
+    pub fn std_dev(&self) -> Option<f64> {
+        let mean = self.mean()?;
+        let variance = self
+            .lookup
+            .iter()
+            .map(|value| (value - mean) * (value - mean))
+            .sum::<f64>()
+            / self.lookup.len() as f64;
+
+        Some(variance.sqrt())
     }
 
-    sum * step
-}
+    /// returns the x positions where the stored values change sign
+    ///
+    /// the position between two adjacent values of opposite sign is linearly
+    /// interpolated. when the sign changes over a run of zeros the position of
+    /// the first zero is used. touching zero without changing sign is not a
+    /// crossing
+    pub fn zero_crossings(&self) -> Vec<f64> {
+        let mut rtn = Vec::new();
+        let mut previous: Option<(usize, f64)> = None;
 
-/// performs a right riemann summation with the given callable
-pub fn right_riemann<T>(lower: f64, upper: f64, iterations: u32, cb: &T) -> f64
-where
-    T: Callable<f64> + ?Sized,
-{
-    assert_ne!(iterations, 0);
+        for (index, value) in self.lookup.iter().copied().enumerate() {
+            if value == 0.0 {
+                continue;
+            }
 
-    let step = (upper - lower) / (iterations as f64);
-    let mut sum = 0.0;
+            if let Some((prev_index, prev_value)) = previous {
+                if prev_value.signum() != value.signum() {
+                    if prev_index + 1 == index {
+                        let x0 = self.x_at(prev_index);
+                        let x1 = self.x_at(index);
 
-    for iter in 0..iterations {
-        let i = (iter + 1) as f64;
-        let x = lower + i * step;
+                        rtn.push(x0 + (x1 - x0) * prev_value / (prev_value - value));
+                    } else {
+                        rtn.push(self.x_at(prev_index + 1));
+                    }
+                }
+            }
 
-        sum += cb.call(x);
+            previous = Some((index, value));
+        }
+
+        rtn
     }
 
-    sum * step
-}
+    /// returns the x position where the interpolated values reach the given
+    /// value or `None` if it is outside of the stored values
+    ///
+    /// the bracketing values are found with a binary search so the result is
+    /// only meaningful when the stored values are monotonic, either
+    /// increasing or decreasing
+    ///
+    /// ```
+    /// use train_sim::summation::InterpolateLookup;
+    ///
+    /// let velocity = InterpolateLookup::from(vec![0.0, 10.0, 20.0, 40.0]);
+    ///
+    /// assert_eq!(velocity.find_x(30.0), Some(2.5));
+    /// assert_eq!(velocity.find_x(50.0), None);
+    /// ```
+    pub fn find_x(&self, y: f64) -> Option<f64> {
+        let first = *self.lookup.first()?;
+        let last = *self.lookup.last()?;
+        let increasing = first <= last;
 
-/// performs a trapezoidal summation with the given callable
-pub fn trapezoidal<T>(lower: f64, upper: f64, iterations: u32, cb: &T) -> f64
-where
-    T: Callable<f64> + ?Sized,
-{
-    assert_ne!(iterations, 0);
+        if !(first.min(last)..=first.max(last)).contains(&y) {
+            return None;
+        }
 
-    let step = (upper - lower) / (iterations as f64);
-    let mut sum = (cb.call(upper) + cb.call(lower)) / 2.0;
+        let index = if increasing {
+            self.lookup.partition_point(|value| *value < y)
+        } else {
+            self.lookup.partition_point(|value| *value > y)
+        };
 
-    for iter in 1..iterations {
-        let i = iter as f64;
-        let x = lower + i * step;
+        if self.lookup[index] == y || index == 0 {
+            return Some(self.x_at(index));
+        }
 
-        sum += cb.call(x);
+        let y0 = self.lookup[index - 1];
+        let y1 = self.lookup[index];
+        let x0 = self.x_at(index - 1);
+        let x1 = self.x_at(index);
+
+        Some(x0 + (y - y0) / (y1 - y0) * (x1 - x0))
     }
 
-    sum * step
-}
+    /// returns a table of the finite differences of the stored values divided
+    /// by the distance between their x values
+    ///
+    /// central differences are used for the interior values and one sided
+    /// differences are used at the ends. the new table has the same x values
+    /// as this one. tables with less than 2 values will return a table of
+    /// zeros
+    ///
+    /// ```
+    /// use train_sim::summation::InterpolateLookup;
+    ///
+    /// let lt = InterpolateLookup::from_pairs(vec![(0.0, 0.0), (1.0, 2.0), (4.0, 8.0)]);
+    ///
+    /// assert_eq!(lt.derivative().to_vec(), vec![2.0, 2.0, 2.0]);
+    /// ```
+    pub fn derivative(&self) -> InterpolateLookup {
+        let len = self.lookup.len();
+        let slope =
+            |a: usize, b: usize| (self.lookup[b] - self.lookup[a]) / (self.x_at(b) - self.x_at(a));
 
-/// performs simpsons summation with the given callable
-pub fn simpsons<T>(lower: f64, upper: f64, iterations: u32, cb: &T) -> f64
-where
-    T: Callable<f64> + ?Sized,
-{
-    assert_ne!(iterations, 0);
+        let rtn = if len < 2 {
+            vec![0.0; len]
+        } else {
+            let mut rtn = Vec::with_capacity(len);
+            rtn.push(slope(0, 1));
 
-    let step = (upper - lower) / (iterations as f64);
-    let mut sum = 0.0;
+            for index in 1..(len - 1) {
+                rtn.push(slope(index - 1, index + 1));
+            }
 
-    for iter in 0..=iterations {
-        let i = iter as f64;
-        let x = lower + i * step;
-        let res = cb.call(x);
+            rtn.push(slope(len - 2, len - 1));
+            rtn
+        };
 
-        if iter == 0 || iter == iterations {
-            sum += res;
-        } else if iter % 2 == 1 {
-            sum += 4.0 * res;
+        match &self.xs {
+            Some(xs) => InterpolateLookup::from_pairs(xs.iter().copied().zip(rtn).collect()),
+            None => InterpolateLookup::with_spacing(rtn, self.dx),
+        }
+    }
+
+    /// returns a table where each value is the average of the given amount of
+    /// values centered on it
+    ///
+    /// the window shrinks at the ends of the table to the values that are
+    /// available. an even window uses one more value before than after. the
+    /// new table has the same x values as this one
+    ///
+    /// panics if the window is zero
+    ///
+    /// ```
+    /// use train_sim::summation::InterpolateLookup;
+    ///
+    /// let lt = InterpolateLookup::from(vec![0.0, 3.0, 0.0, 6.0]);
+    ///
+    /// assert_eq!(lt.smoothed(3).to_vec(), vec![1.5, 1.0, 3.0, 3.0]);
+    /// assert_eq!(lt.smoothed(2).to_vec(), vec![0.0, 1.5, 1.5, 3.0]);
+    /// ```
+    pub fn smoothed(&self, window: usize) -> InterpolateLookup {
+        assert!(window > 0, "smoothing window must be at least 1");
+
+        // the running sums below would not return the exact values
+        if window == 1 {
+            return self.clone();
+        }
+
+        let before = window / 2;
+        let after = (window - 1) / 2;
+        let len = self.lookup.len();
+
+        let mut prefix = Vec::with_capacity(len + 1);
+        prefix.push(0.0);
+
+        for value in &self.lookup {
+            prefix.push(prefix[prefix.len() - 1] + value);
+        }
+
+        let rtn = (0..len)
+            .map(|index| {
+                let start = index.saturating_sub(before);
+                let end = (index + after + 1).min(len);
+
+                (prefix[end] - prefix[start]) / (end - start) as f64
+            })
+            .collect();
+
+        InterpolateLookup {
+            lookup: rtn,
+            dx: self.dx,
+            xs: self.xs.clone(),
+            extrapolation: self.extrapolation,
+        }
+    }
+
+    /// returns a table of pairs sampled from the interpolated curve at the
+    /// given amount of evenly spaced x values from the first to the last x
+    /// value of this table
+    ///
+    /// the first and last values are kept exactly. a length of 1 only keeps
+    /// the first value and an empty table stays empty
+    ///
+    /// panics if the new length is zero
+    ///
+    /// ```
+    /// use train_sim::summation::InterpolateLookup;
+    ///
+    /// let lt = InterpolateLookup::from(vec![0.0, 2.0, 4.0, 6.0, 8.0]);
+    /// let resampled = lt.resample(3);
+    ///
+    /// assert_eq!(resampled.xs(), Some([0.0, 2.0, 4.0].as_slice()));
+    /// assert_eq!(resampled.to_vec(), vec![0.0, 4.0, 8.0]);
+    /// ```
+    pub fn resample(&self, new_len: usize) -> InterpolateLookup {
+        assert!(new_len > 0, "resample length must be at least 1");
+
+        let (Some(first), Some(last)) = (self.lookup.first(), self.lookup.last()) else {
+            return InterpolateLookup::from_pairs(Vec::new());
+        };
+
+        let start = self.x_at(0);
+        let end = self.x_at(self.lookup.len() - 1);
+
+        let mut pairs = Vec::with_capacity(new_len);
+        pairs.push((start, *first));
+
+        for index in 1..new_len.saturating_sub(1) {
+            let x = start + (end - start) * index as f64 / (new_len - 1) as f64;
+
+            pairs.push((x, self.call(x)));
+        }
+
+        if new_len > 1 {
+            pairs.push((end, *last));
+        }
+
+        let mut rtn = InterpolateLookup::from_pairs(pairs);
+        rtn.set_extrapolation(self.extrapolation);
+        rtn
+    }
+
+    /// compares the values of two lookup tables
+    ///
+    /// panics if the lookup tables are not the same length
+    pub fn diff_stats(&self, other: &Self) -> DiffStats {
+        assert_eq!(
+            self.lookup.len(),
+            other.lookup.len(),
+            "lookup tables must be the same length"
+        );
+
+        let mut rtn = DiffStats::default();
+        let mut sum_sq = 0.0;
+
+        for (index, (a, b)) in self.lookup.iter().zip(&other.lookup).enumerate() {
+            let diff = (a - b).abs();
+
+            if diff > rtn.max {
+                rtn.max = diff;
+                rtn.max_index = index;
+            }
+
+            sum_sq += diff * diff;
+        }
+
+        if !self.lookup.is_empty() {
+            rtn.rms = (sum_sq / self.lookup.len() as f64).sqrt();
+        }
+
+        rtn
+    }
+}
+
+/// the differences between two lookup tables
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DiffStats {
+    /// the maximum absolute difference between two values
+    pub max: f64,
+    /// the root mean square of the differences
+    pub rms: f64,
+    /// the index of the maximum absolute difference
+    pub max_index: usize,
+}
+
+impl<V> From<Vec<V>> for InterpolateLookup<V> {
+    fn from(given: Vec<V>) -> Self {
+        Self {
+            lookup: given,
+            dx: 1.0,
+            xs: None,
+            extrapolation: Extrapolation::default(),
+        }
+    }
+}
+
+impl<V: Clone> From<&[V]> for InterpolateLookup<V> {
+    fn from(given: &[V]) -> Self {
+        Self::from(given.to_vec())
+    }
+}
+
+impl<V> FromIterator<V> for InterpolateLookup<V> {
+    fn from_iter<I: IntoIterator<Item = V>>(iter: I) -> Self {
+        Self::from(iter.into_iter().collect::<Vec<V>>())
+    }
+}
+
+impl<V: Sample> InterpolateLookup<V> {
+    /// interpolates between the explicit x values of a table of pairs
+    fn try_call_pairs(&self, xs: &[f64], x: f64) -> Option<V> {
+        // the first index with an x value that is not less than the given one
+        let index = xs.partition_point(|value| *value < x);
+
+        if xs.get(index) == Some(&x) {
+            return self.lookup.get(index).copied();
+        }
+
+        if index == 0 || index == xs.len() {
+            return None;
+        }
+
+        let x0 = xs[index - 1];
+        let x1 = xs[index];
+        let y0 = self.lookup[index - 1];
+        let y1 = self.lookup[index];
+
+        Some(y0 + (y1 - y0) * ((x - x0) / (x1 - x0)))
+    }
+}
+
+impl<V: Sample> InterpolateLookup<V> {
+    /// interpolates between the evenly spaced values of the table
+    fn try_call_uniform(&self, x: f64) -> Option<V> {
+        // convert the x value into the index space of the table
+        let scaled = x / self.dx;
+        let index = scaled.floor();
+
+        if index < 0.0 {
+            return None;
+        }
+
+        // check to see if the given x lands on an index, if so then dont
+        // interpolate and instead just retrieve the value at that index
+        // if possible
+        if index == scaled {
+            return self.try_get_index(index);
+        }
+
+        let x0 = index * self.dx;
+        let x1 = (index + 1.0) * self.dx;
+
+        let y0 = self.try_get_index(index)?;
+        let y1 = self.try_get_index(index + 1.0)?;
+
+        Some(y0 + (y1 - y0) * ((x - x0) / (x1 - x0)))
+    }
+
+    /// calculates the value for an x outside of the table with the selected
+    /// extrapolation
+    fn extrapolate(&self, x: f64) -> Option<V> {
+        let len = self.lookup.len();
+
+        if len == 0 {
+            return None;
+        }
+
+        // the indices of the two values at the nearest end of the table
+        let (near, far) = if x < self.x_at(0) {
+            (0, 1.min(len - 1))
+        } else {
+            (len - 1, len.saturating_sub(2))
+        };
+
+        match self.extrapolation {
+            Extrapolation::Clamp => Some(self.lookup[near]),
+            Extrapolation::Linear => {
+                if near == far {
+                    return Some(self.lookup[near]);
+                }
+
+                let x0 = self.x_at(near);
+                let x1 = self.x_at(far);
+                let y0 = self.lookup[near];
+                let y1 = self.lookup[far];
+
+                Some(y0 + (y1 - y0) * ((x - x0) / (x1 - x0)))
+            }
+            Extrapolation::Zero => Some(V::default()),
+            Extrapolation::Panic => None,
+        }
+    }
+}
+
+impl<V: Sample> TryCallable<f64, V> for InterpolateLookup<V> {
+    /// values outside of the table use the selected [`Extrapolation`]
+    fn try_call(&self, x: f64) -> Option<V> {
+        let found = match &self.xs {
+            Some(xs) => self.try_call_pairs(xs, x),
+            None => self.try_call_uniform(x),
+        };
+
+        found.or_else(|| self.extrapolate(x))
+    }
+}
+
+impl<V: Sample> Callable<f64, V> for InterpolateLookup<V> {
+    /// panics if the x value is outside of the table and the extrapolation is
+    /// [`Extrapolation::Panic`], see [`TryCallable::try_call`] for a non
+    /// panicking version
+    fn call(&self, x: f64) -> V {
+        self.try_call(x)
+            .expect("x value is outside of the lookup table")
+    }
+}
+
+/// the available ways of calculating values between the entries of a lookup
+/// table
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Interpolation {
+    /// straight line between neighboring entries, see [`InterpolateLookup`]
+    Linear,
+    /// holds the previous entry until the next one, see [`StepLookup`]
+    Step,
+    /// natural cubic spline through the entries, see [`CubicSplineLookup`]
+    Cubic,
+}
+
+impl Interpolation {
+    /// all available interpolation methods
+    pub const ALL: [Interpolation; 3] = [
+        Interpolation::Linear,
+        Interpolation::Step,
+        Interpolation::Cubic,
+    ];
+
+    /// the short name of the interpolation method
+    pub fn name(&self) -> &'static str {
+        match self {
+            Interpolation::Linear => "linear",
+            Interpolation::Step => "step",
+            Interpolation::Cubic => "cubic",
+        }
+    }
+}
+
+/// wraps a lookup table and returns the value of the entry at or before the
+/// given x instead of interpolating between entries
+pub struct StepLookup<'a, V = f64> {
+    inner: &'a InterpolateLookup<V>,
+}
+
+impl<'a, V> StepLookup<'a, V> {
+    /// creates a new step lookup from the given lookup table
+    pub fn new(inner: &'a InterpolateLookup<V>) -> Self {
+        Self { inner }
+    }
+}
+
+impl<V: Sample> Callable<f64, V> for StepLookup<'_, V> {
+    fn call(&self, x: f64) -> V {
+        if let Some(xs) = &self.inner.xs {
+            // the last index with an x value that is not greater than the
+            // given one
+            let index = xs.partition_point(|value| *value <= x);
+
+            assert!(index != 0, "x value is outside of the lookup table");
+
+            return self.inner.get_index((index - 1) as f64);
+        }
+
+        self.inner.get_index((x / self.inner.dx).floor())
+    }
+
+    fn adaptive_tolerance(&self) -> f64 {
+        self.inner.adaptive_tolerance()
+    }
+
+    fn romberg_levels(&self) -> u32 {
+        self.inner.romberg_levels()
+    }
+}
+
+/// a natural cubic spline through the values of a lookup table
+///
+/// the coefficients of each segment are calculated once when created so each
+/// call only evaluates a single cubic. the second derivative is zero at both
+/// ends of the table so only straight lines are reproduced exactly, other
+/// curves will match at the stored values with smooth segments in between
+///
+/// ```
+/// use train_sim::summation::{Callable, CubicSplineLookup, InterpolateLookup};
+///
+/// let lt = InterpolateLookup::from(vec![0.0, 1.0, 1.0, 0.0]);
+/// let spline = CubicSplineLookup::new(&lt);
+///
+/// assert_eq!(spline.call(1.0), 1.0);
+/// // curves above the flat line that linear interpolation would give
+/// assert!(spline.call(1.5) > 1.0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct CubicSplineLookup {
+    xs: Vec<f64>,
+    /// the coefficients of `y + b * t + c * t^2 + d * t^3` for each segment
+    /// where `t` is the distance from the start of the segment
+    coefficients: Vec<[f64; 4]>,
+}
+
+impl CubicSplineLookup {
+    /// calculates the spline through the values of the given lookup table
+    /// using the x values of the table
+    ///
+    /// panics if the table is empty
+    pub fn new(given: &InterpolateLookup) -> Self {
+        let len = given.lookup.len();
+
+        assert!(len != 0, "cubic spline requires a non empty table");
+
+        let xs = (0..len)
+            .map(|index| given.x_at(index))
+            .collect::<Vec<f64>>();
+        let ys = &given.lookup;
+
+        if len == 1 {
+            return Self {
+                xs,
+                coefficients: vec![[ys[0], 0.0, 0.0, 0.0]],
+            };
+        }
+
+        let h = xs
+            .windows(2)
+            .map(|pair| pair[1] - pair[0])
+            .collect::<Vec<f64>>();
+
+        // solve the tridiagonal system for the second derivatives of the
+        // interior values with the thomas algorithm, the ends are zero
+        let mut m = vec![0.0; len];
+        let mut upper = vec![0.0; len];
+        let mut rhs = vec![0.0; len];
+
+        for i in 1..len - 1 {
+            let slope = (ys[i + 1] - ys[i]) / h[i] - (ys[i] - ys[i - 1]) / h[i - 1];
+            let diag = 2.0 * (h[i - 1] + h[i]) - h[i - 1] * upper[i - 1];
+
+            upper[i] = h[i] / diag;
+            rhs[i] = (6.0 * slope - h[i - 1] * rhs[i - 1]) / diag;
+        }
+
+        for i in (1..len - 1).rev() {
+            m[i] = rhs[i] - upper[i] * m[i + 1];
+        }
+
+        let coefficients = (0..len - 1)
+            .map(|i| {
+                let b = (ys[i + 1] - ys[i]) / h[i] - h[i] * (2.0 * m[i] + m[i + 1]) / 6.0;
+                let c = m[i] / 2.0;
+                let d = (m[i + 1] - m[i]) / (6.0 * h[i]);
+
+                [ys[i], b, c, d]
+            })
+            .collect();
+
+        Self { xs, coefficients }
+    }
+}
+
+impl TryCallable<f64> for CubicSplineLookup {
+    fn try_call(&self, x: f64) -> Option<f64> {
+        let first = *self.xs.first()?;
+        let last = *self.xs.last()?;
+
+        if !(first..=last).contains(&x) {
+            return None;
+        }
+
+        // the segment that starts at or before the given x, the last value
+        // uses the end of the final segment
+        let index =
+            (self.xs.partition_point(|value| *value <= x) - 1).min(self.coefficients.len() - 1);
+        let [y, b, c, d] = self.coefficients[index];
+        let t = x - self.xs[index];
+
+        Some(y + t * (b + t * (c + t * d)))
+    }
+}
+
+impl Callable<f64> for CubicSplineLookup {
+    /// panics if the x value is outside of the table
+    fn call(&self, x: f64) -> f64 {
+        self.try_call(x)
+            .expect("x value is outside of the lookup table")
+    }
+}
+
+/// a single sine term of a [`FourierSeries`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FourierTerm {
+    /// the peak value of the term
+    pub amplitude: f64,
+    /// the frequency of the term in cycles per second
+    pub frequency: f64,
+    /// the phase offset of the term in radians
+    pub phase: f64,
+}
+
+impl FourierTerm {
+    /// evaluates the term at the given x
+    pub fn value(&self, x: f64) -> f64 {
+        self.amplitude * (std::f64::consts::TAU * self.frequency * x + self.phase).sin()
+    }
+}
+
+/// a profile defined by a truncated series of sine terms
+///
+/// ```
+/// use train_sim::summation::{Callable, FourierSeries, FourierTerm};
+///
+/// let series = FourierSeries::new(vec![FourierTerm {
+///     amplitude: 2.0,
+///     frequency: 0.25,
+///     phase: 0.0,
+/// }]);
+///
+/// assert_eq!(series.call(1.0), 2.0);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct FourierSeries {
+    terms: Vec<FourierTerm>,
+}
+
+impl FourierSeries {
+    /// creates a new series from the given terms
+    pub fn new(terms: Vec<FourierTerm>) -> Self {
+        Self { terms }
+    }
+
+    /// the terms of the series
+    pub fn terms(&self) -> &[FourierTerm] {
+        &self.terms
+    }
+}
+
+impl Callable<f64> for FourierSeries {
+    fn call(&self, x: f64) -> f64 {
+        self.terms.iter().map(|term| term.value(x)).sum()
+    }
+}
+
+/// options for how the summation functions will access the lookup tables
+#[derive(Debug, Clone)]
+pub struct SummationConfig {
+    /// when true the lookup tables will panic if a value outside of the table
+    /// is requested. when false the [`UncheckedLookup`] should be used
+    ///
+    /// defaults to true
+    pub checked: bool,
+    /// the error tolerance of each interval for [`adaptive_simpson_panel`]
+    ///
+    /// defaults to [`DEFAULT_ADAPTIVE_TOLERANCE`]
+    pub tolerance: f64,
+    /// the amount of trapezoidal refinements of each interval for
+    /// [`romberg_panel`]
+    ///
+    /// defaults to [`DEFAULT_ROMBERG_LEVELS`]
+    pub levels: u32,
+}
+
+impl SummationConfig {
+    /// wraps the given callable so the summation functions use the settings
+    /// of this config with it
+    pub fn apply<'a, T: ?Sized>(&self, inner: &'a T) -> ConfiguredCallable<'a, T> {
+        ConfiguredCallable {
+            inner,
+            tolerance: self.tolerance,
+            levels: self.levels,
+        }
+    }
+}
+
+impl Default for SummationConfig {
+    fn default() -> Self {
+        Self {
+            checked: true,
+            tolerance: DEFAULT_ADAPTIVE_TOLERANCE,
+            levels: DEFAULT_ROMBERG_LEVELS,
+        }
+    }
+}
+
+/// wraps a callable with the settings of a [`SummationConfig`], created with
+/// [`SummationConfig::apply`]
+///
+/// the values are passed through unchanged, only the settings that the
+/// summation functions read from the callable are replaced
+///
+/// ```
+/// use train_sim::summation::{adaptive_simpson_panel, Callable, SummationConfig};
+///
+/// let curve = |x: f64| x.sqrt();
+/// let config = SummationConfig {
+///     tolerance: 1e-12,
+///     ..SummationConfig::default()
+/// };
+/// let configured = config.apply(&curve);
+///
+/// assert_eq!(configured.adaptive_tolerance(), 1e-12);
+/// let result = adaptive_simpson_panel(0.0, 1.0, 1, &configured).unwrap();
+///
+/// assert!((result - 2.0 / 3.0).abs() < 1e-11);
+/// ```
+pub struct ConfiguredCallable<'a, T: ?Sized> {
+    inner: &'a T,
+    tolerance: f64,
+    levels: u32,
+}
+
+impl<T, V> Callable<f64, V> for ConfiguredCallable<'_, T>
+where
+    T: Callable<f64, V> + ?Sized,
+{
+    fn call(&self, x: f64) -> V {
+        self.inner.call(x)
+    }
+
+    fn adaptive_tolerance(&self) -> f64 {
+        self.tolerance
+    }
+
+    fn romberg_levels(&self) -> u32 {
+        self.levels
+    }
+}
+
+/// provides interpolated lookups without the bounds checks of
+/// [`InterpolateLookup`]
+///
+/// any indexs that fall outside of the table will be clamped to the first or
+/// last value of the table instead of panicking. this is only useful for
+/// trusted data where it is known that all requested x values are within the
+/// table.
+///
+/// ```
+/// use train_sim::summation::{Callable, InterpolateLookup, LookupError, UncheckedLookup};
+///
+/// let lt = InterpolateLookup::from(vec![0.0, 1.5, 3.0]);
+/// let unchecked = UncheckedLookup::new(&lt).unwrap();
+///
+/// assert_eq!(lt.call(1.5), unchecked.call(1.5));
+///
+/// let empty = InterpolateLookup::<f64>::from(vec![]);
+///
+/// assert_eq!(UncheckedLookup::new(&empty).unwrap_err(), LookupError::Empty);
+/// ```
+#[derive(Debug, Clone)]
+pub struct UncheckedLookup<'a, V = f64> {
+    lookup: &'a [V],
+    last: usize,
+    dx: f64,
+}
+
+impl<'a, V> UncheckedLookup<'a, V> {
+    /// creates a new unchecked lookup from the given lookup table
+    ///
+    /// fails if the lookup table is empty or was created from pairs
+    pub fn new(given: &'a InterpolateLookup<V>) -> Result<Self, LookupError> {
+        if given.lookup.is_empty() {
+            return Err(LookupError::Empty);
+        }
+
+        if given.xs.is_some() {
+            return Err(LookupError::Pairs);
+        }
+
+        Ok(Self {
+            lookup: &given.lookup,
+            last: given.lookup.len() - 1,
+            dx: given.dx,
+        })
+    }
+}
+
+impl<V: Sample> UncheckedLookup<'_, V> {
+    /// retrieves the value at the given index clamped to the table
+    fn get_index(&self, index: usize) -> V {
+        self.lookup[index.min(self.last)]
+    }
+}
+
+impl<V: Sample> Callable<f64, V> for UncheckedLookup<'_, V> {
+    fn call(&self, x: f64) -> V {
+        let scaled = x / self.dx;
+        let x0 = scaled.floor();
+        let index = x0 as usize;
+
+        if x0 == scaled {
+            return self.get_index(index);
+        }
+
+        let y0 = self.get_index(index);
+        let y1 = self.get_index(index + 1);
+
+        y0 + (y1 - y0) * (scaled - x0)
+    }
+}
+
+/// wraps a callable and squares the values that it returns
+///
+/// useful for calculating the energy of a signal when integrated
+pub struct SquaredCallable<'a, T: ?Sized> {
+    inner: &'a T,
+}
+
+impl<'a, T: ?Sized> SquaredCallable<'a, T> {
+    /// creates a new squared callable from the given callable
+    pub fn new(inner: &'a T) -> Self {
+        Self { inner }
+    }
+}
+
+impl<T> Callable<f64> for SquaredCallable<'_, T>
+where
+    T: Callable<f64> + ?Sized,
+{
+    fn call(&self, x: f64) -> f64 {
+        let y = self.inner.call(x);
+
+        y * y
+    }
+
+    fn adaptive_tolerance(&self) -> f64 {
+        self.inner.adaptive_tolerance()
+    }
+
+    fn romberg_levels(&self) -> u32 {
+        self.inner.romberg_levels()
+    }
+}
+
+/// wraps two callables and multiplies the values that they return
+///
+/// useful for calculating the power of a force and velocity when integrated.
+/// the summation settings come from the left callable
+pub struct ProductCallable<'a, A: ?Sized, B: ?Sized> {
+    lhs: &'a A,
+    rhs: &'a B,
+}
+
+impl<'a, A: ?Sized, B: ?Sized> ProductCallable<'a, A, B> {
+    /// creates a new product callable from the given callables
+    pub fn new(lhs: &'a A, rhs: &'a B) -> Self {
+        Self { lhs, rhs }
+    }
+}
+
+impl<A, B> Callable<f64> for ProductCallable<'_, A, B>
+where
+    A: Callable<f64> + ?Sized,
+    B: Callable<f64> + ?Sized,
+{
+    fn call(&self, x: f64) -> f64 {
+        self.lhs.call(x) * self.rhs.call(x)
+    }
+
+    fn adaptive_tolerance(&self) -> f64 {
+        self.lhs.adaptive_tolerance()
+    }
+
+    fn romberg_levels(&self) -> u32 {
+        self.lhs.romberg_levels()
+    }
+}
+
+/// wraps a callable and returns the absolute value of the values that it
+/// returns
+///
+/// useful for calculating the total magnitude of a signal when integrated
+pub struct AbsCallable<'a, T: ?Sized> {
+    inner: &'a T,
+}
+
+impl<'a, T: ?Sized> AbsCallable<'a, T> {
+    /// creates a new absolute callable from the given callable
+    pub fn new(inner: &'a T) -> Self {
+        Self { inner }
+    }
+}
+
+impl<T> Callable<f64> for AbsCallable<'_, T>
+where
+    T: Callable<f64> + ?Sized,
+{
+    fn call(&self, x: f64) -> f64 {
+        self.inner.call(x).abs()
+    }
+
+    fn adaptive_tolerance(&self) -> f64 {
+        self.inner.adaptive_tolerance()
+    }
+
+    fn romberg_levels(&self) -> u32 {
+        self.inner.romberg_levels()
+    }
+}
+
+/// wraps a callable and returns zero for the unit intervals that are masked
+/// out
+///
+/// a `false` entry in the mask removes the interval starting at that index so
+/// gaps in the data do not contribute to an integral instead of being
+/// interpolated over
+pub struct MaskedCallable<'a, T: ?Sized> {
+    inner: &'a T,
+    mask: &'a [bool],
+}
+
+impl<'a, T: ?Sized> MaskedCallable<'a, T> {
+    /// creates a new masked callable from the given callable and mask
+    pub fn new(inner: &'a T, mask: &'a [bool]) -> Self {
+        Self { inner, mask }
+    }
+}
+
+impl<T> Callable<f64> for MaskedCallable<'_, T>
+where
+    T: Callable<f64> + ?Sized,
+{
+    fn call(&self, x: f64) -> f64 {
+        if x >= 0.0 && self.mask.get(x.floor() as usize) == Some(&false) {
+            0.0
+        } else {
+            self.inner.call(x)
+        }
+    }
+
+    fn adaptive_tolerance(&self) -> f64 {
+        self.inner.adaptive_tolerance()
+    }
+
+    fn romberg_levels(&self) -> u32 {
+        self.inner.romberg_levels()
+    }
+}
+
+/// the value returned by a [`ShiftedCallable`] before the delay has passed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ShiftFill {
+    /// returns zero
+    Zero,
+    /// returns the first value of the inner callable
+    Clamp,
+}
+
+/// wraps a callable and shifts it later in time by the given delay
+///
+/// the inner callable is called with `x - delay` and any x before the delay
+/// will return the specified [`ShiftFill`]
+///
+/// ```
+/// use train_sim::summation::{Callable, InterpolateLookup, ShiftFill, ShiftedCallable};
+///
+/// let lt = InterpolateLookup::from(vec![1.0, 2.0, 3.0]);
+/// let shifted = ShiftedCallable::new(&lt, 1.0, ShiftFill::Zero);
+///
+/// assert_eq!(shifted.call(0.5), 0.0);
+/// assert_eq!(shifted.call(2.0), 2.0);
+/// ```
+pub struct ShiftedCallable<'a, T: ?Sized> {
+    inner: &'a T,
+    delay: f64,
+    fill: ShiftFill,
+}
+
+impl<'a, T: ?Sized> ShiftedCallable<'a, T> {
+    /// creates a new shifted callable from the given callable
+    pub fn new(inner: &'a T, delay: f64, fill: ShiftFill) -> Self {
+        Self { inner, delay, fill }
+    }
+}
+
+impl<T> Callable<f64> for ShiftedCallable<'_, T>
+where
+    T: Callable<f64> + ?Sized,
+{
+    fn call(&self, x: f64) -> f64 {
+        let shifted = x - self.delay;
+
+        if shifted < 0.0 {
+            match self.fill {
+                ShiftFill::Zero => 0.0,
+                ShiftFill::Clamp => self.inner.call(0.0),
+            }
+        } else {
+            self.inner.call(shifted)
+        }
+    }
+
+    fn adaptive_tolerance(&self) -> f64 {
+        self.inner.adaptive_tolerance()
+    }
+
+    fn romberg_levels(&self) -> u32 {
+        self.inner.romberg_levels()
+    }
+}
+
+/// sums each unit interval from 0 to `length - 1` of the given callable with
+/// the provided summation function
+pub fn sum_panels<T, V>(
+    length: usize,
+    step: u32,
+    cb: &T,
+    sum_cb: fn(f64, f64, u32, &T) -> Result<V, SummationError>,
+) -> Result<V, SummationError>
+where
+    T: Callable<f64, V> + ?Sized,
+    V: Sample,
+{
+    (1..length)
+        .map(|sec| sum_cb((sec - 1) as f64, sec as f64, step, cb))
+        .try_fold(V::default(), |acc, value| Ok(acc + value?))
+}
+
+/// same as [`sum_panels`] but accumulates the panels with [`KahanSum`]
+pub fn sum_panels_compensated<T>(
+    length: usize,
+    step: u32,
+    cb: &T,
+    sum_cb: fn(f64, f64, u32, &T) -> Result<f64, SummationError>,
+) -> Result<f64, SummationError>
+where
+    T: Callable<f64> + ?Sized,
+{
+    sum_bounds_compensated(
+        length,
+        |sec| ((sec - 1) as f64, sec as f64),
+        step,
+        cb,
+        sum_cb,
+    )
+}
+
+/// same as [`sum_panels_compensated`] but each panel is between consecutive
+/// values of the given times instead of one second apart
+pub fn sum_intervals_compensated<T>(
+    times: &[f64],
+    step: u32,
+    cb: &T,
+    sum_cb: fn(f64, f64, u32, &T) -> Result<f64, SummationError>,
+) -> Result<f64, SummationError>
+where
+    T: Callable<f64> + ?Sized,
+{
+    sum_bounds_compensated(
+        times.len(),
+        |index| (times[index - 1], times[index]),
+        step,
+        cb,
+        sum_cb,
+    )
+}
+
+/// sums the panels between the bounds of each index from 1 up to the given
+/// length with [`KahanSum`]
+fn sum_bounds_compensated<T, B>(
+    length: usize,
+    bounds: B,
+    step: u32,
+    cb: &T,
+    sum_cb: fn(f64, f64, u32, &T) -> Result<f64, SummationError>,
+) -> Result<f64, SummationError>
+where
+    T: Callable<f64> + ?Sized,
+    B: Fn(usize) -> (f64, f64),
+{
+    let mut sum = KahanSum::default();
+
+    for index in 1..length {
+        let (lower, upper) = bounds(index);
+
+        sum.add(sum_cb(lower, upper, step, cb)?);
+    }
+
+    Ok(sum.value())
+}
+
+/// multi-threaded version of [`sum_panels_compensated`]
+///
+/// the panels are calculated on the global rayon thread pool but collected in
+/// order and summed on the calling thread so the result is identical to
+/// [`sum_panels_compensated`]. when repeatable each thread always calculates
+/// the same panels, see [`crate::schedule::static_map`]
+pub fn sum_panels_rayon<T>(
+    length: usize,
+    step: u32,
+    cb: &T,
+    sum_cb: fn(f64, f64, u32, &T) -> Result<f64, SummationError>,
+    repeatable: bool,
+) -> Result<f64, SummationError>
+where
+    T: Callable<f64> + Sync + ?Sized,
+{
+    sum_bounds_rayon(
+        length,
+        |sec| ((sec - 1) as f64, sec as f64),
+        step,
+        cb,
+        sum_cb,
+        repeatable,
+    )
+}
+
+/// multi-threaded version of [`sum_intervals_compensated`], see
+/// [`sum_panels_rayon`]
+pub fn sum_intervals_rayon<T>(
+    times: &[f64],
+    step: u32,
+    cb: &T,
+    sum_cb: fn(f64, f64, u32, &T) -> Result<f64, SummationError>,
+    repeatable: bool,
+) -> Result<f64, SummationError>
+where
+    T: Callable<f64> + Sync + ?Sized,
+{
+    sum_bounds_rayon(
+        times.len(),
+        |index| (times[index - 1], times[index]),
+        step,
+        cb,
+        sum_cb,
+        repeatable,
+    )
+}
+
+/// multi-threaded version of [`sum_bounds_compensated`]
+fn sum_bounds_rayon<T, B>(
+    length: usize,
+    bounds: B,
+    step: u32,
+    cb: &T,
+    sum_cb: fn(f64, f64, u32, &T) -> Result<f64, SummationError>,
+    repeatable: bool,
+) -> Result<f64, SummationError>
+where
+    T: Callable<f64> + Sync + ?Sized,
+    B: Fn(usize) -> (f64, f64) + Sync,
+{
+    use rayon::prelude::*;
+
+    let panel = |index: usize| {
+        let (lower, upper) = bounds(index);
+
+        sum_cb(lower, upper, step, cb)
+    };
+    let panels = if repeatable {
+        crate::schedule::static_map(1..length, panel)
+    } else {
+        (1..length).into_par_iter().map(panel).collect::<Vec<_>>()
+    };
+
+    let mut sum = KahanSum::default();
+
+    for value in panels {
+        sum.add(value?);
+    }
+
+    Ok(sum.value())
+}
+
+/// calculates the time weighted average of the given callable over the unit
+/// intervals from 0 to `length - 1`
+///
+/// this is the integral of the callable divided by the total duration. if
+/// there are not enough values to have a duration then zero is returned
+pub fn time_average<T>(
+    length: usize,
+    step: u32,
+    cb: &T,
+    sum_cb: fn(f64, f64, u32, &T) -> Result<f64, SummationError>,
+) -> Result<f64, SummationError>
+where
+    T: Callable<f64> + ?Sized,
+{
+    if length < 2 {
+        return Ok(0.0);
+    }
+
+    Ok(sum_panels(length, step, cb, sum_cb)? / (length - 1) as f64)
+}
+
+/// same as [`time_average`] but over the intervals between consecutive values
+/// of the given times
+///
+/// the integral is divided by the time between the first and last values so
+/// wider intervals carry more weight
+///
+/// ```
+/// use train_sim::summation::{time_average_intervals, trapezoidal, InterpolateLookup};
+///
+/// let times = [0.0, 1.0, 4.0];
+/// let lookup = InterpolateLookup::from_pairs(vec![(0.0, 0.0), (1.0, 0.0), (4.0, 6.0)]);
+///
+/// assert_eq!(time_average_intervals(&times, 1, &lookup, trapezoidal), Ok(2.25));
+/// ```
+pub fn time_average_intervals<T>(
+    times: &[f64],
+    step: u32,
+    cb: &T,
+    sum_cb: fn(f64, f64, u32, &T) -> Result<f64, SummationError>,
+) -> Result<f64, SummationError>
+where
+    T: Callable<f64> + ?Sized,
+{
+    if times.len() < 2 {
+        return Ok(0.0);
+    }
+
+    Ok(sum_intervals_compensated(times, step, cb, sum_cb)? / (times[times.len() - 1] - times[0]))
+}
+
+/// builds a lookup table of the running sum of each unit interval from 0 to
+/// `length - 1` of the given callable with the provided summation function
+///
+/// the first value of the table is always zero and the last value will be the
+/// same as [`sum_panels`]
+pub fn cumulative_panels<T, V>(
+    length: usize,
+    step: u32,
+    cb: &T,
+    sum_cb: fn(f64, f64, u32, &T) -> Result<V, SummationError>,
+) -> Result<InterpolateLookup<V>, SummationError>
+where
+    T: Callable<f64, V> + ?Sized,
+    V: Sample,
+{
+    let mut rtn = InterpolateLookup::from(Vec::with_capacity(length));
+    let mut rolling = V::default();
+    rtn.push(rolling);
+
+    for sec in 1..length {
+        rolling += sum_cb((sec - 1) as f64, sec as f64, step, cb)?;
+
+        rtn.push(rolling);
+    }
+
+    Ok(rtn)
+}
+
+/// same as [`cumulative_panels`] but the running sum is never allowed to go
+/// below the given minimum or above the given maximum
+///
+/// the sum is clamped after each unit interval so anything that would have
+/// gone past the bounds is discarded, a following interval going the other
+/// way will move the sum away from the bound it was clamped to
+pub fn cumulative_panels_clamped<T>(
+    length: usize,
+    step: u32,
+    cb: &T,
+    sum_cb: fn(f64, f64, u32, &T) -> Result<f64, SummationError>,
+    min: f64,
+    max: f64,
+) -> Result<InterpolateLookup, SummationError>
+where
+    T: Callable<f64> + ?Sized,
+{
+    let mut rtn = InterpolateLookup::from(Vec::with_capacity(length));
+    let mut rolling = 0.0f64;
+    rtn.push(rolling);
+
+    for sec in 1..length {
+        rolling = (rolling + sum_cb((sec - 1) as f64, sec as f64, step, cb)?).clamp(min, max);
+
+        rtn.push(rolling);
+    }
+
+    Ok(rtn)
+}
+
+/// chooses the amount of steps for each unit interval of the lookup table
+/// based on the curvature around it
+///
+/// the curvature is estimated from the second difference of the neighboring
+/// values on either end of the interval. the interval with the largest
+/// curvature gets `max_step` while flat intervals get `min_step`, anything in
+/// between is scaled linearly. the returned list has one entry per unit
+/// interval
+pub fn curvature_steps(lookup: &InterpolateLookup, min_step: u32, max_step: u32) -> Vec<u32> {
+    assert_ne!(min_step, 0);
+    assert!(min_step <= max_step);
+
+    let values = lookup.to_vec();
+
+    if values.len() < 2 {
+        return Vec::new();
+    }
+
+    let last = values.len() - 1;
+    let second_diff = |index: usize| {
+        if index == 0 || index == last {
+            0.0
+        } else {
+            (values[index - 1] - 2.0 * values[index] + values[index + 1]).abs()
+        }
+    };
+
+    let curvature = (0..last)
+        .map(|index| second_diff(index).max(second_diff(index + 1)))
+        .collect::<Vec<f64>>();
+    let max_curvature = curvature.iter().copied().fold(0.0, f64::max);
+
+    if max_curvature == 0.0 {
+        return vec![min_step; last];
+    }
+
+    let range = (max_step - min_step) as f64;
+
+    curvature
+        .into_iter()
+        .map(|value| min_step + (range * value / max_curvature).round() as u32)
+        .collect()
+}
+
+/// sums each unit interval of the given callable with the provided summation
+/// function using the amount of steps specified for that interval
+///
+/// the amount of unit intervals is the length of the steps provided
+pub fn sum_adaptive_panels<T, V>(
+    steps: &[u32],
+    cb: &T,
+    sum_cb: fn(f64, f64, u32, &T) -> Result<V, SummationError>,
+) -> Result<V, SummationError>
+where
+    T: Callable<f64, V> + ?Sized,
+    V: Sample,
+{
+    steps
+        .iter()
+        .enumerate()
+        .map(|(sec, step)| sum_cb(sec as f64, (sec + 1) as f64, *step, cb))
+        .try_fold(V::default(), |acc, value| Ok(acc + value?))
+}
+
+/// builds a lookup table of the running sum of each unit interval of the given
+/// callable using the amount of steps specified for that interval
+///
+/// the first value of the table is always zero and the last value will be the
+/// same as [`sum_adaptive_panels`]
+pub fn cumulative_adaptive_panels<T, V>(
+    steps: &[u32],
+    cb: &T,
+    sum_cb: fn(f64, f64, u32, &T) -> Result<V, SummationError>,
+) -> Result<InterpolateLookup<V>, SummationError>
+where
+    T: Callable<f64, V> + ?Sized,
+    V: Sample,
+{
+    let mut rtn = InterpolateLookup::from(Vec::with_capacity(steps.len() + 1));
+    let mut rolling = V::default();
+    rtn.push(rolling);
+
+    for (sec, step) in steps.iter().enumerate() {
+        rolling += sum_cb(sec as f64, (sec + 1) as f64, *step, cb)?;
+
+        rtn.push(rolling);
+    }
+
+    Ok(rtn)
+}
+
+/// calculates the L2 norm of the given callable over the unit intervals from 0
+/// to `length - 1`
+///
+/// this is the square root of the integral of the squared values
+pub fn l2_norm<'a, T>(
+    length: usize,
+    step: u32,
+    cb: &'a T,
+    sum_cb: fn(f64, f64, u32, &SquaredCallable<'a, T>) -> Result<f64, SummationError>,
+) -> Result<f64, SummationError>
+where
+    T: Callable<f64> + ?Sized,
+{
+    Ok(sum_panels(length, step, &SquaredCallable::new(cb), sum_cb)?.sqrt())
+}
+
+/// performs a left riemann summation with the given callable
+pub fn left_riemann<T, V>(
+    lower: f64,
+    upper: f64,
+    iterations: u32,
+    cb: &T,
+) -> Result<V, SummationError>
+where
+    T: Callable<f64, V> + ?Sized,
+    V: Sample,
+{
+    check_iterations(iterations)?;
+
+    let step = (upper - lower) / (iterations as f64);
+    let mut sum = V::default();
+
+    for iter in 0..iterations {
+        let i = iter as f64;
+        let x = lower + i * step;
+
+        sum += cb.call(x);
+    }
+
+    Ok(sum * step)
+}
+
+/// performs a midpoint riemann summation with the given callable
+pub fn mid_riemann<T, V>(
+    lower: f64,
+    upper: f64,
+    iterations: u32,
+    cb: &T,
+) -> Result<V, SummationError>
+where
+    T: Callable<f64, V> + ?Sized,
+    V: Sample,
+{
+    check_iterations(iterations)?;
+
+    let step = (upper - lower) / (iterations as f64);
+    let half = step / 2.0;
+    let mut sum = V::default();
+
+    for iter in 0..iterations {
+        let i = iter as f64;
+        let x = (lower + i * step) + half;
+
+        sum += cb.call(x);
+    }
+
+    Ok(sum * step)
+}
+
+/// performs a right riemann summation with the given callable
+pub fn right_riemann<T, V>(
+    lower: f64,
+    upper: f64,
+    iterations: u32,
+    cb: &T,
+) -> Result<V, SummationError>
+where
+    T: Callable<f64, V> + ?Sized,
+    V: Sample,
+{
+    check_iterations(iterations)?;
+
+    let step = (upper - lower) / (iterations as f64);
+    let mut sum = V::default();
+
+    for iter in 0..iterations {
+        let i = (iter + 1) as f64;
+        let x = lower + i * step;
+
+        sum += cb.call(x);
+    }
+
+    Ok(sum * step)
+}
+
+/// performs a trapezoidal summation with the given callable
+pub fn trapezoidal<T, V>(
+    lower: f64,
+    upper: f64,
+    iterations: u32,
+    cb: &T,
+) -> Result<V, SummationError>
+where
+    T: Callable<f64, V> + ?Sized,
+    V: Sample,
+{
+    check_iterations(iterations)?;
+
+    let step = (upper - lower) / (iterations as f64);
+    let mut sum = (cb.call(upper) + cb.call(lower)) / 2.0;
+
+    for iter in 1..iterations {
+        let i = iter as f64;
+        let x = lower + i * step;
+
+        sum += cb.call(x);
+    }
+
+    Ok(sum * step)
+}
+
+/// the weight of the value at the given iteration for [`simpsons`] in units of
+/// a third of the step
+///
+/// an even amount of iterations uses the usual 1, 4, 2, ..., 4, 1 pattern.
+/// with an odd amount the last 3 steps use simpsons 3/8 rule instead so that
+/// the pairing still works, and a single step falls back to a trapezoid
+pub fn simpsons_weight(iter: u32, iterations: u32) -> f64 {
+    if iterations == 1 {
+        return 1.5;
+    }
+
+    // the end of the steps that use the 1/3 rule
+    let split = if iterations.is_multiple_of(2) {
+        iterations
+    } else {
+        iterations - 3
+    };
+    let mut rtn = 0.0;
+
+    if iter <= split && split != 0 {
+        rtn += if iter == 0 || iter == split {
+            1.0
+        } else if iter % 2 == 1 {
+            4.0
+        } else {
+            2.0
+        };
+    }
+
+    if iter >= split && split != iterations {
+        // the 3/8 rule is 3/8, 9/8, 9/8, 3/8 of the step
+        rtn += if iter == split || iter == iterations {
+            9.0 / 8.0
+        } else {
+            27.0 / 8.0
+        };
+    }
+
+    rtn
+}
+
+/// performs simpsons summation with the given callable
+///
+/// see [`simpsons_weight`] for how an odd amount of iterations is handled
+pub fn simpsons<T, V>(lower: f64, upper: f64, iterations: u32, cb: &T) -> Result<V, SummationError>
+where
+    T: Callable<f64, V> + ?Sized,
+    V: Sample,
+{
+    check_iterations(iterations)?;
+
+    let step = (upper - lower) / (iterations as f64);
+    let mut sum = V::default();
+
+    for iter in 0..=iterations {
+        let i = iter as f64;
+        let x = lower + i * step;
+        let res = cb.call(x);
+
+        sum += res * simpsons_weight(iter, iterations);
+    }
+
+    Ok(sum * step / 3.0)
+}
+
+/// the furthest that the tanh-sinh nodes extend to on either side of the
+/// center of the transform
+const TANH_SINH_RANGE: f64 = 4.0;
+
+/// calculates the nodes and weights for tanh-sinh quadrature between lower and
+/// upper with the given level
+///
+/// the level is the amount of nodes on each side of the center. the nodes
+/// cluster towards the bounds but will never be on the bounds themselves, any
+/// nodes that are too close to a bound to be distinguished from it are
+/// skipped. fails if the level is zero
+pub fn tanh_sinh_nodes(
+    lower: f64,
+    upper: f64,
+    level: u32,
+) -> Result<impl Iterator<Item = (f64, f64)>, SummationError> {
+    check_iterations(level)?;
+
+    let half = (upper - lower) / 2.0;
+    let center = lower + half;
+    let h = TANH_SINH_RANGE / level as f64;
+    let level = level as i64;
+
+    Ok((-level..=level).filter_map(move |k| {
+        let t = k as f64 * h;
+        let u = std::f64::consts::FRAC_PI_2 * t.sinh();
+        let cosh_u = u.cosh();
+
+        // calculate the distance from the nearest bound directly to avoid
+        // losing precision when tanh is close to 1
+        let offset = half * 2.0 / ((2.0 * u.abs()).exp() + 1.0);
+        let x = if k < 0 {
+            lower + offset
+        } else {
+            upper - offset
+        };
+        let x = if k == 0 { center } else { x };
+        let weight = half * h * std::f64::consts::FRAC_PI_2 * t.cosh() / (cosh_u * cosh_u);
+
+        if x <= lower || x >= upper || weight == 0.0 {
+            None
+        } else {
+            Some((x, weight))
+        }
+    }))
+}
+
+/// performs tanh-sinh (double exponential) quadrature with the given callable
+///
+/// the iterations are the level of the quadrature, see [`tanh_sinh_nodes`].
+/// since the bounds are never evaluated this can handle integrable
+/// singularities at the bounds
+pub fn tanh_sinh<T, V>(lower: f64, upper: f64, iterations: u32, cb: &T) -> Result<V, SummationError>
+where
+    T: Callable<f64, V> + ?Sized,
+    V: Sample,
+{
+    let mut sum = V::default();
+
+    for (x, weight) in tanh_sinh_nodes(lower, upper, iterations)? {
+        sum += cb.call(x) * weight;
+    }
+
+    Ok(sum)
+}
+
+/// same as [`left_riemann`] but returns an error for zero iterations or
+/// at the first value that cannot be calculated
+pub fn try_left_riemann<T, V>(
+    lower: f64,
+    upper: f64,
+    iterations: u32,
+    cb: &T,
+) -> Result<V, SummationError>
+where
+    T: TryCallable<f64, V> + ?Sized,
+    V: Sample,
+{
+    check_iterations(iterations)?;
+
+    let step = (upper - lower) / (iterations as f64);
+    let mut sum = V::default();
+
+    for iter in 0..iterations {
+        let i = iter as f64;
+        let x = lower + i * step;
+
+        sum += cb.try_call(x).ok_or(SummationError::OutOfRange)?;
+    }
+
+    Ok(sum * step)
+}
+
+/// same as [`mid_riemann`] but returns an error for zero iterations or
+/// at the first value that cannot be calculated
+pub fn try_mid_riemann<T, V>(
+    lower: f64,
+    upper: f64,
+    iterations: u32,
+    cb: &T,
+) -> Result<V, SummationError>
+where
+    T: TryCallable<f64, V> + ?Sized,
+    V: Sample,
+{
+    check_iterations(iterations)?;
+
+    let step = (upper - lower) / (iterations as f64);
+    let half = step / 2.0;
+    let mut sum = V::default();
+
+    for iter in 0..iterations {
+        let i = iter as f64;
+        let x = (lower + i * step) + half;
+
+        sum += cb.try_call(x).ok_or(SummationError::OutOfRange)?;
+    }
+
+    Ok(sum * step)
+}
+
+/// same as [`right_riemann`] but returns an error for zero iterations or
+/// at the first value that cannot be calculated
+pub fn try_right_riemann<T, V>(
+    lower: f64,
+    upper: f64,
+    iterations: u32,
+    cb: &T,
+) -> Result<V, SummationError>
+where
+    T: TryCallable<f64, V> + ?Sized,
+    V: Sample,
+{
+    check_iterations(iterations)?;
+
+    let step = (upper - lower) / (iterations as f64);
+    let mut sum = V::default();
+
+    for iter in 0..iterations {
+        let i = (iter + 1) as f64;
+        let x = lower + i * step;
+
+        sum += cb.try_call(x).ok_or(SummationError::OutOfRange)?;
+    }
+
+    Ok(sum * step)
+}
+
+/// same as [`trapezoidal`] but returns an error for zero iterations or
+/// at the first value that cannot be calculated
+pub fn try_trapezoidal<T, V>(
+    lower: f64,
+    upper: f64,
+    iterations: u32,
+    cb: &T,
+) -> Result<V, SummationError>
+where
+    T: TryCallable<f64, V> + ?Sized,
+    V: Sample,
+{
+    check_iterations(iterations)?;
+
+    let step = (upper - lower) / (iterations as f64);
+    let mut sum = (cb.try_call(upper).ok_or(SummationError::OutOfRange)?
+        + cb.try_call(lower).ok_or(SummationError::OutOfRange)?)
+        / 2.0;
+
+    for iter in 1..iterations {
+        let i = iter as f64;
+        let x = lower + i * step;
+
+        sum += cb.try_call(x).ok_or(SummationError::OutOfRange)?;
+    }
+
+    Ok(sum * step)
+}
+
+/// same as [`simpsons`] but returns an error for zero iterations or
+/// at the first value that cannot be calculated
+pub fn try_simpsons<T, V>(
+    lower: f64,
+    upper: f64,
+    iterations: u32,
+    cb: &T,
+) -> Result<V, SummationError>
+where
+    T: TryCallable<f64, V> + ?Sized,
+    V: Sample,
+{
+    check_iterations(iterations)?;
+
+    let step = (upper - lower) / (iterations as f64);
+    let mut sum = V::default();
+
+    for iter in 0..=iterations {
+        let i = iter as f64;
+        let x = lower + i * step;
+        let res = cb.try_call(x).ok_or(SummationError::OutOfRange)?;
+
+        sum += res * simpsons_weight(iter, iterations);
+    }
+
+    Ok(sum * step / 3.0)
+}
+
+/// performs quadrature with the given rule of nodes and weights on `[-1, 1]`
+///
+/// the interval is split into the given amount of panels and the rule is
+/// mapped onto each one
+///
+/// ```
+/// use train_sim::summation::custom_quadrature;
+///
+/// // 2 point gauss-legendre is exact for cubics
+/// let node = 1.0 / 3.0f64.sqrt();
+/// let rule = [(-node, 1.0), (node, 1.0)];
+/// let result: f64 = custom_quadrature(&rule, 0.0, 2.0, 1, &|x: f64| x * x * x).unwrap();
+///
+/// assert!((result - 4.0).abs() < 1e-12);
+/// ```
+pub fn custom_quadrature<T, V>(
+    rule: &[(f64, f64)],
+    lower: f64,
+    upper: f64,
+    iterations: u32,
+    cb: &T,
+) -> Result<V, SummationError>
+where
+    T: Callable<f64, V> + ?Sized,
+    V: Sample,
+{
+    check_iterations(iterations)?;
+
+    let step = (upper - lower) / (iterations as f64);
+    let half = step / 2.0;
+    let mut sum = V::default();
+
+    for iter in 0..iterations {
+        let center = lower + iter as f64 * step + half;
+
+        for (node, weight) in rule {
+            sum += cb.call(center + node * half) * *weight;
+        }
+    }
+
+    Ok(sum * half)
+}
+
+/// the deepest that [`adaptive_simpson`] will subdivide an interval
+pub const ADAPTIVE_MAX_DEPTH: u32 = 50;
+
+/// the error tolerance used by [`adaptive_simpson_panel`] when the callable
+/// does not provide one
+pub const DEFAULT_ADAPTIVE_TOLERANCE: f64 = 1e-6;
+
+/// performs adaptive simpsons quadrature with the given callable
+///
+/// each interval is split in half until the two halves agree with the whole
+/// interval to within the tolerance, so flat regions use few evaluations and
+/// sharp ones use many. intervals stop being split after
+/// [`ADAPTIVE_MAX_DEPTH`] levels so discontinuous data still finishes
+///
+/// ```
+/// use train_sim::summation::adaptive_simpson;
+///
+/// let result = adaptive_simpson(0.0, 1.0, 1e-10, &|x: f64| x.sqrt());
+///
+/// assert!((result - 2.0 / 3.0).abs() < 1e-9);
+/// ```
+pub fn adaptive_simpson<T>(lower: f64, upper: f64, tol: f64, cb: &T) -> f64
+where
+    T: Callable<f64> + ?Sized,
+{
+    let f_lower = cb.call(lower);
+    let f_upper = cb.call(upper);
+    let mid = (lower + upper) / 2.0;
+    let f_mid = cb.call(mid);
+    let whole = (upper - lower) * (f_lower + 4.0 * f_mid + f_upper) / 6.0;
+
+    adaptive_simpson_split(
+        (lower, f_lower),
+        (mid, f_mid),
+        (upper, f_upper),
+        whole,
+        tol,
+        ADAPTIVE_MAX_DEPTH,
+        cb,
+    )
+}
+
+/// splits the interval in half and compares the halves against the estimate
+/// of the whole interval, recursing into each half if they do not agree
+fn adaptive_simpson_split<T>(
+    (lower, f_lower): (f64, f64),
+    (mid, f_mid): (f64, f64),
+    (upper, f_upper): (f64, f64),
+    whole: f64,
+    tol: f64,
+    depth: u32,
+    cb: &T,
+) -> f64
+where
+    T: Callable<f64> + ?Sized,
+{
+    let left_mid = (lower + mid) / 2.0;
+    let right_mid = (mid + upper) / 2.0;
+    let f_left_mid = cb.call(left_mid);
+    let f_right_mid = cb.call(right_mid);
+
+    let left = (mid - lower) * (f_lower + 4.0 * f_left_mid + f_mid) / 6.0;
+    let right = (upper - mid) * (f_mid + 4.0 * f_right_mid + f_upper) / 6.0;
+    let diff = left + right - whole;
+
+    if depth == 0 || diff.abs() <= 15.0 * tol {
+        // richardson extrapolation of the two estimates
+        return left + right + diff / 15.0;
+    }
+
+    adaptive_simpson_split(
+        (lower, f_lower),
+        (left_mid, f_left_mid),
+        (mid, f_mid),
+        left,
+        tol / 2.0,
+        depth - 1,
+        cb,
+    ) + adaptive_simpson_split(
+        (mid, f_mid),
+        (right_mid, f_right_mid),
+        (upper, f_upper),
+        right,
+        tol / 2.0,
+        depth - 1,
+        cb,
+    )
+}
+
+/// performs [`adaptive_simpson`] with the tolerance of the callable, see
+/// [`Callable::adaptive_tolerance`], so it can be used like the other
+/// summation functions
+///
+/// the iterations are ignored since the amount of evaluations is decided by
+/// the tolerance
+pub fn adaptive_simpson_panel<T>(
+    lower: f64,
+    upper: f64,
+    _iterations: u32,
+    cb: &T,
+) -> Result<f64, SummationError>
+where
+    T: Callable<f64> + ?Sized,
+{
+    let tol = cb.adaptive_tolerance();
+
+    check_tolerance(tol)?;
+
+    Ok(adaptive_simpson(lower, upper, tol, cb))
+}
+
+/// the amount of refinements used by [`romberg_panel`] when the callable does
+/// not provide them
+pub const DEFAULT_ROMBERG_LEVELS: u32 = 5;
+
+/// performs romberg integration with the given callable
+///
+/// the interval is refined with the trapezoidal rule the given amount of
+/// times, doubling the amount of steps each time, and richardson
+/// extrapolation is applied across the columns of the tableau. each
+/// refinement only evaluates the new midpoints so all previous evaluations
+/// are reused
+///
+/// ```
+/// use train_sim::summation::romberg;
+///
+/// let result: f64 = romberg(0.0, 1.0, 4, &|x: f64| x.exp()).unwrap();
+///
+/// assert!((result - (std::f64::consts::E - 1.0)).abs() < 1e-12);
+/// ```
+pub fn romberg<T, V>(lower: f64, upper: f64, max_levels: u32, cb: &T) -> Result<V, SummationError>
+where
+    T: Callable<f64, V> + ?Sized,
+    V: Sample,
+{
+    romberg_tableau(lower, upper, max_levels, cb, trapezoidal, mid_riemann)
+}
+
+/// performs romberg integration using compensated summation for each
+/// refinement with the given callable
+pub fn romberg_compensated<T>(
+    lower: f64,
+    upper: f64,
+    max_levels: u32,
+    cb: &T,
+) -> Result<f64, SummationError>
+where
+    T: Callable<f64> + ?Sized,
+{
+    romberg_tableau(
+        lower,
+        upper,
+        max_levels,
+        cb,
+        trapezoidal_compensated,
+        mid_riemann_compensated,
+    )
+}
+
+/// builds the romberg tableau with the given trapezoidal and midpoint
+/// summation functions
+///
+/// the trapezoidal estimate with twice the steps is the average of the
+/// previous trapezoidal estimate and the midpoint estimate with the same
+/// steps, which is how the evaluations are reused
+fn romberg_tableau<T, V>(
+    lower: f64,
+    upper: f64,
+    max_levels: u32,
+    cb: &T,
+    trap_cb: fn(f64, f64, u32, &T) -> Result<V, SummationError>,
+    mid_cb: fn(f64, f64, u32, &T) -> Result<V, SummationError>,
+) -> Result<V, SummationError>
+where
+    T: ?Sized,
+    V: Sample,
+{
+    let mut previous = vec![trap_cb(lower, upper, 1, cb)?];
+
+    for level in 1..=max_levels {
+        let steps = 1u32 << (level - 1);
+        let mut row = Vec::with_capacity(previous.len() + 1);
+
+        row.push((previous[0] + mid_cb(lower, upper, steps, cb)?) / 2.0);
+
+        for column in 1..=level as usize {
+            let factor = 4f64.powi(column as i32);
+            let extrapolated = (row[column - 1] * factor - previous[column - 1]) / (factor - 1.0);
+
+            row.push(extrapolated);
+        }
+
+        previous = row;
+    }
+
+    Ok(previous[previous.len() - 1])
+}
+
+/// performs [`romberg`] with the levels of the callable, see
+/// [`Callable::romberg_levels`], so it can be used like the other summation
+/// functions
+///
+/// the iterations are ignored since the amount of evaluations is decided by
+/// the levels
+pub fn romberg_panel<T, V>(
+    lower: f64,
+    upper: f64,
+    _iterations: u32,
+    cb: &T,
+) -> Result<V, SummationError>
+where
+    T: Callable<f64, V> + ?Sized,
+    V: Sample,
+{
+    romberg(lower, upper, cb.romberg_levels(), cb)
+}
+
+/// same as [`romberg_panel`] but uses [`romberg_compensated`]
+pub fn romberg_panel_compensated<T>(
+    lower: f64,
+    upper: f64,
+    _iterations: u32,
+    cb: &T,
+) -> Result<f64, SummationError>
+where
+    T: Callable<f64> + ?Sized,
+{
+    romberg_compensated(lower, upper, cb.romberg_levels(), cb)
+}
+
+/// the nodes and weights of 5 point gauss-legendre quadrature on `[-1, 1]`
+pub const GAUSS_LEGENDRE_5: [(f64, f64); 5] = [
+    (-0.906_179_845_938_664, 0.236_926_885_056_189_1),
+    (-0.538_469_310_105_683_1, 0.478_628_670_499_366_5),
+    (0.0, 0.568_888_888_888_888_9),
+    (0.538_469_310_105_683_1, 0.478_628_670_499_366_5),
+    (0.906_179_845_938_664, 0.236_926_885_056_189_1),
+];
+
+/// performs 5 point gauss-legendre quadrature on each of the steps between
+/// lower and upper with the given callable
+///
+/// exact for polynomials up to degree 9 on each step
+pub fn gauss_legendre<T, V>(
+    lower: f64,
+    upper: f64,
+    iterations: u32,
+    cb: &T,
+) -> Result<V, SummationError>
+where
+    T: Callable<f64, V> + ?Sized,
+    V: Sample,
+{
+    custom_quadrature(&GAUSS_LEGENDRE_5, lower, upper, iterations, cb)
+}
+
+/// same as [`cumulative_panels`] but each unit interval is calculated with
+/// [`custom_quadrature`] and the given rule
+pub fn cumulative_custom_quadrature<T, V>(
+    length: usize,
+    step: u32,
+    rule: &[(f64, f64)],
+    cb: &T,
+) -> Result<InterpolateLookup<V>, SummationError>
+where
+    T: Callable<f64, V> + ?Sized,
+    V: Sample,
+{
+    let mut rtn = InterpolateLookup::from(Vec::with_capacity(length));
+    let mut rolling = V::default();
+    rtn.push(rolling);
+
+    for sec in 1..length {
+        rolling += custom_quadrature(rule, (sec - 1) as f64, sec as f64, step, cb)?;
+
+        rtn.push(rolling);
+    }
+
+    Ok(rtn)
+}
+
+/// compensated summation that tracks the low order bits lost when adding
+/// floating point values together
+///
+/// this is the classic Kahan summation which assumes the running sum is larger
+/// than the values being added, see [`NeumaierSum`] for when that is not the
+/// case
+///
+/// ```
+/// use train_sim::summation::KahanSum;
+///
+/// let mut sum = KahanSum::new(1.0);
+///
+/// for _ in 0..10 {
+///     sum.add(0.1);
+/// }
+///
+/// assert_eq!(sum.value(), 2.0);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct KahanSum {
+    sum: f64,
+    compensation: f64,
+}
+
+impl KahanSum {
+    /// creates a sum starting at the given value
+    pub fn new(start: f64) -> Self {
+        KahanSum {
+            sum: start,
+            compensation: 0.0,
+        }
+    }
+
+    /// adds the given value to the running sum
+    pub fn add(&mut self, given: f64) {
+        let adjusted = given - self.compensation;
+        let total = self.sum + adjusted;
+
+        self.compensation = (total - self.sum) - adjusted;
+        self.sum = total;
+    }
+
+    /// returns the compensated sum of all values added
+    pub fn value(&self) -> f64 {
+        self.sum
+    }
+}
+
+/// compensated summation that tracks the low order bits lost when adding
+/// floating point values together
+///
+/// uses the Neumaier variant of Kahan summation which also handles the case of
+/// the value being added being larger than the running sum
+///
+/// ```
+/// use train_sim::summation::NeumaierSum;
+///
+/// let mut sum = NeumaierSum::default();
+///
+/// for _ in 0..10 {
+///     sum.add(0.1);
+/// }
+///
+/// assert_eq!(sum.value(), 1.0);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct NeumaierSum {
+    sum: f64,
+    compensation: f64,
+}
+
+impl NeumaierSum {
+    /// adds the given value to the running sum
+    pub fn add(&mut self, given: f64) {
+        let total = self.sum + given;
+
+        if self.sum.abs() >= given.abs() {
+            self.compensation += (self.sum - total) + given;
+        } else {
+            self.compensation += (given - total) + self.sum;
+        }
+
+        self.sum = total;
+    }
+
+    /// returns the compensated sum of all values added
+    pub fn value(&self) -> f64 {
+        self.sum + self.compensation
+    }
+}
+
+/// performs a left riemann summation with the given callable using
+/// compensated summation
+pub fn left_riemann_compensated<T>(
+    lower: f64,
+    upper: f64,
+    iterations: u32,
+    cb: &T,
+) -> Result<f64, SummationError>
+where
+    T: Callable<f64> + ?Sized,
+{
+    check_iterations(iterations)?;
+
+    let step = (upper - lower) / (iterations as f64);
+    let mut sum = NeumaierSum::default();
+
+    for iter in 0..iterations {
+        let i = iter as f64;
+        let x = lower + i * step;
+
+        sum.add(cb.call(x));
+    }
+
+    Ok(sum.value() * step)
+}
+
+/// performs a midpoint riemann summation with the given callable using
+/// compensated summation
+pub fn mid_riemann_compensated<T>(
+    lower: f64,
+    upper: f64,
+    iterations: u32,
+    cb: &T,
+) -> Result<f64, SummationError>
+where
+    T: Callable<f64> + ?Sized,
+{
+    check_iterations(iterations)?;
+
+    let step = (upper - lower) / (iterations as f64);
+    let half = step / 2.0;
+    let mut sum = NeumaierSum::default();
+
+    for iter in 0..iterations {
+        let i = iter as f64;
+        let x = (lower + i * step) + half;
+
+        sum.add(cb.call(x));
+    }
+
+    Ok(sum.value() * step)
+}
+
+/// performs a right riemann summation with the given callable using
+/// compensated summation
+pub fn right_riemann_compensated<T>(
+    lower: f64,
+    upper: f64,
+    iterations: u32,
+    cb: &T,
+) -> Result<f64, SummationError>
+where
+    T: Callable<f64> + ?Sized,
+{
+    check_iterations(iterations)?;
+
+    let step = (upper - lower) / (iterations as f64);
+    let mut sum = NeumaierSum::default();
+
+    for iter in 0..iterations {
+        let i = (iter + 1) as f64;
+        let x = lower + i * step;
+
+        sum.add(cb.call(x));
+    }
+
+    Ok(sum.value() * step)
+}
+
+/// performs a trapezoidal summation with the given callable using compensated
+/// summation
+pub fn trapezoidal_compensated<T>(
+    lower: f64,
+    upper: f64,
+    iterations: u32,
+    cb: &T,
+) -> Result<f64, SummationError>
+where
+    T: Callable<f64> + ?Sized,
+{
+    check_iterations(iterations)?;
+
+    let step = (upper - lower) / (iterations as f64);
+    let mut sum = NeumaierSum::default();
+    sum.add((cb.call(upper) + cb.call(lower)) / 2.0);
+
+    for iter in 1..iterations {
+        let i = iter as f64;
+        let x = lower + i * step;
+
+        sum.add(cb.call(x));
+    }
+
+    Ok(sum.value() * step)
+}
+
+/// performs simpsons summation with the given callable using compensated
+/// summation
+pub fn simpsons_compensated<T>(
+    lower: f64,
+    upper: f64,
+    iterations: u32,
+    cb: &T,
+) -> Result<f64, SummationError>
+where
+    T: Callable<f64> + ?Sized,
+{
+    check_iterations(iterations)?;
+
+    let step = (upper - lower) / (iterations as f64);
+    let mut sum = NeumaierSum::default();
+
+    for iter in 0..=iterations {
+        let i = iter as f64;
+        let x = lower + i * step;
+        let res = cb.call(x);
+
+        sum.add(simpsons_weight(iter, iterations) * res);
+    }
+
+    Ok(step * sum.value() / 3.0)
+}
+
+/// performs tanh-sinh quadrature with the given callable using compensated
+/// summation
+pub fn tanh_sinh_compensated<T>(
+    lower: f64,
+    upper: f64,
+    iterations: u32,
+    cb: &T,
+) -> Result<f64, SummationError>
+where
+    T: Callable<f64> + ?Sized,
+{
+    let mut sum = NeumaierSum::default();
+
+    for (x, weight) in tanh_sinh_nodes(lower, upper, iterations)? {
+        sum.add(cb.call(x) * weight);
+    }
+
+    Ok(sum.value())
+}
+
+/// performs gauss-legendre quadrature using compensated summation with the
+/// given callable
+pub fn gauss_legendre_compensated<T>(
+    lower: f64,
+    upper: f64,
+    iterations: u32,
+    cb: &T,
+) -> Result<f64, SummationError>
+where
+    T: Callable<f64> + ?Sized,
+{
+    check_iterations(iterations)?;
+
+    let step = (upper - lower) / (iterations as f64);
+    let half = step / 2.0;
+    let mut sum = NeumaierSum::default();
+
+    for iter in 0..iterations {
+        let center = lower + iter as f64 * step + half;
+
+        for (node, weight) in GAUSS_LEGENDRE_5 {
+            sum.add(cb.call(center + node * half) * weight);
+        }
+    }
+
+    Ok(sum.value() * half)
+}
+
+#[cfg(test)]
+mod test {
+    use approx::assert_relative_eq;
+
+    use super::*;
+
+    fn simple_curve(x: f64) -> f64 {
+        4.0 * x - x * x
+    }
+
+    #[test]
+    fn left() {
+        let calc = left_riemann(0.0, 4.0, 4, &simple_curve).unwrap();
+
+        assert_relative_eq!(calc, 10.0);
+    }
+
+    #[test]
+    fn mid() {
+        let calc = mid_riemann(0.0, 4.0, 4, &simple_curve).unwrap();
+
+        assert_relative_eq!(calc, 11.0);
+    }
+
+    #[test]
+    fn right() {
+        let calc = right_riemann(0.0, 4.0, 4, &simple_curve).unwrap();
+
+        assert_relative_eq!(calc, 10.0);
+    }
+
+    #[test]
+    fn trap() {
+        let calc = trapezoidal(0.0, 4.0, 4, &simple_curve).unwrap();
+
+        assert_relative_eq!(calc, 10.0);
+    }
+
+    #[test]
+    fn trap_asymmetric() {
+        // counting the lower bound twice and skipping the upper bound would be
+        // off by about (e - 1) / 2 * step instead of the expected error of
+        // (e - 1) / 12 * step^2
+        let exact = std::f64::consts::E - 1.0;
+        let expected_error = exact / 12.0 * 0.01 * 0.01;
+
+        let calc: f64 = trapezoidal(0.0, 1.0, 100, &|x: f64| x.exp()).unwrap();
+        let compensated = trapezoidal_compensated(0.0, 1.0, 100, &|x: f64| x.exp()).unwrap();
+
+        assert_relative_eq!(calc - exact, expected_error, max_relative = 1e-3);
+        assert_relative_eq!(compensated - exact, expected_error, max_relative = 1e-3);
+    }
+
+    #[test]
+    fn interpolate() {
+        let lookup = InterpolateLookup::from(vec![0.0, 1.0, 2.0]);
+
+        assert_relative_eq!(lookup.call(0.0), 0.0);
+        assert_relative_eq!(lookup.call(1.0), 1.0);
+        assert_relative_eq!(lookup.call(0.5), 0.5);
+        assert_relative_eq!(lookup.call(1.5), 1.5);
+    }
+
+    #[test]
+    fn l2_constant() {
+        let lookup = InterpolateLookup::from(vec![-3.0; 11]);
+        let calc = l2_norm(lookup.len(), 10, &lookup, trapezoidal).unwrap();
+
+        assert_relative_eq!(calc, 3.0 * 10.0f64.sqrt(), epsilon = 1e-12);
+    }
+
+    fn tenth(_x: f64) -> f64 {
+        0.1
+    }
+
+    #[test]
+    fn neumaier() {
+        let mut naive = 0.0f64;
+        let mut sum = NeumaierSum::default();
+
+        for _ in 0..10 {
+            naive += 0.1;
+            sum.add(0.1);
+        }
+
+        assert_ne!(naive, 1.0);
+        assert_eq!(sum.value(), 1.0);
+    }
+
+    #[test]
+    fn kahan() {
+        // the spacing between floats at 1e16 is 2 so a naive sum rounds
+        // every 1.0 away
+        let mut naive = 1e16f64;
+        let mut sum = KahanSum::new(1e16);
+
+        for _ in 0..10_000 {
+            naive += 1.0;
+            sum.add(1.0);
+        }
+
+        assert_eq!(naive, 1e16);
+        assert_eq!(sum.value(), 1e16 + 10_000.0);
+    }
+
+    #[test]
+    fn rayon_matches_sequential() {
+        let lookup = InterpolateLookup::from(
+            (0..1000)
+                .map(|x| (x as f64 * 0.37).sin() * 1e3 + 0.1)
+                .collect::<Vec<f64>>(),
+        );
+        let sequential = sum_panels_compensated(lookup.len(), 7, &lookup, simpsons).unwrap();
+
+        for repeatable in [false, true] {
+            let calc = sum_panels_rayon(lookup.len(), 7, &lookup, simpsons, repeatable).unwrap();
+
+            assert_eq!(calc.to_bits(), sequential.to_bits());
+        }
+    }
+
+    #[test]
+    fn compensated() {
+        let iterations = 1_000_000;
+        let naive = (mid_riemann(0.0, 1.0, iterations, &tenth).unwrap() - 0.1).abs();
+        let calc = (mid_riemann_compensated(0.0, 1.0, iterations, &tenth).unwrap() - 0.1).abs();
+
+        assert!(calc < naive, "compensated: {calc} naive: {naive}");
+    }
+
+    #[test]
+    fn channels() {
+        let lookup = InterpolateLookup::from(vec![Channels([1.0, -2.0]); 11]);
+        let calc = sum_panels(lookup.len(), 10, &lookup, trapezoidal).unwrap();
+
+        assert_relative_eq!(calc.0[0], 10.0, epsilon = 1e-12);
+        assert_relative_eq!(calc.0[1], -20.0, epsilon = 1e-12);
+
+        let half = lookup.call(0.5);
+
+        assert_eq!(half, Channels([1.0, -2.0]));
+    }
+
+    #[test]
+    fn into_inner() {
+        let values = vec![0.0, 1.5, -3.0, 4.25];
+        let lookup = InterpolateLookup::from(values.clone());
+
+        assert_eq!(lookup.to_vec(), values);
+        assert_eq!(lookup.into_inner(), values);
+    }
+
+    #[test]
+    fn from_iter_and_slice() {
+        let values = [0.0, 1.5, -3.0, 4.25];
+        let collected = values.iter().copied().collect::<InterpolateLookup>();
+        let sliced = InterpolateLookup::from(values.as_slice());
+
+        assert_eq!(collected.to_vec(), values);
+        assert_eq!(sliced.to_vec(), values);
+        assert_eq!(sliced.dx(), 1.0);
+        assert_eq!(sliced.call(0.5), 0.75);
+    }
+
+    #[test]
+    fn append() {
+        let mut profile = InterpolateLookup::with_spacing(vec![0.0, 1.0, 2.0], 0.5);
+        profile
+            .append(&InterpolateLookup::with_spacing(vec![4.0, 2.0], 0.5))
+            .unwrap();
+
+        assert_eq!(profile.len(), 5);
+        assert_eq!(profile.call(1.25), 3.0);
+        assert_eq!(profile.call(2.0), 2.0);
+
+        let mut pairs = InterpolateLookup::from_pairs(vec![(0.0, 1.0), (2.0, 3.0)]);
+        pairs
+            .append(&InterpolateLookup::from_pairs(vec![(1.0, 5.0), (4.0, 2.0)]))
+            .unwrap();
+
+        assert_eq!(pairs.xs(), Some([0.0, 2.0, 3.0, 6.0].as_slice()));
+        assert_eq!(pairs.call(2.5), 4.0);
+    }
+
+    #[test]
+    fn append_pairs() {
+        // the braking phase starts at zero so its first pair is the seam
+        let mut pairs = InterpolateLookup::from_pairs(vec![(0.0, 1.0), (2.0, 3.0)]);
+        pairs
+            .append(&InterpolateLookup::from_pairs(vec![
+                (0.0, 3.0),
+                (1.0, -1.0),
+                (3.0, -2.0),
+            ]))
+            .unwrap();
+
+        assert_eq!(pairs.xs(), Some([0.0, 2.0, 3.0, 5.0].as_slice()));
+        assert_eq!(pairs.to_vec(), vec![1.0, 3.0, -1.0, -2.0]);
+        assert_eq!(pairs.call(2.5), 1.0);
+
+        let mut empty = InterpolateLookup::from_pairs(vec![]);
+        empty
+            .append(&InterpolateLookup::from_pairs(vec![(0.0, 1.0), (1.0, 2.0)]))
+            .unwrap();
+
+        assert_eq!(empty.xs(), Some([0.0, 1.0].as_slice()));
+
+        let before = pairs.clone();
+
+        assert_eq!(
+            pairs.append(&InterpolateLookup::from_pairs(vec![
+                (-1.0, 0.0),
+                (1.0, 0.0)
+            ])),
+            Err(LookupError::NotIncreasing)
+        );
+        assert_eq!(
+            pairs.append(&InterpolateLookup::from(vec![0.0])),
+            Err(LookupError::Mixed)
+        );
+        assert_eq!(pairs.xs(), before.xs());
+        assert_eq!(pairs.to_vec(), before.to_vec());
+    }
+
+    #[test]
+    fn append_spacing() {
+        let mut profile = InterpolateLookup::from(vec![0.0, 1.0]);
+
+        assert_eq!(
+            profile.append(&InterpolateLookup::with_spacing(vec![2.0], 0.5)),
+            Err(LookupError::Spacing)
+        );
+        assert_eq!(profile.len(), 2);
+    }
+
+    #[test]
+    fn single_and_double() {
+        let double = (0..11)
+            .map(|x| 0.5 * x as f64)
+            .collect::<InterpolateLookup>();
+        let single = double
+            .iter()
+            .map(|value| Single(value as f32))
+            .collect::<InterpolateLookup<Single>>();
+
+        assert_relative_eq!(
+            sum_panels(double.len(), 4, &double, trapezoidal).unwrap(),
+            25.0
+        );
+        assert_relative_eq!(
+            sum_panels(double.len(), 4, &double, simpsons).unwrap(),
+            25.0
+        );
+        assert_relative_eq!(
+            sum_panels(single.len(), 4, &single, trapezoidal).unwrap().0,
+            25.0
+        );
+        assert_relative_eq!(
+            sum_panels(single.len(), 4, &single, simpsons).unwrap().0,
+            25.0
+        );
+
+        let cumulative = cumulative_panels(single.len(), 4, &single, trapezoidal).unwrap();
+
+        assert_eq!(cumulative.last(), Some(Single(25.0)));
+        assert_eq!(cumulative.call(2.0), Single(1.0));
+    }
+
+    #[test]
+    fn iter() {
+        let empty = InterpolateLookup::<f64>::from(Vec::new());
+
+        assert!(empty.is_empty());
+        assert_eq!(empty.iter().next(), None);
+
+        let lookup = InterpolateLookup::from(vec![0.5, -1.0, 2.0]);
+
+        assert!(!lookup.is_empty());
+        assert_eq!(lookup.iter().collect::<Vec<f64>>(), lookup.to_vec());
+        assert_eq!(lookup.iter().len(), 3);
+    }
+
+    #[test]
+    fn clear_keeps_capacity() {
+        let mut lookup = InterpolateLookup::from(Vec::with_capacity(32));
+
+        for value in 0..32 {
+            lookup.push(value as f64);
+        }
+
+        lookup.clear();
+
+        assert!(lookup.is_empty());
+        assert_eq!(lookup.capacity(), 32);
+
+        lookup.push(1.0);
+        lookup.push(3.0);
+
+        assert_eq!(lookup.call(0.5), 2.0);
+        assert_eq!(lookup.capacity(), 32);
+    }
+
+    fn step_down(x: f64) -> f64 {
+        if x < 5.0 {
+            2.0
         } else {
-            sum += 2.0 * res;
+            -2.0
         }
     }
 
-    step * sum / 3.0
-}
+    #[test]
+    fn abs_integral() {
+        let signed = sum_panels(11, 10, &step_down, mid_riemann).unwrap();
+        let abs = sum_panels(11, 10, &AbsCallable::new(&step_down), mid_riemann).unwrap();
 
-#[cfg(test)]
-mod test {
-    use approx::assert_relative_eq;
+        assert_relative_eq!(signed, 0.0, epsilon = 1e-12);
+        assert_relative_eq!(abs, 2.0 * 2.0 * 5.0, epsilon = 1e-12);
+    }
 
-    use super::*;
+    #[test]
+    fn unchecked() {
+        let lookup = (0..50)
+            .map(|x| simple_curve(x as f64 / 10.0))
+            .collect::<Vec<f64>>();
+        let lookup = InterpolateLookup::from(lookup);
+        let unchecked = UncheckedLookup::new(&lookup).unwrap();
 
-    fn simple_curve(x: f64) -> f64 {
-        4.0 * x - x * x
+        for (checked, calc) in [
+            (
+                sum_panels(lookup.len(), 10, &lookup, left_riemann).unwrap(),
+                sum_panels(lookup.len(), 10, &unchecked, left_riemann).unwrap(),
+            ),
+            (
+                sum_panels(lookup.len(), 10, &lookup, mid_riemann).unwrap(),
+                sum_panels(lookup.len(), 10, &unchecked, mid_riemann).unwrap(),
+            ),
+            (
+                sum_panels(lookup.len(), 10, &lookup, right_riemann).unwrap(),
+                sum_panels(lookup.len(), 10, &unchecked, right_riemann).unwrap(),
+            ),
+            (
+                sum_panels(lookup.len(), 10, &lookup, trapezoidal).unwrap(),
+                sum_panels(lookup.len(), 10, &unchecked, trapezoidal).unwrap(),
+            ),
+            (
+                sum_panels(lookup.len(), 10, &lookup, simpsons).unwrap(),
+                sum_panels(lookup.len(), 10, &unchecked, simpsons).unwrap(),
+            ),
+        ] {
+            assert_eq!(checked.to_bits(), calc.to_bits());
+        }
+
+        let pairs = InterpolateLookup::from_pairs(vec![(0.0, 1.0), (2.0, 3.0)]);
+
+        assert_eq!(
+            UncheckedLookup::new(&InterpolateLookup::<f64>::from(vec![])).unwrap_err(),
+            LookupError::Empty
+        );
+        assert_eq!(
+            UncheckedLookup::new(&pairs).unwrap_err(),
+            LookupError::Pairs
+        );
+
+        assert_relative_eq!(unchecked.call(-1.0), lookup.call(0.0));
+        assert_relative_eq!(unchecked.call(100.5), lookup.call(49.0));
     }
 
     #[test]
-    fn left() {
-        let calc = left_riemann(0.0, 4.0, 4, &simple_curve);
+    fn time_weighted() {
+        let values = vec![0.0, 0.0, 10.0];
+        let mean = values.iter().sum::<f64>() / values.len() as f64;
+        let lookup = InterpolateLookup::from(values);
+        let calc = time_average(lookup.len(), 10, &lookup, trapezoidal).unwrap();
 
-        assert_relative_eq!(calc, 10.0);
+        assert_relative_eq!(calc, 2.5, epsilon = 1e-12);
+        assert!((calc - mean).abs() > 0.5);
     }
 
     #[test]
-    fn mid() {
-        let calc = mid_riemann(0.0, 4.0, 4, &simple_curve);
+    fn time_weighted_intervals() {
+        // a short burst followed by a long coast weighs the coast more than
+        // the simple mean of the samples does
+        let times = [1.0, 1.5, 2.0, 6.0];
+        let values = [4.0, 4.0, 1.0, 1.0];
+        let mean = values.iter().sum::<f64>() / values.len() as f64;
+        let lookup = InterpolateLookup::from_pairs(times.into_iter().zip(values).collect());
+        let calc = time_average_intervals(&times, 10, &lookup, trapezoidal).unwrap();
 
-        assert_relative_eq!(calc, 11.0);
+        // 2 + 1.25 + 4 over 5 seconds
+        assert_relative_eq!(calc, 1.45, epsilon = 1e-12);
+        assert!((calc - mean).abs() > 0.5);
+
+        assert_eq!(
+            time_average_intervals(&[2.0], 10, &lookup, trapezoidal).unwrap(),
+            0.0
+        );
     }
 
     #[test]
-    fn right() {
-        let calc = right_riemann(0.0, 4.0, 4, &simple_curve);
+    fn shifted_onset() {
+        let lookup =
+            InterpolateLookup::from(vec![0.0, 0.0, 0.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0]);
+        let shifted = ShiftedCallable::new(&lookup, 3.0, ShiftFill::Zero);
 
-        assert_relative_eq!(calc, 10.0);
+        let vel = cumulative_panels(lookup.len(), 10, &lookup, trapezoidal).unwrap();
+        let calc = cumulative_panels(lookup.len(), 10, &shifted, trapezoidal).unwrap();
+
+        for sec in 0..lookup.len() {
+            let expected = if sec < 3 {
+                0.0
+            } else {
+                vel.get_index((sec - 3) as f64)
+            };
+
+            assert_relative_eq!(calc.get_index(sec as f64), expected, epsilon = 1e-12);
+        }
+
+        // the velocity does not start increasing until after the shifted step
+        assert_eq!(calc.get_index(5.0), 0.0);
+        assert!(calc.get_index(6.0) > 0.0);
     }
 
     #[test]
-    fn trap() {
-        let calc = trapezoidal(0.0, 4.0, 4, &simple_curve);
+    fn diff_stats() {
+        let a = InterpolateLookup::from(vec![0.0, 1.0, 2.0, 3.0]);
+        let b = InterpolateLookup::from(vec![0.0, 1.1, 1.7, 3.0]);
+        let stats = a.diff_stats(&b);
 
-        assert_relative_eq!(calc, 10.0);
+        assert_eq!(stats.max_index, 2);
+        assert_relative_eq!(stats.max, 0.3, epsilon = 1e-12);
+        assert_relative_eq!(stats.rms, (0.1f64 / 4.0).sqrt(), epsilon = 1e-12);
+        assert_eq!(a.diff_stats(&a), DiffStats::default());
     }
 
     #[test]
-    fn interpolate() {
+    fn derivative_ramp() {
+        let velocity =
+            InterpolateLookup::from((0..10).map(|x| 3.0 * x as f64 + 1.0).collect::<Vec<f64>>());
+        let accel = velocity.derivative();
+
+        assert_eq!(accel.len(), velocity.len());
+
+        for value in accel.to_vec() {
+            assert_relative_eq!(value, 3.0);
+        }
+
+        assert_relative_eq!(accel.min().unwrap(), 3.0);
+        assert_relative_eq!(accel.max().unwrap(), 3.0);
+        assert_relative_eq!(accel.mean().unwrap(), 3.0);
+    }
+
+    #[test]
+    fn derivative_spacing() {
+        let velocity = (0..10)
+            .map(|x| 3.0 * x as f64 + 1.0)
+            .collect::<InterpolateLookup>();
+        let spaced = InterpolateLookup::with_spacing(velocity.to_vec(), 0.5);
+        let accel = spaced.derivative();
+
+        assert_eq!(accel.dx(), 0.5);
+
+        for value in accel.to_vec() {
+            assert_relative_eq!(value, 6.0);
+        }
+
+        // integrating the derivative recovers the ramp
+        let accel = velocity.derivative();
+        let integrated = cumulative_panels(accel.len(), 10, &accel, trapezoidal).unwrap();
+
+        for (value, expected) in integrated.to_vec().into_iter().zip(velocity.to_vec()) {
+            assert_relative_eq!(value + 1.0, expected, epsilon = 1e-9);
+        }
+    }
+
+    #[test]
+    fn resample() {
+        let lookup = InterpolateLookup::with_spacing(vec![1.0, 3.0, 2.0, 5.0], 0.5);
+
+        let down = lookup.resample(2);
+        assert_eq!(down.xs(), Some([0.0, 1.5].as_slice()));
+        assert_eq!(down.to_vec(), vec![1.0, 5.0]);
+
+        let up = lookup.resample(7);
+        assert_eq!(up.len(), 7);
+        assert_eq!(up.xs().unwrap()[6], 1.5);
+        assert_eq!(up.to_vec(), vec![1.0, 2.0, 3.0, 2.5, 2.0, 3.5, 5.0]);
+
+        let single = lookup.resample(1);
+        assert_eq!(single.xs(), Some([0.0].as_slice()));
+        assert_eq!(single.to_vec(), vec![1.0]);
+
+        assert!(InterpolateLookup::from(Vec::new()).resample(4).is_empty());
+    }
+
+    #[test]
+    fn find_x() {
+        let ramp = (0..10)
+            .map(|x| 3.0 * x as f64)
+            .collect::<InterpolateLookup>();
+
+        assert_eq!(ramp.find_x(0.0), Some(0.0));
+        assert_eq!(ramp.find_x(12.0), Some(4.0));
+        assert_eq!(ramp.find_x(27.0), Some(9.0));
+        assert_relative_eq!(ramp.find_x(13.5).unwrap(), 4.5, epsilon = 1e-12);
+        assert_eq!(ramp.find_x(-0.5), None);
+        assert_eq!(ramp.find_x(27.5), None);
+        assert_eq!(ramp.find_x(f64::NAN), None);
+
+        let braking = InterpolateLookup::with_spacing(vec![30.0, 20.0, 10.0, 0.0], 2.0);
+
+        assert_relative_eq!(braking.find_x(15.0).unwrap(), 3.0, epsilon = 1e-12);
+        assert_eq!(braking.find_x(10.0), Some(4.0));
+        assert_eq!(InterpolateLookup::from(Vec::new()).find_x(0.0), None);
+    }
+
+    #[test]
+    fn integrate_rect() {
+        let lookup = (0..5).map(|x| simple_curve(x as f64)).collect::<Vec<f64>>();
+        let lookup = InterpolateLookup::from(lookup);
+
+        assert_eq!(
+            lookup.integrate_rect(0.0, 4.0, 100).unwrap(),
+            mid_riemann(0.0, 4.0, 100, &lookup).unwrap()
+        );
+        assert_eq!(
+            simple_curve.integrate_rect(0.0, 4.0, 4).unwrap(),
+            mid_riemann(0.0, 4.0, 4, &simple_curve).unwrap()
+        );
+    }
+
+    #[test]
+    fn adaptive_panels() {
+        use std::cell::Cell;
+        use std::rc::Rc;
+
+        // mostly flat with a single narrow peak at 10 seconds
+        let peak = |x: f64| (-((x - 10.0) / 0.3).powi(2)).exp();
+        let expected = 0.3 * std::f64::consts::PI.sqrt();
+
+        let evaluations = Rc::new(Cell::new(0u32));
+        let counter = Rc::clone(&evaluations);
+        let counted = move |x: f64| {
+            counter.set(counter.get() + 1);
+            peak(x)
+        };
+
+        let samples = (0..=20).map(|x| peak(x as f64)).collect::<Vec<f64>>();
+        let samples = InterpolateLookup::from(samples);
+
+        let steps = curvature_steps(&samples, 2, 64);
+
+        assert_eq!(steps.len(), 20);
+        assert_eq!(steps[0], 2);
+        assert_eq!(steps[9], 64);
+        assert_eq!(steps[10], 64);
+
+        let adaptive = sum_adaptive_panels(&steps, &counted, simpsons).unwrap();
+        let adaptive_evals = evaluations.replace(0);
+
+        let uniform = sum_panels(21, 64, &counted, simpsons).unwrap();
+        let uniform_evals = evaluations.replace(0);
+
+        assert_relative_eq!(adaptive, expected, epsilon = 1e-6);
+        assert_relative_eq!(uniform, expected, epsilon = 1e-6);
+        assert!(adaptive_evals * 3 < uniform_evals);
+
+        let cumulative = cumulative_adaptive_panels(&steps, &counted, simpsons).unwrap();
+
+        assert_eq!(cumulative.len(), 21);
+        assert_relative_eq!(cumulative.get_index(20.0), adaptive);
+    }
+
+    #[test]
+    fn step_lookup() {
+        let lookup = InterpolateLookup::from(vec![1.0, 3.0, 2.0]);
+        let step = StepLookup::new(&lookup);
+
+        assert_eq!(step.call(0.0), 1.0);
+        assert_eq!(step.call(0.9), 1.0);
+        assert_eq!(step.call(1.5), 3.0);
+        assert_eq!(step.call(2.0), 2.0);
+        assert_relative_eq!(left_riemann(0.0, 2.0, 10, &step).unwrap(), 4.0);
+    }
+
+    #[test]
+    fn fourier_sine() {
+        let term = FourierTerm {
+            amplitude: 3.0,
+            frequency: 0.1,
+            phase: 0.5,
+        };
+        let series = FourierSeries::new(vec![term]);
+
+        // integral of a * sin(2 pi f x + p) from 0 to 7
+        let omega = std::f64::consts::TAU * term.frequency;
+        let expected =
+            term.amplitude / omega * (term.phase.cos() - (omega * 7.0 + term.phase).cos());
+
+        let calc = sum_panels(8, 100, &series, simpsons).unwrap();
+
+        assert_relative_eq!(calc, expected, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn capped_velocity() {
+        // accelerates at 1 for 11 seconds and then decelerates at 1 for 1
+        let mut accel = vec![1.0; 11];
+        accel.extend([-1.0, -1.0]);
+        let accel = InterpolateLookup::from(accel);
+        let accel = StepLookup::new(&accel);
+
+        let uncapped = cumulative_panels(13, 10, &accel, left_riemann).unwrap();
+        let capped =
+            cumulative_panels_clamped(13, 10, &accel, left_riemann, f64::NEG_INFINITY, 4.0)
+                .unwrap();
+
+        assert_relative_eq!(uncapped.get_index(10.0), 10.0);
+        assert_relative_eq!(capped.get_index(3.0), 3.0);
+        assert_relative_eq!(capped.get_index(4.0), 4.0);
+        assert_relative_eq!(capped.get_index(10.0), 4.0);
+        assert_relative_eq!(capped.get_index(12.0), 3.0);
+
+        let uncapped_position = sum_panels(13, 10, &uncapped, trapezoidal).unwrap();
+        let capped_position = sum_panels(13, 10, &capped, trapezoidal).unwrap();
+
+        // ramps up to 4 over 4 seconds, holds for 7, then ramps down to 3
+        assert_relative_eq!(capped_position, 8.0 + 28.0 + 3.5);
+        assert!(capped_position < uncapped_position);
+    }
+
+    #[test]
+    fn work_constant_force() {
+        let mass = 3.0;
+        let accel = InterpolateLookup::from(vec![2.0; 5]);
+        let vel = cumulative_panels(accel.len(), 10, &accel, simpsons).unwrap();
+        let distance = sum_panels(accel.len(), 10, &vel, simpsons).unwrap();
+
+        let power = ProductCallable::new(&accel, &vel);
+        let work = mass * sum_panels(accel.len(), 10, &power, simpsons).unwrap();
+
+        assert_relative_eq!(distance, 16.0, epsilon = 1e-9);
+        assert_relative_eq!(work, mass * 2.0 * distance, epsilon = 1e-9);
+    }
+
+    #[test]
+    fn tanh_sinh_singularity() {
+        // 1 / sqrt(x) is infinite at 0 so the rules that evaluate the bounds
+        // are given 0 instead
+        let singular = |x: f64| if x > 0.0 { x.powf(-0.5) } else { 0.0 };
+
+        let trap = trapezoidal(0.0, 1.0, 40, &singular).unwrap();
+        let tanh = tanh_sinh(0.0, 1.0, 40, &singular).unwrap();
+        let compensated = tanh_sinh_compensated(0.0, 1.0, 40, &singular).unwrap();
+
+        assert!((tanh - 2.0).abs() < 1e-8, "tanh-sinh: {tanh}");
+        assert!((tanh - 2.0).abs() < (trap - 2.0).abs());
+        assert_relative_eq!(compensated, tanh, epsilon = 1e-12);
+
+        // the nodes should never land on the bounds
+        assert!(tanh_sinh_nodes(0.0, 1.0, 40)
+            .unwrap()
+            .all(|(x, _)| x > 0.0 && x < 1.0));
+        assert_relative_eq!(
+            tanh_sinh(0.0, 4.0, 40, &simple_curve).unwrap(),
+            32.0 / 3.0,
+            epsilon = 1e-9
+        );
+    }
+
+    #[test]
+    fn abs_jerk_ramp() {
+        // acceleration decreasing by 0.5 every second for 10 seconds
+        let accel = (0..=10)
+            .map(|sec| 2.0 - 0.5 * sec as f64)
+            .collect::<Vec<f64>>();
+        let accel = InterpolateLookup::from(accel);
+        let jerk = accel.derivative();
+
+        assert!(jerk.to_vec().iter().all(|value| *value == -0.5));
+
+        let abs_jerk = sum_panels(accel.len(), 10, &AbsCallable::new(&jerk), simpsons).unwrap();
+
+        assert_relative_eq!(abs_jerk, 0.5 * 10.0, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn clamped_nonneg_velocity() {
+        // accelerates for 3 seconds and then brakes for 5
+        let mut accel = vec![1.0; 3];
+        accel.extend([-1.0; 6]);
+        let accel = InterpolateLookup::from(accel);
+        let accel = StepLookup::new(&accel);
+
+        let unclamped = cumulative_panels(9, 10, &accel, left_riemann).unwrap();
+        let clamped =
+            cumulative_panels_clamped(9, 10, &accel, left_riemann, 0.0, f64::INFINITY).unwrap();
+
+        assert_relative_eq!(unclamped.get_index(8.0), -2.0);
+        assert_relative_eq!(clamped.get_index(3.0), 3.0);
+        assert_relative_eq!(clamped.get_index(4.0), 2.0);
+        assert_eq!(clamped.get_index(7.0), 0.0);
+        assert_eq!(clamped.get_index(8.0), 0.0);
+        assert!(clamped.to_vec().iter().all(|value| *value >= 0.0));
+    }
+
+    #[test]
+    fn masked_constant() {
+        let lookup = InterpolateLookup::from(vec![1.0; 11]);
+        let mut mask = vec![true; 11];
+        mask[4] = false;
+        mask[5] = false;
+
+        let full = sum_panels(lookup.len(), 100, &lookup, trapezoidal).unwrap();
+        let masked = sum_panels(
+            lookup.len(),
+            100,
+            &MaskedCallable::new(&lookup, &mask),
+            trapezoidal,
+        )
+        .unwrap();
+
+        assert_relative_eq!(full, 10.0, epsilon = 1e-9);
+        assert_relative_eq!(masked, 8.0, epsilon = 1e-2);
+    }
+
+    #[test]
+    fn custom_gauss() {
+        let lookup = InterpolateLookup::from(vec![1.0, 3.0, 2.0, 0.5, 4.0, 1.0]);
+        let node = 1.0 / 3.0f64.sqrt();
+        let rule = [(-node, 1.0), (node, 1.0)];
+
+        // linear interpolation is exact for both, gauss only needs a single
+        // panel per unit interval
+        let gauss = cumulative_custom_quadrature(lookup.len(), 1, &rule, &lookup).unwrap();
+        let trap = cumulative_panels(lookup.len(), 1, &lookup, trapezoidal).unwrap();
+
+        for sec in 0..lookup.len() {
+            assert_relative_eq!(
+                gauss.get_index(sec as f64),
+                trap.get_index(sec as f64),
+                epsilon = 1e-12
+            );
+        }
+
+        // cubics are exact with 2 point gauss just like simpsons
+        let cube = |x: f64| x * x * x;
+        let custom: f64 = custom_quadrature(&rule, -1.0, 3.0, 4, &cube).unwrap();
+        let simpson: f64 = simpsons(-1.0, 3.0, 4, &cube).unwrap();
+
+        assert_relative_eq!(custom, 20.0, epsilon = 1e-12);
+        assert_relative_eq!(custom, simpson, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn zero_crossings() {
+        let lookup = InterpolateLookup::from(vec![3.0, 2.0, 1.0, -3.0, -1.0]);
+        let crossings = lookup.zero_crossings();
+
+        assert_eq!(crossings.len(), 1);
+        assert_relative_eq!(crossings[0], 2.25);
+        assert_relative_eq!(lookup.call(crossings[0]), 0.0);
+
+        let through_zero = InterpolateLookup::from(vec![-1.0, 0.0, 0.0, 2.0, 0.0, 1.0]);
+
+        assert_eq!(through_zero.zero_crossings(), vec![1.0]);
+
+        let spaced = InterpolateLookup::with_spacing(vec![3.0, 2.0, 1.0, -3.0, -1.0], 0.5);
+        let crossings = spaced.zero_crossings();
+
+        assert_eq!(crossings.len(), 1);
+        assert_relative_eq!(crossings[0], 1.125);
+        assert_relative_eq!(spaced.call(crossings[0]), 0.0);
+
+        let pairs = InterpolateLookup::from_pairs(vec![
+            (0.0, -1.0),
+            (2.0, 0.0),
+            (3.0, 0.0),
+            (7.0, 2.0),
+            (8.0, 1.0),
+            (12.0, -3.0),
+        ]);
+        let crossings = pairs.zero_crossings();
+
+        assert_eq!(crossings.len(), 2);
+        assert_eq!(crossings[0], 2.0);
+        assert_relative_eq!(crossings[1], 9.0);
+        assert_relative_eq!(pairs.call(crossings[1]), 0.0);
+    }
+
+    #[test]
+    fn lookup_spacing() {
+        let lookup = InterpolateLookup::with_spacing(vec![1.0, 3.0, 9.0], 2.0);
+
+        assert_eq!(lookup.dx(), 2.0);
+        assert_eq!(lookup.call(2.0), 3.0);
+        assert_eq!(lookup.call(4.0), 9.0);
+        assert_relative_eq!(lookup.call(1.0), 2.0);
+        assert_relative_eq!(lookup.call(3.0), 6.0);
+        assert_relative_eq!(lookup.call(3.5), 7.5);
+
+        let unchecked = UncheckedLookup::new(&lookup).unwrap();
+
+        assert_relative_eq!(unchecked.call(1.0), 2.0);
+        assert_relative_eq!(unchecked.call(3.0), 6.0);
+        assert_eq!(StepLookup::new(&lookup).call(3.5), 3.0);
+
+        assert_eq!(InterpolateLookup::from(vec![0.0, 1.0]).dx(), 1.0);
+    }
+
+    #[test]
+    fn lookup_pairs() {
+        let lookup = InterpolateLookup::from_pairs(vec![(0.0, 0.0), (3.0, 9.0), (10.0, 100.0)]);
+
+        assert_eq!(lookup.xs(), Some([0.0, 3.0, 10.0].as_slice()));
+        assert_eq!(lookup.len(), 3);
+        assert_eq!(lookup.get_index(1.0), 9.0);
+
+        assert_eq!(lookup.call(0.0), 0.0);
+        assert_eq!(lookup.call(3.0), 9.0);
+        assert_eq!(lookup.call(10.0), 100.0);
+        assert_relative_eq!(lookup.call(1.5), 4.5);
+        assert_relative_eq!(lookup.call(6.5), 54.5);
+
+        assert_eq!(StepLookup::new(&lookup).call(6.5), 9.0);
+        assert_eq!(StepLookup::new(&lookup).call(10.0), 100.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "outside of the lookup table")]
+    fn lookup_pairs_past_end() {
+        let lookup = InterpolateLookup::from_pairs(vec![(0.0, 0.0), (3.0, 9.0), (10.0, 100.0)]);
+
+        lookup.call(10.5);
+    }
+
+    #[test]
+    fn try_summation() {
+        let lookup = InterpolateLookup::from(vec![0.0, 1.0, 2.0, 3.0, 4.0]);
+
+        assert_eq!(lookup.try_call(-0.5), None);
+        assert_eq!(lookup.try_call(4.5), None);
+        assert_eq!(lookup.try_call(4.0), Some(4.0));
+
+        let inside: Result<f64, SummationError> = try_trapezoidal(0.0, 4.0, 8, &lookup);
+        let checked: f64 = trapezoidal(0.0, 4.0, 8, &lookup).unwrap();
+
+        assert_eq!(inside, Ok(checked));
+        assert_eq!(
+            try_left_riemann(0.0, 4.0, 8, &lookup),
+            Ok(left_riemann(0.0, 4.0, 8, &lookup).unwrap())
+        );
+        assert_eq!(
+            try_mid_riemann(0.0, 4.0, 8, &lookup),
+            Ok(mid_riemann(0.0, 4.0, 8, &lookup).unwrap())
+        );
+        assert_eq!(
+            try_simpsons(0.0, 4.0, 8, &lookup),
+            Ok(simpsons(0.0, 4.0, 8, &lookup).unwrap())
+        );
+
+        assert_eq!(
+            try_right_riemann::<_, f64>(0.0, 4.5, 9, &lookup),
+            Err(SummationError::OutOfRange)
+        );
+        assert_eq!(
+            try_trapezoidal::<_, f64>(0.0, 4.5, 9, &lookup),
+            Err(SummationError::OutOfRange)
+        );
+        assert_eq!(
+            try_simpsons::<_, f64>(-0.5, 4.0, 9, &lookup),
+            Err(SummationError::OutOfRange)
+        );
+        assert_eq!(
+            try_trapezoidal::<_, f64>(0.0, 4.0, 0, &lookup),
+            Err(SummationError::ZeroIterations)
+        );
+    }
+
+    #[test]
+    fn zero_iterations() {
         let lookup = InterpolateLookup::from(vec![0.0, 1.0, 2.0]);
+        let results = [
+            left_riemann(0.0, 2.0, 0, &lookup),
+            mid_riemann(0.0, 2.0, 0, &lookup),
+            right_riemann(0.0, 2.0, 0, &lookup),
+            trapezoidal(0.0, 2.0, 0, &lookup),
+            simpsons(0.0, 2.0, 0, &lookup),
+            tanh_sinh(0.0, 2.0, 0, &lookup),
+            gauss_legendre(0.0, 2.0, 0, &lookup),
+            left_riemann_compensated(0.0, 2.0, 0, &lookup),
+            mid_riemann_compensated(0.0, 2.0, 0, &lookup),
+            right_riemann_compensated(0.0, 2.0, 0, &lookup),
+            trapezoidal_compensated(0.0, 2.0, 0, &lookup),
+            simpsons_compensated(0.0, 2.0, 0, &lookup),
+            gauss_legendre_compensated(0.0, 2.0, 0, &lookup),
+        ];
 
-        assert_relative_eq!(lookup.call(0.0), 0.0);
-        assert_relative_eq!(lookup.call(1.0), 1.0);
-        assert_relative_eq!(lookup.call(0.5), 0.5);
-        assert_relative_eq!(lookup.call(1.5), 1.5);
+        for result in results {
+            assert_eq!(result, Err(SummationError::ZeroIterations));
+        }
+
+        assert!(tanh_sinh_nodes(0.0, 1.0, 0).is_err());
+        assert_eq!(
+            custom_quadrature::<_, f64>(&GAUSS_LEGENDRE_5, 0.0, 2.0, 0, &lookup),
+            Err(SummationError::ZeroIterations)
+        );
+        assert_eq!(
+            cumulative_panels(lookup.len(), 0, &lookup, trapezoidal).err(),
+            Some(SummationError::ZeroIterations)
+        );
+
+        assert_eq!(
+            sum_panels_rayon(lookup.len(), 0, &lookup, trapezoidal_compensated, false),
+            Err(SummationError::ZeroIterations)
+        );
+
+        let config = SummationConfig {
+            tolerance: 0.0,
+            ..SummationConfig::default()
+        };
+
+        assert_eq!(
+            adaptive_simpson_panel(0.0, 2.0, 1, &config.apply(&lookup)),
+            Err(SummationError::InvalidTolerance)
+        );
+    }
+
+    #[test]
+    fn extrapolation_modes() {
+        let mut lookup = InterpolateLookup::from(vec![1.0, 2.0, 4.0]);
+
+        assert_eq!(lookup.extrapolation(), Extrapolation::Panic);
+        assert_eq!(lookup.try_call(-0.5), None);
+        assert_eq!(lookup.try_call(2.5), None);
+
+        lookup.set_extrapolation(Extrapolation::Clamp);
+
+        assert_eq!(lookup.call(-0.5), 1.0);
+        assert_eq!(lookup.call(2.5), 4.0);
+        assert_eq!(lookup.call(1.5), 3.0);
+
+        lookup.set_extrapolation(Extrapolation::Linear);
+
+        assert_relative_eq!(lookup.call(-0.5), 0.5);
+        assert_relative_eq!(lookup.call(2.5), 5.0);
+        assert_relative_eq!(lookup.call(4.0), 8.0);
+
+        lookup.set_extrapolation(Extrapolation::Zero);
+
+        assert_eq!(lookup.call(-0.5), 0.0);
+        assert_eq!(lookup.call(2.5), 0.0);
+
+        let mut pairs = InterpolateLookup::from_pairs(vec![(0.0, 0.0), (3.0, 9.0), (10.0, 100.0)]);
+        pairs.set_extrapolation(Extrapolation::Linear);
+
+        assert_relative_eq!(pairs.call(17.0), 191.0);
+        assert_relative_eq!(pairs.call(-1.0), -3.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "outside of the lookup table")]
+    fn extrapolation_panic() {
+        let lookup = InterpolateLookup::from(vec![1.0, 2.0, 4.0]);
+
+        lookup.call(2.5);
+    }
+
+    #[test]
+    fn cubic_spline() {
+        // straight lines are reproduced exactly, including with spacing
+        let line = InterpolateLookup::with_spacing(vec![1.0, 3.0, 5.0, 7.0, 9.0], 0.5);
+        let spline = CubicSplineLookup::new(&line);
+
+        for x in [0.0, 0.1, 0.75, 1.3, 2.0] {
+            assert_relative_eq!(spline.call(x), 1.0 + 4.0 * x, epsilon = 1e-12);
+        }
+
+        // a cubic matches at the stored values and is close in between away
+        // from the natural ends
+        let cube = |x: f64| x * x * x - 6.0 * x * x + 3.0 * x;
+        let lookup = InterpolateLookup::from((0..21).map(|x| cube(x as f64)).collect::<Vec<f64>>());
+        let spline = CubicSplineLookup::new(&lookup);
+
+        for x in 0..21 {
+            assert_relative_eq!(spline.call(x as f64), cube(x as f64), epsilon = 1e-9);
+        }
+
+        let linear_error = (lookup.call(10.5) - cube(10.5)).abs();
+        let spline_error = (spline.call(10.5) - cube(10.5)).abs();
+
+        assert!(spline_error < linear_error / 100.0);
+        assert_eq!(spline.try_call(20.5), None);
+
+        let pairs = InterpolateLookup::from_pairs(vec![(0.0, 0.0), (3.0, 9.0), (10.0, 100.0)]);
+
+        assert_eq!(CubicSplineLookup::new(&pairs).call(3.0), 9.0);
+    }
+
+    #[test]
+    fn gauss_legendre_parabola() {
+        let parabola = |x: f64| 4.0 * x - x * x;
+
+        let result: f64 = gauss_legendre(0.0, 4.0, 1, &parabola).unwrap();
+        let compensated = gauss_legendre_compensated(0.0, 4.0, 3, &parabola).unwrap();
+
+        assert_relative_eq!(result, 32.0 / 3.0, epsilon = 1e-12);
+        assert_relative_eq!(compensated, 32.0 / 3.0, epsilon = 1e-12);
+
+        // degree 9 is still exact on a single step
+        let ninth: f64 = gauss_legendre(0.0, 1.0, 1, &|x: f64| x.powi(9)).unwrap();
+
+        assert_relative_eq!(ninth, 0.1, epsilon = 1e-12);
+    }
+
+    #[test]
+    fn adaptive_simpson_sharp() {
+        // a narrow peak that a fixed step mostly steps over
+        let peak = |x: f64| 1.0 / (1e-4 + (x - 0.3) * (x - 0.3));
+        let exact = 100.0 * ((0.7f64 / 0.01).atan() + (0.3f64 / 0.01).atan());
+
+        let fixed: f64 = simpsons(0.0, 1.0, 10, &peak).unwrap();
+        let adaptive = adaptive_simpson(0.0, 1.0, 1e-8, &peak);
+
+        assert!((fixed - exact).abs() > 1.0);
+        assert_relative_eq!(adaptive, exact, epsilon = 1e-6);
+
+        // the depth cap keeps a jump from subdividing forever
+        let jump = |x: f64| if x < 1.0 / 3.0 { 0.0 } else { 1.0 };
+
+        assert_relative_eq!(
+            adaptive_simpson(0.0, 1.0, 0.0, &jump),
+            2.0 / 3.0,
+            epsilon = 1e-9
+        );
+    }
+
+    #[test]
+    fn romberg_convergence() {
+        let exact = std::f64::consts::E - 1.0;
+        let error = |levels: u32| {
+            let result: f64 = romberg(0.0, 1.0, levels, &|x: f64| x.exp()).unwrap();
+
+            (result - exact).abs()
+        };
+
+        // no levels is a single trapezoid and one level is simpsons
+        assert_relative_eq!(
+            romberg::<_, f64>(0.0, 1.0, 0, &|x: f64| x.exp()).unwrap(),
+            trapezoidal::<_, f64>(0.0, 1.0, 1, &|x: f64| x.exp()).unwrap()
+        );
+        assert_relative_eq!(
+            romberg::<_, f64>(0.0, 1.0, 1, &|x: f64| x.exp()).unwrap(),
+            simpsons::<_, f64>(0.0, 1.0, 2, &|x: f64| x.exp()).unwrap(),
+            epsilon = 1e-15
+        );
+
+        // each level removes another even power of h from the error so it
+        // should shrink by at least 4^(level + 1) from the step halving
+        for levels in 1..4 {
+            let ratio = error(levels) / error(levels + 1);
+
+            assert!(ratio > 4f64.powi(levels as i32 + 1), "{levels}: {ratio}");
+        }
+
+        assert_relative_eq!(
+            romberg_compensated(0.0, 1.0, 4, &|x: f64| x.exp()).unwrap(),
+            exact,
+            epsilon = 1e-12
+        );
+    }
+
+    #[test]
+    fn configured_panels() {
+        let curve = |x: f64| x.exp();
+        let config = SummationConfig {
+            tolerance: 1e-3,
+            levels: 2,
+            ..SummationConfig::default()
+        };
+        let configured = config.apply(&curve);
+
+        assert_eq!(
+            romberg_panel::<_, f64>(0.0, 1.0, 100, &configured).unwrap(),
+            romberg::<_, f64>(0.0, 1.0, 2, &curve).unwrap()
+        );
+        assert_eq!(
+            romberg_panel::<_, f64>(0.0, 1.0, 100, &curve).unwrap(),
+            romberg::<_, f64>(0.0, 1.0, DEFAULT_ROMBERG_LEVELS, &curve).unwrap()
+        );
+        assert_eq!(
+            romberg_panel_compensated(0.0, 1.0, 100, &configured).unwrap(),
+            romberg_compensated(0.0, 1.0, 2, &curve).unwrap()
+        );
+        assert_eq!(
+            adaptive_simpson_panel(0.0, 1.0, 100, &configured).unwrap(),
+            adaptive_simpson(0.0, 1.0, 1e-3, &curve)
+        );
+    }
+
+    #[test]
+    fn l2_norm_levels() {
+        let accel = InterpolateLookup::from(vec![0.0, 1.0, 4.0, 9.0]);
+        let levels = |levels: u32| SummationConfig {
+            levels,
+            ..SummationConfig::default()
+        };
+
+        // no refinements leaves a single trapezoid on each second
+        assert_eq!(
+            l2_norm(accel.len(), 1, &levels(0).apply(&accel), romberg_panel).unwrap(),
+            l2_norm(accel.len(), 1, &accel, trapezoidal).unwrap()
+        );
+        assert_ne!(
+            l2_norm(accel.len(), 1, &levels(6).apply(&accel), romberg_panel).unwrap(),
+            l2_norm(accel.len(), 1, &levels(0).apply(&accel), romberg_panel).unwrap()
+        );
+
+        // the levels pass through the wrappers of the configured callable
+        let configured = levels(3).apply(&accel);
+        let masked = MaskedCallable::new(&configured, &[true, false, true]);
+        let shifted = ShiftedCallable::new(&masked, 0.5, ShiftFill::Zero);
+        let product = ProductCallable::new(&shifted, &accel);
+
+        assert_eq!(
+            AbsCallable::new(&SquaredCallable::new(&product)).romberg_levels(),
+            3
+        );
+    }
+
+    #[test]
+    fn l2_norm_tolerance() {
+        let curve = |x: f64| x.exp();
+        let tolerance = |tolerance: f64| SummationConfig {
+            tolerance,
+            ..SummationConfig::default()
+        };
+        let squared = |x: f64| x.exp() * x.exp();
+        let expected = |tol: f64| {
+            (adaptive_simpson(0.0, 1.0, tol, &squared) + adaptive_simpson(1.0, 2.0, tol, &squared))
+                .sqrt()
+        };
+
+        // the squared callable of the norm keeps the tolerance of the
+        // configured callable that it wraps
+        let loose = l2_norm(3, 1, &tolerance(1e-1).apply(&curve), adaptive_simpson_panel).unwrap();
+        let tight = l2_norm(
+            3,
+            1,
+            &tolerance(1e-12).apply(&curve),
+            adaptive_simpson_panel,
+        )
+        .unwrap();
+
+        assert_eq!(loose, expected(1e-1));
+        assert_eq!(tight, expected(1e-12));
+        assert_ne!(loose, tight);
+
+        let configured = tolerance(1e-9).apply(&curve);
+        let masked = MaskedCallable::new(&configured, &[true, false, true]);
+        let shifted = ShiftedCallable::new(&masked, 0.5, ShiftFill::Zero);
+        let product = ProductCallable::new(&shifted, &curve);
+
+        assert_eq!(
+            AbsCallable::new(&SquaredCallable::new(&product)).adaptive_tolerance(),
+            1e-9
+        );
+    }
+
+    #[test]
+    fn simpsons_odd() {
+        // simpsons is exact for cubics with any amount of steps since the odd
+        // steps end with the 3/8 rule
+        let cubic = |x: f64| x * x * x - 2.0 * x;
+        let exact = 81.0 / 4.0 - 9.0;
+
+        for iterations in [2, 3, 4, 5, 7, 9] {
+            let calc: f64 = simpsons(0.0, 3.0, iterations, &cubic).unwrap();
+
+            assert_relative_eq!(calc, exact, epsilon = 1e-12);
+            assert_relative_eq!(
+                simpsons_compensated(0.0, 3.0, iterations, &cubic).unwrap(),
+                exact,
+                epsilon = 1e-12
+            );
+        }
+
+        // a single step is a trapezoid
+        assert_relative_eq!(
+            simpsons::<_, f64>(0.0, 3.0, 1, &cubic).unwrap(),
+            trapezoidal::<_, f64>(0.0, 3.0, 1, &cubic).unwrap()
+        );
+
+        // the weights always add up to the full interval
+        for iterations in 1..10 {
+            let total = (0..=iterations)
+                .map(|iter| simpsons_weight(iter, iterations))
+                .sum::<f64>();
+
+            assert_relative_eq!(total, 3.0 * iterations as f64, epsilon = 1e-12);
+        }
     }
 }
@@ -2,18 +2,25 @@
 
 use std::default::Default;
 use std::fmt::{Display, Formatter, Result as FmtResult};
+use std::path::PathBuf;
 use std::time::{Duration, Instant};
 
+use anyhow::Context;
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+
 /// collects timing information for convience
 ///
-/// tracks the minimum, maximum, total, and count of the values provided to the
-/// [`Timing::update`] function. can also be [`Display`]ed to show the minimum,
-/// maximum, average, and total time values stored.
+/// tracks the minimum, maximum, total, count, and standard deviation of the
+/// values provided to the [`Timing::update`] function. can also be
+/// [`Display`]ed to show the minimum, maximum, average, standard deviation,
+/// and total time values stored.
 ///
 /// if the total timing information collected is only 1 then it will only
 /// display the total time as all the values will be the same.
 ///
 /// ```
+/// use train_sim::time::Timing;
+///
 /// let mut timing = Timing::default();
 ///
 /// for _ in 0..10 {
@@ -31,11 +38,34 @@ pub struct Timing {
     max: Duration,
     total: Duration,
     counted: u32,
+    mean_nanos: f64,
+    m2_nanos: f64,
+    samples: Vec<Duration>,
 }
 
+/// the most individual durations that a [`Timing`] will keep for percentiles
+pub const MAX_SAMPLES: usize = 1 << 16;
+
 impl Timing {
+    /// creates a timing that keeps up to the given amount of individual
+    /// durations so that percentiles can be calculated
+    ///
+    /// the capacity is limited to [`MAX_SAMPLES`] and is allocated up front.
+    /// any durations past the capacity are only included in the min, max,
+    /// total, and count
+    pub fn with_samples(capacity: usize) -> Self {
+        Self {
+            samples: Vec::with_capacity(capacity.min(MAX_SAMPLES)),
+            ..Default::default()
+        }
+    }
+
     /// updates tracked values with the given duration
     pub fn update(&mut self, given: Duration) {
+        if self.samples.len() < self.samples.capacity() {
+            self.samples.push(given);
+        }
+
         if self.min > given {
             self.min = given;
         }
@@ -46,7 +76,117 @@ impl Timing {
 
         self.total += given;
         self.counted += 1;
+
+        // welford's online algorithm so that the squares do not overflow
+        let nanos = given.as_nanos() as f64;
+        let delta = nanos - self.mean_nanos;
+        self.mean_nanos += delta / self.counted as f64;
+        self.m2_nanos += delta * (nanos - self.mean_nanos);
+    }
+
+    /// returns the average of all the durations provided
+    ///
+    /// if no durations have been provided then zero is returned
+    pub fn average(&self) -> Duration {
+        if self.counted == 0 {
+            Duration::ZERO
+        } else {
+            self.total / self.counted
+        }
+    }
+
+    /// returns the population standard deviation of all the durations
+    /// provided
+    ///
+    /// if no durations have been provided then zero is returned
+    pub fn std_dev(&self) -> Duration {
+        if self.counted == 0 {
+            Duration::ZERO
+        } else {
+            Duration::from_nanos((self.m2_nanos / self.counted as f64).sqrt().round() as u64)
+        }
+    }
+
+    /// returns the smallest duration provided
+    ///
+    /// if no durations have been provided then zero is returned
+    pub fn min(&self) -> Duration {
+        if self.counted == 0 {
+            Duration::ZERO
+        } else {
+            self.min
+        }
+    }
+
+    /// returns the largest duration provided
+    pub fn max(&self) -> Duration {
+        self.max
+    }
+
+    /// returns the sum of all the durations provided
+    pub fn total(&self) -> Duration {
+        self.total
+    }
+
+    /// returns the amount of durations provided
+    pub fn count(&self) -> u32 {
+        self.counted
+    }
+
+    /// returns the amount of individual durations kept for percentiles
+    pub fn samples(&self) -> usize {
+        self.samples.len()
+    }
+
+    /// returns the duration at the given percentile of the kept durations
+    /// using the nearest rank
+    ///
+    /// if no durations have been kept then zero is returned
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use train_sim::time::Timing;
+    ///
+    /// let mut timing = Timing::with_samples(4);
+    ///
+    /// for millis in [4, 1, 3, 2] {
+    ///     timing.update(Duration::from_millis(millis));
+    /// }
+    ///
+    /// assert_eq!(timing.percentile(50.0), Duration::from_millis(2));
+    /// assert_eq!(timing.percentile(100.0), Duration::from_millis(4));
+    /// ```
+    pub fn percentile(&self, given: f64) -> Duration {
+        if self.samples.is_empty() {
+            return Duration::ZERO;
+        }
+
+        let mut sorted = self.samples.clone();
+        sorted.sort_unstable();
+
+        let rank = (given.clamp(0.0, 100.0) / 100.0 * sorted.len() as f64).ceil() as usize;
+
+        sorted[rank.clamp(1, sorted.len()) - 1]
     }
+
+    /// formats the tracked values as a csv header row and a single data row
+    ///
+    /// all durations are in seconds
+    pub fn to_csv(&self) -> String {
+        format!(
+            "min,max,avg,total,count\n{},{},{},{},{}\n",
+            secs(self.min()),
+            secs(self.max),
+            secs(self.average()),
+            secs(self.total),
+            self.counted,
+        )
+    }
+}
+
+/// formats the given duration as seconds with nanosecond precision
+fn secs(given: Duration) -> String {
+    format!("{}.{:09}", given.as_secs(), given.subsec_nanos())
 }
 
 impl Default for Timing {
@@ -56,6 +196,9 @@ impl Default for Timing {
             max: Duration::ZERO,
             total: Duration::ZERO,
             counted: 0,
+            mean_nanos: 0.0,
+            m2_nanos: 0.0,
+            samples: Vec::new(),
         }
     }
 }
@@ -63,37 +206,50 @@ impl Default for Timing {
 impl Display for Timing {
     fn fmt(&self, f: &mut Formatter<'_>) -> FmtResult {
         if self.counted > 1 {
-            let avg = self.total / self.counted;
-
-            write!(
-                f,
-                "min: {}.{:09}\nmax: {}.{:09}\navg: {}.{:09}\ntot: {}.{:09}",
-                self.min.as_secs(),
-                self.min.subsec_nanos(),
-                self.max.as_secs(),
-                self.max.subsec_nanos(),
-                avg.as_secs(),
-                avg.subsec_nanos(),
-                self.total.as_secs(),
-                self.total.subsec_nanos(),
-            )
+            writeln!(f, "min: {}", secs(self.min))?;
+            writeln!(f, "max: {}", secs(self.max))?;
+            writeln!(f, "avg: {}", secs(self.average()))?;
+            writeln!(f, "std: {}", secs(self.std_dev()))?;
+
+            if !self.samples.is_empty() {
+                for (name, percentile) in [("p50", 50.0), ("p90", 90.0), ("p99", 99.0)] {
+                    writeln!(f, "{name}: {}", secs(self.percentile(percentile)))?;
+                }
+            }
+
+            write!(f, "tot: {}", secs(self.total))
         } else {
-            write!(
-                f,
-                "total: {}.{:09}",
-                self.total.as_secs(),
-                self.total.subsec_nanos()
-            )
+            write!(f, "total: {}", secs(self.total))
         }
     }
 }
 
+/// serializes the min, max, average, standard deviation, and total durations
+/// in nanoseconds along with the count
+impl Serialize for Timing {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut state = serializer.serialize_struct("Timing", 6)?;
+        state.serialize_field("min_nanos", &self.min().as_nanos())?;
+        state.serialize_field("max_nanos", &self.max.as_nanos())?;
+        state.serialize_field("avg_nanos", &self.average().as_nanos())?;
+        state.serialize_field("std_nanos", &self.std_dev().as_nanos())?;
+        state.serialize_field("total_nanos", &self.total.as_nanos())?;
+        state.serialize_field("count", &self.counted)?;
+        state.end()
+    }
+}
+
 /// timer that will indicate if a certain amout of time has passed since the
 /// previously stored value
 ///
 /// by default, the duration of time that can pass is 10 seconds
 ///
 /// ```
+/// use train_sim::time::LogTimer;
+///
 /// let mut timer = LogTimer::default();
 ///
 /// // do work of some kind
@@ -116,12 +272,22 @@ pub struct LogTimer {
 }
 
 impl LogTimer {
+    /// creates a timer that indicates when the given duration has passed
+    ///
+    /// a zero duration will indicate on every update
+    pub fn with_duration(drtn: Duration) -> Self {
+        Self {
+            last: Instant::now(),
+            drtn,
+        }
+    }
+
     /// checks the current timestamp with the internal time and updates if the
-    /// difference is greater than the specified duration
+    /// difference is at least the specified duration
     pub fn update(&mut self) -> bool {
         let now = Instant::now();
 
-        if now - self.last > self.drtn {
+        if now - self.last >= self.drtn {
             self.last = now;
 
             true
@@ -133,9 +299,182 @@ impl LogTimer {
 
 impl Default for LogTimer {
     fn default() -> Self {
-        Self {
-            last: Instant::now(),
-            drtn: Duration::from_secs(10),
+        Self::with_duration(Duration::from_secs(10))
+    }
+}
+
+/// persists [`Timing`] information to a file after a set amount of iterations
+///
+/// the file will contain the csv output of [`Timing::to_csv`] and is
+/// overwritten every time it is updated
+///
+/// ```no_run
+/// use train_sim::time::{IterCheckpoint, Timing};
+///
+/// let checkpoint = IterCheckpoint::new("timing.csv".into(), 10);
+/// let mut timing = Timing::default();
+///
+/// for iter in 0..100 {
+///     let start = std::time::Instant::now();
+///
+///     // do something
+///
+///     timing.update(start.elapsed());
+///     checkpoint.update(iter, &timing).unwrap();
+/// }
+/// ```
+pub struct IterCheckpoint {
+    /// the file path to write the timing information to
+    path: PathBuf,
+    /// the amount of iterations between each write
+    every: u32,
+}
+
+impl IterCheckpoint {
+    /// creates a new checkpoint that writes to the given path every specified
+    /// amount of iterations
+    ///
+    /// panics if every is zero
+    pub fn new(path: PathBuf, every: u32) -> Self {
+        assert_ne!(every, 0);
+
+        Self { path, every }
+    }
+
+    /// writes the timing information if the given zero based iteration is on
+    /// the checkpoint cadence. returns true if the file was written
+    pub fn update(&self, iter: u32, timing: &Timing) -> anyhow::Result<bool> {
+        if !(iter + 1).is_multiple_of(self.every) {
+            return Ok(false);
         }
+
+        std::fs::write(&self.path, timing.to_csv()).context("failed to write timing checkpoint")?;
+
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn timing_csv() {
+        let mut timing = Timing::default();
+        timing.update(Duration::from_millis(1));
+        timing.update(Duration::from_millis(3));
+
+        let csv = timing.to_csv();
+        let mut lines = csv.lines();
+
+        assert_eq!(lines.next(), Some("min,max,avg,total,count"));
+
+        let row = lines.next().unwrap();
+        let values = row
+            .split(',')
+            .map(|v| v.parse::<f64>().unwrap())
+            .collect::<Vec<f64>>();
+
+        assert_eq!(values, vec![0.001, 0.003, 0.002, 0.004, 2.0]);
+        assert_eq!(lines.next(), None);
+    }
+
+    #[test]
+    fn timing_json() {
+        let mut timing = Timing::default();
+        timing.update(Duration::from_millis(1));
+        timing.update(Duration::from_millis(3));
+
+        let json = serde_json::to_value(&timing).unwrap();
+
+        assert_eq!(
+            json,
+            serde_json::json!({
+                "min_nanos": 1_000_000,
+                "max_nanos": 3_000_000,
+                "avg_nanos": 2_000_000,
+                "std_nanos": 1_000_000,
+                "total_nanos": 4_000_000,
+                "count": 2,
+            })
+        );
+    }
+
+    #[test]
+    fn std_dev() {
+        let mut timing = Timing::default();
+
+        assert_eq!(timing.std_dev(), Duration::ZERO);
+
+        // mean of 5 with squared deviations summing to 32
+        for millis in [2, 4, 4, 4, 5, 5, 7, 9] {
+            timing.update(Duration::from_millis(millis));
+        }
+
+        assert_eq!(timing.std_dev(), Duration::from_millis(2));
+    }
+
+    #[test]
+    fn percentiles() {
+        let mut timing = Timing::with_samples(100);
+
+        // added out of order so that the sorting is checked
+        for millis in (1..=100).rev() {
+            timing.update(Duration::from_millis(millis));
+        }
+
+        assert_eq!(timing.samples(), 100);
+        assert_eq!(timing.percentile(0.0), Duration::from_millis(1));
+        assert_eq!(timing.percentile(50.0), Duration::from_millis(50));
+        assert_eq!(timing.percentile(90.0), Duration::from_millis(90));
+        assert_eq!(timing.percentile(99.0), Duration::from_millis(99));
+
+        // durations past the capacity are not kept
+        timing.update(Duration::from_secs(1));
+
+        assert_eq!(timing.samples(), 100);
+        assert_eq!(timing.max(), Duration::from_secs(1));
+        assert_eq!(timing.percentile(100.0), Duration::from_millis(100));
+        assert_eq!(Timing::default().percentile(50.0), Duration::ZERO);
+    }
+
+    #[test]
+    fn log_interval() {
+        let mut every = LogTimer::with_duration(Duration::ZERO);
+        let mut never = LogTimer::with_duration(Duration::MAX);
+
+        for _ in 0..3 {
+            assert!(every.update());
+            assert!(!never.update());
+        }
+    }
+
+    #[test]
+    fn checkpoint_iters() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("checkpoint.csv");
+        let checkpoint = IterCheckpoint::new(path.clone(), 3);
+        let mut timing = Timing::default();
+        let mut written = Vec::new();
+
+        for iter in 0..10 {
+            timing.update(Duration::from_millis(1));
+
+            if checkpoint.update(iter, &timing).unwrap() {
+                let contents = std::fs::read_to_string(&path).unwrap();
+                let count = contents.lines().nth(1).unwrap().rsplit(',').next().unwrap();
+
+                written.push((iter, count.to_owned()));
+            }
+        }
+
+        assert_eq!(
+            written,
+            vec![
+                (2, String::from("3")),
+                (5, String::from("6")),
+                (8, String::from("9"))
+            ]
+        );
     }
 }